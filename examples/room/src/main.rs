@@ -397,7 +397,7 @@ pub fn main() {
     // Select first display
     let display = displays.get(0).unwrap();
 
-    let display_data = display.borrow().data();
+    let display_data = display.read().unwrap().data();
     println!("VRDisplay: {:?}", display_data);
 
     let render_width = display_data.left_eye_parameters.render_width;
@@ -488,9 +488,9 @@ pub fn main() {
     let test_pose = false; 
 
     loop {
-        display.borrow_mut().sync_poses();
+        display.read().unwrap().sync_poses();
 
-        let display_data = display.borrow().data();
+        let display_data = display.read().unwrap().data();
         if let Some(ref stage) = display_data.stage_parameters {
             // TODO: use event queue instead of checking this every frame
             standing_transform = vec_to_matrix(&stage.sitting_to_standing_transform).inverse_transform().unwrap();
@@ -500,7 +500,7 @@ pub fn main() {
         gl.clear_color(1.0, 0.0, 0.0, 1.0);
         gl.clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
 
-        let data: VRFrameData = display.borrow().synced_frame_data(near, far);
+        let data: VRFrameData = display.read().unwrap().synced_frame_data(near, far);
 
         let (left_view_matrix, right_view_matrix) = if test_pose {
              // Calculate view transform based on pose data
@@ -555,7 +555,7 @@ pub fn main() {
             texture_id: target_texture,
             .. Default::default()
         };
-        display.borrow_mut().submit_frame(&layer);
+        display.write().unwrap().submit_frame(&layer);
 
         // render to desktop display
         gl.bind_framebuffer(gl::FRAMEBUFFER, screen_fbo);
@@ -0,0 +1,43 @@
+#![cfg(feature = "googlevr")]
+
+#[cfg(target_os="android")]
+use android_injected_glue::ffi as ndk;
+use gvr_sys as gvr;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+pub type GoogleVRContextPtr = Arc<Mutex<GoogleVRContext>>;
+
+// GVR contexts shared by the service, its display and gamepad.
+// GVR and the GL calls made while presenting are not thread safe, so every call
+// is made while holding the lock of the GoogleVRContextPtr.
+pub struct GoogleVRContext {
+    pub ctx: *mut gvr::gvr_context,
+    pub controller_ctx: *mut gvr::gvr_controller_context,
+    // Global references to the GVRService Java object, valid in any thread
+    #[cfg(target_os="android")]
+    pub java_object: ndk::jobject,
+    #[cfg(target_os="android")]
+    pub java_class: ndk::jclass,
+}
+
+// The contexts are not bound to the thread that created them, so they can be moved
+// to other threads. Sync is provided by the Mutex of GoogleVRContextPtr, which
+// serializes the calls.
+unsafe impl Send for GoogleVRContext {}
+
+impl Drop for GoogleVRContext {
+    fn drop(&mut self) {
+        if !self.controller_ctx.is_null() {
+            unsafe {
+                gvr::gvr_controller_destroy(mem::transmute(&self.controller_ctx));
+            }
+        }
+
+        if !self.ctx.is_null() {
+            unsafe {
+                gvr::gvr_destroy(mem::transmute(&self.ctx));
+            }
+        }
+    }
+}
@@ -1,7 +1,7 @@
 #![cfg(feature = "googlevr")]
 use {VRDisplay, VRDisplayData, VRDisplayCapabilities,
-    VREvent, VRDisplayEvent, VREyeParameters, VRFrameData, VRLayer};
-use super::service::GoogleVRService;
+    VREvent, VRDisplayEvent, VREyeParameters, VRFrameData, VRFrameStats, VRLayer, VRReferenceSpace};
+use super::context::{GoogleVRContext, GoogleVRContextPtr};
 use super::super::utils;
 #[cfg(target_os="android")]
 use super::jni_utils::JNIScope;
@@ -10,41 +10,52 @@ use gvr_sys as gvr;
 use gvr_sys::gvr_color_format_type::*;
 use gvr_sys::gvr_depth_stencil_format_type::*;
 use std::ffi::CStr;
-use std::sync::Arc;
-use std::cell::RefCell;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::ptr;
 use std::mem;
-use std::sync::Mutex;
 
-pub type GoogleVRDisplayPtr = Arc<RefCell<GoogleVRDisplay>>;
+pub type GoogleVRDisplayPtr = Arc<RwLock<GoogleVRDisplay>>;
 
 // 50ms is a good estimate recommended by the GVR Team.
 // It takes in account the time between frame submission (without vsync) and 
 // when the rendered image is sent to the physical pixels on the display.
 const PREDICTION_OFFSET_NANOS: i64 = 50000000; // 50ms
 
+// GVR doesn't expose the refresh rate. Daydream ready devices refresh the display at 60Hz.
+const REFRESH_RATE: f64 = 60.0;
+
 pub struct GoogleVRDisplay {
-    service: *const GoogleVRService,
-    ctx: *mut gvr::gvr_context,
+    context: GoogleVRContextPtr,
+    // Render state, updated by sync_poses through a shared reference. Locked after the context.
+    frame_state: Mutex<GoogleVRFrameState>,
+    synced_head_matrix: Mutex<gvr::gvr_mat4f>,
+    display_id: u32,
+    presenting: AtomicBool,
+    paused: AtomicBool,
+    new_events_hint: AtomicBool,
+    pending_events: Mutex<Vec<VREvent>>,
+    processed_events: Mutex<Vec<VREvent>>,
+    reference_space: VRReferenceSpace
+}
+
+struct GoogleVRFrameState {
     viewport_list: *mut gvr::gvr_buffer_viewport_list,
     left_eye_vp: *mut gvr::gvr_buffer_viewport,
     right_eye_vp: *mut gvr::gvr_buffer_viewport,
     render_size: gvr::gvr_sizei,
     swap_chain: *mut gvr::gvr_swap_chain,
     frame: *mut gvr::gvr_frame,
-    synced_head_matrix: gvr::gvr_mat4f,
     fbo_id: u32,
     fbo_texture: u32,
-    display_id: u32,
-    presenting: bool,
-    paused: bool,
-    new_events_hint: bool,
-    pending_events: Mutex<Vec<VREvent>>,
-    processed_events: Mutex<Vec<VREvent>>
+    // GVR doesn't report frame timing, so it's measured in sync_poses and submit_frame
+    stats: VRFrameStats,
+    // Time in milliseconds when the last sync_poses call acquired a frame
+    frame_start: Option<f64>
 }
 
-unsafe impl Send for GoogleVRDisplay {}
-unsafe impl Sync for GoogleVRDisplay {}
+// The GVR objects of the display are only used while the GoogleVRContext is locked
+unsafe impl Send for GoogleVRFrameState {}
 
 impl VRDisplay for GoogleVRDisplay {
 
@@ -54,9 +65,11 @@ impl VRDisplay for GoogleVRDisplay {
 
     fn data(&self) -> VRDisplayData {
         let mut data = VRDisplayData::default();
+        let context = self.context.lock().unwrap();
+        let state = self.frame_state.lock().unwrap();
 
         let (vendor, model) = unsafe {
-            (to_string(gvr::gvr_get_viewer_vendor(self.ctx)), to_string(gvr::gvr_get_viewer_model(self.ctx)))
+            (to_string(gvr::gvr_get_viewer_vendor(context.ctx)), to_string(gvr::gvr_get_viewer_model(context.ctx)))
         };
         if vendor.is_empty() {
             data.display_name = model;
@@ -68,7 +81,7 @@ impl VRDisplay for GoogleVRDisplay {
     
         self.fetch_capabilities(&mut data.capabilities);
         unsafe {
-            self.fetch_eye_parameters(&mut data.left_eye_parameters, &mut data.right_eye_parameters);
+            self.fetch_eye_parameters(&context, &state, &mut data.left_eye_parameters, &mut data.right_eye_parameters);
         }
         data.stage_parameters = None;
 
@@ -77,10 +90,12 @@ impl VRDisplay for GoogleVRDisplay {
 
     fn inmediate_frame_data(&self, near: f64, far: f64) -> VRFrameData {
         let mut data = VRFrameData::default();
+        let context = self.context.lock().unwrap();
+        let state = self.frame_state.lock().unwrap();
         unsafe {
             let time = gvr::gvr_get_time_point_now();
-            let head_matrix = self.fetch_head_matrix(&time);
-            self.fetch_frame_data(&mut data, &head_matrix, near as f32, far as f32);
+            let head_matrix = self.fetch_head_matrix(&context, &time);
+            self.fetch_frame_data(&context, &state, &mut data, &head_matrix, near as f32, far as f32);
         };
         
         data
@@ -88,65 +103,109 @@ impl VRDisplay for GoogleVRDisplay {
 
     fn synced_frame_data(&self, near: f64, far: f64) -> VRFrameData {
         let mut data = VRFrameData::default();
-        self.fetch_frame_data(&mut data, &self.synced_head_matrix, near as f32, far as f32);
+        let head_matrix = *self.synced_head_matrix.lock().unwrap();
+        let context = self.context.lock().unwrap();
+        let state = self.frame_state.lock().unwrap();
+        self.fetch_frame_data(&context, &state, &mut data, &head_matrix, near as f32, far as f32);
         
         data
     }
 
+    fn predicted_frame_data(&self, near: f64, far: f64, target_time: f64) -> VRFrameData {
+        let mut data = VRFrameData::default();
+        let context = self.context.lock().unwrap();
+        let state = self.frame_state.lock().unwrap();
+        unsafe {
+            // GVR predicts the head pose for a time point of its own clock
+            let mut time = gvr::gvr_get_time_point_now();
+            time.monotonic_system_time_nanos += ((target_time - utils::timestamp()) * 1e6) as i64;
+            let head_matrix = self.fetch_head_matrix(&context, &time);
+            self.fetch_frame_data(&context, &state, &mut data, &head_matrix, near as f32, far as f32);
+        };
+        data.timestamp = target_time;
+
+        data
+    }
+
     fn reset_pose(&mut self) {
         // On the Daydream platform, recentering is handled automatically and should never
         // be triggered programatically by applications. Hybrid apps that support both
         // Only call this function when in Cardboard mode
+        let context = self.context.lock().unwrap();
         unsafe {
-            if gvr::gvr_get_viewer_type(self.ctx) == gvr::gvr_viewer_type::GVR_VIEWER_TYPE_CARDBOARD as i32 {
-                gvr::gvr_reset_tracking(self.ctx);
+            if gvr::gvr_get_viewer_type(context.ctx) == gvr::gvr_viewer_type::GVR_VIEWER_TYPE_CARDBOARD as i32 {
+                gvr::gvr_reset_tracking(context.ctx);
             }
         }
     }
 
-    fn sync_poses(&mut self) {
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+
+    fn sync_poses(&self) {
         self.handle_events();
-        if !self.presenting {
-            self.start_present();
+        if !self.presenting.load(Ordering::SeqCst) {
+            self.set_presenting(true);
         }
-        if self.swap_chain.is_null() {
+        let context = self.context.lock().unwrap();
+        let mut state = self.frame_state.lock().unwrap();
+        if state.swap_chain.is_null() {
             unsafe {
-                self.initialize_gl();
-                debug_assert!(!self.swap_chain.is_null());
+                self.initialize_gl(&context, &mut state);
+                debug_assert!(!state.swap_chain.is_null());
             }
         }
 
         unsafe {
-            if !self.frame.is_null() {
+            if !state.frame.is_null() {
                 warn!("submit_frame not called");
                 // Release acquired frame if the user has not called submit_Frame()
-                gvr::gvr_frame_submit(mem::transmute(&self.frame), self.viewport_list, self.synced_head_matrix);
+                let head_matrix = *self.synced_head_matrix.lock().unwrap();
+                gvr::gvr_frame_submit(mem::transmute(&state.frame), state.viewport_list, head_matrix);
             }
 
-            gvr::gvr_get_recommended_buffer_viewports(self.ctx, self.viewport_list);
+            gvr::gvr_get_recommended_buffer_viewports(context.ctx, state.viewport_list);
             // Handle resize
-            let size = self.recommended_render_size();
-            if size.width != self.render_size.width || size.height != self.render_size.height {
-                gvr::gvr_swap_chain_resize_buffer(self.swap_chain, 0, size);
-                self.render_size = size;
+            let size = GoogleVRDisplay::recommended_render_size(&context);
+            if size.width != state.render_size.width || size.height != state.render_size.height {
+                gvr::gvr_swap_chain_resize_buffer(state.swap_chain, 0, size);
+                state.render_size = size;
             }
 
-            self.frame = gvr::gvr_swap_chain_acquire_frame(self.swap_chain);
+            state.frame = gvr::gvr_swap_chain_acquire_frame(state.swap_chain);
+        }
+
+        let now = utils::timestamp();
+        state.stats.frame_index += 1;
+        if let Some(start) = state.frame_start {
+            let interval = now - start;
+            state.stats.frame_interval = interval;
+            // Refreshes elapsed since the previous frame, only one of them presented a new frame
+            let refreshes = (interval * REFRESH_RATE / 1000.0).round() as u64;
+            state.stats.missed_frames += refreshes.saturating_sub(1);
         }
+        state.frame_start = Some(now);
 
         // Predict head matrix
         let mut time = unsafe { gvr::gvr_get_time_point_now() };
         time.monotonic_system_time_nanos += PREDICTION_OFFSET_NANOS;
-        self.synced_head_matrix = self.fetch_head_matrix(&time);
+        *self.synced_head_matrix.lock().unwrap() = self.fetch_head_matrix(&context, &time);
         //println!("sync_poses");
     }
 
     fn submit_frame(&mut self, layer: &VRLayer) {
-        if self.frame.is_null() {
+        let _context = self.context.lock().unwrap();
+        let mut state = self.frame_state.lock().unwrap();
+        if state.frame.is_null() {
             warn!("null frame with context");
             return;
         }
-        debug_assert!(self.fbo_id > 0);
+        debug_assert!(state.fbo_id > 0);
         //println!("submit_frame");
 
         unsafe {
@@ -154,144 +213,147 @@ impl VRDisplay for GoogleVRDisplay {
             let mut current_fbo = 0;
             gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut current_fbo);
 
-            if self.fbo_texture != layer.texture_id {
-                // Attach external texture to the used later in BlitFramebuffer.
-                gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo_id);
-                gl::FramebufferTexture2D(gl::FRAMEBUFFER,
-                                        gl::COLOR_ATTACHMENT0,
-                                        gl::TEXTURE_2D,
-                                        layer.texture_id, 0);
-                self.fbo_texture = layer.texture_id;
-            }
-
             let texture_size = layer.texture_size.unwrap_or_else(|| {
-                (self.render_size.width as u32, self.render_size.height as u32)
+                (state.render_size.width as u32, state.render_size.height as u32)
             });
-
-            // BlitFramebuffer: external texture to gvr pixel buffer
-            gvr::gvr_frame_bind_buffer(self.frame, 0);
-            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo_id);
-            gl::BlitFramebuffer(0, 0, texture_size.0 as i32, texture_size.1 as i32,
-                                0, 0, self.render_size.width, self.render_size.height,
-                                gl::COLOR_BUFFER_BIT, gl::LINEAR);
-            gvr::gvr_frame_unbind(self.frame);
+            let width = state.render_size.width;
+
+            // BlitFramebuffer: external textures to gvr pixel buffer
+            gvr::gvr_frame_bind_buffer(state.frame, 0);
+            let (left_bounds, right_bounds) = match layer.right_texture_id {
+                None => {
+                    // Side by side texture: the pixel buffer is a scaled copy of the texture
+                    state.blit_texture(layer.texture_id, texture_size, 0, width);
+                    (layer.left_bounds, layer.right_bounds)
+                },
+                Some(right_texture_id) => {
+                    // Separate eye textures: each texture is copied to a half of the pixel buffer
+                    state.blit_texture(layer.texture_id, texture_size, 0, width / 2);
+                    state.blit_texture(right_texture_id, texture_size, width / 2, width);
+                    (half_texture_bounds(&layer.left_bounds, 0.0), half_texture_bounds(&layer.right_bounds, 0.5))
+                }
+            };
+            gvr::gvr_frame_unbind(state.frame);
 
             // set up uvs
-            gvr::gvr_buffer_viewport_set_source_uv(self.left_eye_vp, gvr_texture_bounds(&layer.left_bounds));
-            gvr::gvr_buffer_viewport_set_source_uv(self.right_eye_vp, gvr_texture_bounds(&layer.right_bounds));
+            gvr::gvr_buffer_viewport_set_source_uv(state.left_eye_vp, gvr_texture_bounds(&left_bounds));
+            gvr::gvr_buffer_viewport_set_source_uv(state.right_eye_vp, gvr_texture_bounds(&right_bounds));
 
             // submit frame
-            gvr::gvr_frame_submit(mem::transmute(&self.frame), self.viewport_list, self.synced_head_matrix);
+            let head_matrix = *self.synced_head_matrix.lock().unwrap();
+            gvr::gvr_frame_submit(mem::transmute(&state.frame), state.viewport_list, head_matrix);
 
             // Restore bound fbo
             gl::BindFramebuffer(gl::FRAMEBUFFER, current_fbo as u32);
         }
-    }
 
-    #[cfg(target_os = "android")]
-    fn start_present(&mut self) {
-        if self.presenting {
-            return;
-        }
-        self.presenting = true;
-        unsafe {
-            if let Ok(jni_scope) = JNIScope::attach() {
-                let jni = jni_scope.jni;
-                let env = jni_scope.env;
-                let method = jni_scope.get_method((*self.service).java_class, "startPresent", "()V", false);
-                (jni.CallVoidMethod)(env, (*self.service).java_object, method);
-            }
+        if let Some(start) = state.frame_start {
+            state.stats.cpu_frame_time = utils::timestamp() - start;
         }
     }
 
-    #[cfg(not(target_os = "android"))]
+    fn frame_stats(&self) -> VRFrameStats {
+        self.frame_state.lock().unwrap().stats.clone()
+    }
+
     fn start_present(&mut self) {
-        self.presenting = true;
+        self.set_presenting(true);
     }
 
     // Hint to indicate that we are going to stop sending frames to the device
-    #[cfg(target_os = "android")]
     fn stop_present(&mut self) {
-        if !self.presenting {
-            return;
-        }
-        self.presenting = false;
-        unsafe {
-            if let Ok(jni_scope) = JNIScope::attach() {
-                let jni = jni_scope.jni;
-                let env = jni_scope.env;
-                let method = jni_scope.get_method((*self.service).java_class, "stopPresent", "()V", false);
-                (jni.CallVoidMethod)(env, (*self.service).java_object, method);
-            }
-        }
+        self.set_presenting(false);
     }
+}
 
-    #[cfg(not(target_os = "android"))]
-    fn stop_present(&mut self) {
-        self.presenting = false;
+impl GoogleVRFrameState {
+    // Blits an external texture to the columns [x0, x1) of the bound gvr pixel buffer
+    unsafe fn blit_texture(&mut self, texture_id: u32, texture_size: (u32, u32), x0: i32, x1: i32) {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo_id);
+        if self.fbo_texture != texture_id {
+            // Attach external texture to the used later in BlitFramebuffer.
+            gl::FramebufferTexture2D(gl::READ_FRAMEBUFFER,
+                                    gl::COLOR_ATTACHMENT0,
+                                    gl::TEXTURE_2D,
+                                    texture_id, 0);
+            self.fbo_texture = texture_id;
+        }
+        gl::BlitFramebuffer(0, 0, texture_size.0 as i32, texture_size.1 as i32,
+                            x0, 0, x1, self.render_size.height,
+                            gl::COLOR_BUFFER_BIT, gl::LINEAR);
     }
 }
 
 impl GoogleVRDisplay {
-    pub unsafe fn new(service: *const GoogleVRService,
-                      ctx: *mut gvr::gvr_context) -> Arc<RefCell<GoogleVRDisplay>> {
-        let list = gvr::gvr_buffer_viewport_list_create(ctx);
-
-        // gvr_refresh_viewer_profile must be called before getting recommended bufer viewports.
-        gvr::gvr_refresh_viewer_profile(ctx);
-
-        // Gets the recommended buffer viewport configuration, populating a previously
-        // allocated gvr_buffer_viewport_list object. The updated values include the
-        // per-eye recommended viewport and field of view for the target.
-        gvr::gvr_get_recommended_buffer_viewports(ctx, list);
-
-        // Create viewport buffers for both eyes.
-        let left_eye_vp = gvr::gvr_buffer_viewport_create(ctx);
-        gvr::gvr_buffer_viewport_list_get_item(list, gvr::gvr_eye::GVR_LEFT_EYE as usize, left_eye_vp);
-        let right_eye_vp = gvr::gvr_buffer_viewport_create(ctx);
-        gvr::gvr_buffer_viewport_list_get_item(list, gvr::gvr_eye::GVR_RIGHT_EYE as usize, right_eye_vp);
-
-        Arc::new(RefCell::new(GoogleVRDisplay {
-            service: service,
-            ctx: ctx,
-            viewport_list: list,
-            left_eye_vp: left_eye_vp,
-            right_eye_vp: right_eye_vp,
-            render_size: gvr::gvr_sizei {
-                width: 0,
-                height: 0,
-            },
-            swap_chain: ptr::null_mut(),
-            frame: ptr::null_mut(),
-            synced_head_matrix: gvr_identity_matrix(),
-            fbo_id: 0,
-            fbo_texture: 0,
+    pub unsafe fn new(context: GoogleVRContextPtr) -> GoogleVRDisplayPtr {
+        let (list, left_eye_vp, right_eye_vp) = {
+            let ctx = context.lock().unwrap();
+            let list = gvr::gvr_buffer_viewport_list_create(ctx.ctx);
+
+            // gvr_refresh_viewer_profile must be called before getting recommended bufer viewports.
+            gvr::gvr_refresh_viewer_profile(ctx.ctx);
+
+            // Gets the recommended buffer viewport configuration, populating a previously
+            // allocated gvr_buffer_viewport_list object. The updated values include the
+            // per-eye recommended viewport and field of view for the target.
+            gvr::gvr_get_recommended_buffer_viewports(ctx.ctx, list);
+
+            // Create viewport buffers for both eyes.
+            let left_eye_vp = gvr::gvr_buffer_viewport_create(ctx.ctx);
+            gvr::gvr_buffer_viewport_list_get_item(list, gvr::gvr_eye::GVR_LEFT_EYE as usize, left_eye_vp);
+            let right_eye_vp = gvr::gvr_buffer_viewport_create(ctx.ctx);
+            gvr::gvr_buffer_viewport_list_get_item(list, gvr::gvr_eye::GVR_RIGHT_EYE as usize, right_eye_vp);
+            (list, left_eye_vp, right_eye_vp)
+        };
+
+        Arc::new(RwLock::new(GoogleVRDisplay {
+            context: context,
+            frame_state: Mutex::new(GoogleVRFrameState {
+                viewport_list: list,
+                left_eye_vp: left_eye_vp,
+                right_eye_vp: right_eye_vp,
+                render_size: gvr::gvr_sizei {
+                    width: 0,
+                    height: 0,
+                },
+                swap_chain: ptr::null_mut(),
+                frame: ptr::null_mut(),
+                fbo_id: 0,
+                fbo_texture: 0,
+                stats: VRFrameStats {
+                    refresh_rate: REFRESH_RATE,
+                    .. VRFrameStats::default()
+                },
+                frame_start: None
+            }),
+            synced_head_matrix: Mutex::new(gvr_identity_matrix()),
             display_id: utils::new_id(),
-            presenting: false,
-            paused: false,
-            new_events_hint: false,
+            presenting: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            new_events_hint: AtomicBool::new(false),
             pending_events: Mutex::new(Vec::new()),
-            processed_events: Mutex::new(Vec::new())
+            processed_events: Mutex::new(Vec::new()),
+            reference_space: VRReferenceSpace::Seated
         }))
     }
 
-    unsafe fn initialize_gl(&mut self) {
+    unsafe fn initialize_gl(&self, context: &GoogleVRContext, state: &mut GoogleVRFrameState) {
         // Initializes gvr necessary GL-related objects.
-        gvr::gvr_initialize_gl(self.ctx);
+        gvr::gvr_initialize_gl(context.ctx);
 
         // Create a framebuffer required to used to attach and
         // blit the external texture into the main gvr pixel buffer.
-        gl::GenFramebuffers(1, &mut self.fbo_id);
+        gl::GenFramebuffers(1, &mut state.fbo_id);
 
         // Initialize gvr swap chain
-        let spec = gvr::gvr_buffer_spec_create(self.ctx);
-        self.render_size = self.recommended_render_size();
-        gvr::gvr_buffer_spec_set_size(spec, self.render_size);
+        let spec = gvr::gvr_buffer_spec_create(context.ctx);
+        state.render_size = GoogleVRDisplay::recommended_render_size(context);
+        gvr::gvr_buffer_spec_set_size(spec, state.render_size);
         // Don't enable 2X MSAA because we only use texture rendering
         gvr::gvr_buffer_spec_set_samples(spec, 0); 
         gvr::gvr_buffer_spec_set_color_format(spec, GVR_COLOR_FORMAT_RGBA_8888 as i32);
         gvr::gvr_buffer_spec_set_depth_stencil_format(spec, GVR_DEPTH_STENCIL_FORMAT_NONE as i32);
-        self.swap_chain = gvr::gvr_swap_chain_create(self.ctx, mem::transmute(&spec), 1);
+        state.swap_chain = gvr::gvr_swap_chain_create(context.ctx, mem::transmute(&spec), 1);
         gvr::gvr_buffer_spec_destroy(mem::transmute(&spec));
     }
 
@@ -300,9 +362,15 @@ impl GoogleVRDisplay {
         capabilities.has_orientation = true;
         capabilities.has_external_display = false;
         capabilities.has_position = false;
+        // Frames are presented from a single swap chain buffer
+        capabilities.max_layers = 1;
     }
 
-    unsafe fn fetch_eye(&self, out: &mut VREyeParameters, eye: gvr::gvr_eye, viewport: *mut gvr::gvr_buffer_viewport) {
+    unsafe fn fetch_eye(&self,
+                        context: &GoogleVRContext,
+                        out: &mut VREyeParameters,
+                        eye: gvr::gvr_eye,
+                        viewport: *mut gvr::gvr_buffer_viewport) {
         let eye_fov = gvr::gvr_buffer_viewport_get_source_fov(viewport);
 
         out.field_of_view.up_degrees = eye_fov.top as f64;
@@ -310,27 +378,31 @@ impl GoogleVRDisplay {
         out.field_of_view.down_degrees = eye_fov.bottom as f64;
         out.field_of_view.left_degrees = eye_fov.left as f64;
 
-        let eye_mat = gvr::gvr_get_eye_from_head_matrix(self.ctx, eye as i32);
+        let eye_mat = gvr::gvr_get_eye_from_head_matrix(context.ctx, eye as i32);
         out.offset = [eye_mat.m[0][3], eye_mat.m[1][3], eye_mat.m[2][3]];
     }
 
-    fn recommended_render_size(&self) -> gvr::gvr_sizei {
+    fn recommended_render_size(context: &GoogleVRContext) -> gvr::gvr_sizei {
         // GVR SDK states that thee maximum effective render target size can be very large.
         // Most applications need to scale down to compensate.
         // Half pixel sizes are used by scaling each dimension by sqrt(2)/2 ~= 7/10ths.
-        let render_target_size = unsafe { gvr::gvr_get_maximum_effective_render_target_size(self.ctx) };
+        let render_target_size = unsafe { gvr::gvr_get_maximum_effective_render_target_size(context.ctx) };
         gvr::gvr_sizei {
             width: (7 * render_target_size.width) / 10,
             height: (7 * render_target_size.height) / 10
         }
     }
 
-    unsafe fn fetch_eye_parameters(&self, left: &mut VREyeParameters, right: &mut VREyeParameters) {
+    unsafe fn fetch_eye_parameters(&self,
+                                   context: &GoogleVRContext,
+                                   state: &GoogleVRFrameState,
+                                   left: &mut VREyeParameters,
+                                   right: &mut VREyeParameters) {
         // Set fov and offset
-        self.fetch_eye(left, gvr::gvr_eye::GVR_LEFT_EYE, self.left_eye_vp);
-        self.fetch_eye(right, gvr::gvr_eye::GVR_RIGHT_EYE, self.right_eye_vp);
+        self.fetch_eye(context, left, gvr::gvr_eye::GVR_LEFT_EYE, state.left_eye_vp);
+        self.fetch_eye(context, right, gvr::gvr_eye::GVR_RIGHT_EYE, state.right_eye_vp);
 
-        let render_size = self.recommended_render_size();
+        let render_size = GoogleVRDisplay::recommended_render_size(context);
         
         left.render_width = render_size.width as u32 / 2;
         left.render_height = render_size.height as u32;
@@ -338,24 +410,26 @@ impl GoogleVRDisplay {
         right.render_height = left.render_height;
     }
 
-    fn fetch_head_matrix(&self, next_vsync: &gvr::gvr_clock_time_point) -> gvr::gvr_mat4f {
+    fn fetch_head_matrix(&self, context: &GoogleVRContext, next_vsync: &gvr::gvr_clock_time_point) -> gvr::gvr_mat4f {
         unsafe {
-            let m = gvr::gvr_get_head_space_from_start_space_rotation(self.ctx, *next_vsync);
-            gvr::gvr_apply_neck_model(self.ctx, m, 1.0)
+            let m = gvr::gvr_get_head_space_from_start_space_rotation(context.ctx, *next_vsync);
+            gvr::gvr_apply_neck_model(context.ctx, m, 1.0)
         }
     }
 
     fn fetch_frame_data(&self,
+                        context: &GoogleVRContext,
+                        state: &GoogleVRFrameState,
                         out: &mut VRFrameData,
                         head_matrix: &gvr::gvr_mat4f,
                         near: f32,
                         far: f32) {
         unsafe {
-            gvr::gvr_get_recommended_buffer_viewports(self.ctx, self.viewport_list);
+            gvr::gvr_get_recommended_buffer_viewports(context.ctx, state.viewport_list);
         }
         // Get matrices from gvr.
-        let left_eye = unsafe { gvr::gvr_get_eye_from_head_matrix(self.ctx, gvr::gvr_eye::GVR_LEFT_EYE as i32) };
-        let right_eye = unsafe { gvr::gvr_get_eye_from_head_matrix(self.ctx, gvr::gvr_eye::GVR_RIGHT_EYE as i32) };
+        let left_eye = unsafe { gvr::gvr_get_eye_from_head_matrix(context.ctx, gvr::gvr_eye::GVR_LEFT_EYE as i32) };
+        let right_eye = unsafe { gvr::gvr_get_eye_from_head_matrix(context.ctx, gvr::gvr_eye::GVR_RIGHT_EYE as i32) };
 
         // Convert gvr matrices to rust slices.
         let head_matrix = gvr_mat4_to_array(&head_matrix);
@@ -370,13 +444,20 @@ impl GoogleVRDisplay {
         utils::multiply_matrix(&right_eye, &view_matrix, &mut out.right_view_matrix);
 
         // Projection matrices
-        let left_fov = unsafe { gvr::gvr_buffer_viewport_get_source_fov(self.left_eye_vp) };
-        let right_fov = unsafe { gvr::gvr_buffer_viewport_get_source_fov(self.right_eye_vp) };
+        let left_fov = unsafe { gvr::gvr_buffer_viewport_get_source_fov(state.left_eye_vp) };
+        let right_fov = unsafe { gvr::gvr_buffer_viewport_get_source_fov(state.right_eye_vp) };
         out.left_projection_matrix = fov_to_projection_matrix(&left_fov, near, far);
         out.right_projection_matrix = fov_to_projection_matrix(&right_fov, near, far);
 
         out.pose.orientation = Some(utils::matrix_to_quat(&head_matrix));
 
+        // GVR tracks in the seated space and doesn't report stage parameters
+        if let Some(transform) = utils::reference_space_transform(VRReferenceSpace::Seated,
+                                                                   self.reference_space,
+                                                                   None) {
+            utils::transform_frame_data(&transform, out);
+        }
+
         // Timestamp
         out.timestamp = utils::timestamp();
     }
@@ -384,25 +465,47 @@ impl GoogleVRDisplay {
     // Warning: this function is called from java Main thread
     // Use mutexes to ensure thread safety and process the event in sync with the render loop.
     #[allow(dead_code)]
-    pub fn pause(&mut self) {
+    pub fn pause(&self) {
         let mut pending = self.pending_events.lock().unwrap();
         pending.push(VRDisplayEvent::Pause(self.display_id).into());
 
-        self.new_events_hint = true;
+        self.new_events_hint.store(true, Ordering::SeqCst);
     }
 
     // Warning: this function is called from java Main thread
     // Use mutexes to ensure thread safety and process the event in sync with the render loop.
     #[allow(dead_code)]
-    pub fn resume(&mut self) {
+    pub fn resume(&self) {
         let mut pending = self.pending_events.lock().unwrap();
         pending.push(VRDisplayEvent::Resume(self.display_id).into());
 
-        self.new_events_hint = true;
+        self.new_events_hint.store(true, Ordering::SeqCst);
     }
 
-    fn handle_events(&mut self) {
-        if !self.new_events_hint {
+    #[cfg(target_os = "android")]
+    fn set_presenting(&self, presenting: bool) {
+        if self.presenting.swap(presenting, Ordering::SeqCst) == presenting {
+            return;
+        }
+        let name = if presenting { "startPresent" } else { "stopPresent" };
+        let context = self.context.lock().unwrap();
+        unsafe {
+            if let Ok(jni_scope) = JNIScope::attach() {
+                let jni = jni_scope.jni;
+                let env = jni_scope.env;
+                let method = jni_scope.get_method(context.java_class, name, "()V", false);
+                (jni.CallVoidMethod)(env, context.java_object, method);
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "android"))]
+    fn set_presenting(&self, presenting: bool) {
+        self.presenting.store(presenting, Ordering::SeqCst);
+    }
+
+    fn handle_events(&self) {
+        if !self.new_events_hint.load(Ordering::SeqCst) {
             // Optimization to avoid mutex locks every frame
             // It doesn't matter if events are processed in the next loop iteration
             return;
@@ -410,7 +513,7 @@ impl GoogleVRDisplay {
         
         let mut pending: Vec<VREvent> = {
             let mut pending_events = self.pending_events.lock().unwrap();
-            self.new_events_hint = false;
+            self.new_events_hint.store(false, Ordering::SeqCst);
             let res = (*pending_events).drain(..).collect();
             res
         };
@@ -429,34 +532,34 @@ impl GoogleVRDisplay {
         processed.extend(pending.drain(..));
     }
 
-    fn handle_display_event(&mut self, event: &VRDisplayEvent) {
+    fn handle_display_event(&self, event: &VRDisplayEvent) {
         match *event {
             VRDisplayEvent::Pause(_) => {
-                if self.paused {
+                if self.paused.swap(true, Ordering::SeqCst) {
                     return;
                 }
+                let context = self.context.lock().unwrap();
                 unsafe {
-                    gvr::gvr_pause_tracking(self.ctx);
+                    gvr::gvr_pause_tracking(context.ctx);
                 }
-                self.paused = true;
             },
             VRDisplayEvent::Resume(_) => {
-                if !self.paused {
+                if !self.paused.swap(false, Ordering::SeqCst) {
                     return;
                 }
+                let context = self.context.lock().unwrap();
                 unsafe {
-                    gvr::gvr_resume_tracking(self.ctx);
+                    gvr::gvr_resume_tracking(context.ctx);
                     // Very important to call refresh after a resume event.
                     // If not called GvrLayout java view shows a black screen
-                    gvr::gvr_refresh_viewer_profile(self.ctx);
+                    gvr::gvr_refresh_viewer_profile(context.ctx);
                 }
-                self.paused = false;
             }
             _ => {}
         }
     }
 
-    pub fn poll_events(&mut self, out: &mut Vec<VREvent>) {
+    pub fn poll_events(&self, out: &mut Vec<VREvent>) {
         self.handle_events();
         let mut processed = self.processed_events.lock().unwrap();
         out.extend(processed.drain(..));
@@ -465,17 +568,19 @@ impl GoogleVRDisplay {
 
 impl Drop for GoogleVRDisplay {
     fn drop(&mut self) {
+        let _context = self.context.lock().unwrap();
+        let state = self.frame_state.lock().unwrap();
         unsafe {
-            if self.fbo_id > 0 {
-                gl::DeleteFramebuffers(1, mem::transmute(&self.fbo_id));
+            if state.fbo_id > 0 {
+                gl::DeleteFramebuffers(1, mem::transmute(&state.fbo_id));
             }
-            if !self.swap_chain.is_null() {
-                gvr::gvr_swap_chain_destroy(mem::transmute(&self.swap_chain));
+            if !state.swap_chain.is_null() {
+                gvr::gvr_swap_chain_destroy(mem::transmute(&state.swap_chain));
             }
 
-            gvr::gvr_buffer_viewport_destroy(mem::transmute(&self.left_eye_vp));
-            gvr::gvr_buffer_viewport_destroy(mem::transmute(&self.right_eye_vp));
-            gvr::gvr_buffer_viewport_list_destroy(mem::transmute(&self.viewport_list));
+            gvr::gvr_buffer_viewport_destroy(mem::transmute(&state.left_eye_vp));
+            gvr::gvr_buffer_viewport_destroy(mem::transmute(&state.right_eye_vp));
+            gvr::gvr_buffer_viewport_list_destroy(mem::transmute(&state.viewport_list));
         }
     }
 }
@@ -496,24 +601,7 @@ fn fov_to_projection_matrix(fov: &gvr::gvr_rectf, near: f32, far: f32) -> [f32;
     let right = fov.right.to_radians().tan() * near;
     let top = fov.top.to_radians().tan() * near;
     let bottom = -fov.bottom.to_radians().tan() * near;
-    frustum_matrix(left, right, bottom, top, near, far)
-}
-
-fn frustum_matrix(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> [f32; 16] {
-    let r_width  = 1.0 / (right - left);
-    let r_height = 1.0 / (top - bottom);
-    let r_depth  = 1.0 / (near - far);
-    let x = 2.0 * (near * r_width);
-    let y = 2.0 * (near * r_height);
-    let a = (right + left) * r_width;
-    let b = (top + bottom) * r_height;
-    let c = (far + near) * r_depth;
-    let d = 2.0 * (far * near * r_depth);
-
-    [x, 0.0, 0.0, 0.0,
-     0.0, y, 0.0, 0.0,
-     a, b, c, -1.0,
-     0.0, 0.0, d, 0.0]
+    utils::frustum_matrix(left, right, bottom, top, near, far)
 }
 
 #[inline]
@@ -536,6 +624,11 @@ fn gvr_texture_bounds(array: &[f32; 4]) -> gvr::gvr_rectf {
     }
 }
 
+// Maps texture bounds to a half of the gvr pixel buffer starting at the u coordinate `offset`
+fn half_texture_bounds(bounds: &[f32; 4], offset: f32) -> [f32; 4] {
+    [offset + bounds[0] * 0.5, bounds[1], bounds[2] * 0.5, bounds[3]]
+}
+
 fn to_string(ptr: *const ::std::os::raw::c_char) -> String {
     if ptr.is_null() {
         return String::new();
@@ -1,89 +1,97 @@
 #![cfg(feature = "googlevr")]
-use {VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton};
+use {VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton, VRPose, VRReferenceSpace};
+use super::context::GoogleVRContextPtr;
 use super::super::utils;
 use gvr_sys as gvr;
 use gvr_sys::gvr_controller_api_status::*;
 use gvr_sys::gvr_controller_button::*;
 use gvr_sys::gvr_controller_connection_state::*;
 use gvr_sys::gvr_controller_handedness::*;
-use std::cell::RefCell;
 use std::mem;
 use std::ffi::CStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-pub type GoogleVRGamepadPtr = Arc<RefCell<GoogleVRGamepad>>;
+pub type GoogleVRGamepadPtr = Arc<RwLock<GoogleVRGamepad>>;
 
 pub struct GoogleVRGamepad {
-    ctx: *mut gvr::gvr_context,
-    controller_ctx: *mut gvr::gvr_controller_context,
-    state: *mut gvr::gvr_controller_state,
+    // Locked after the context
+    state: Mutex<ControllerState>,
+    context: GoogleVRContextPtr,
     gamepad_id: u32,
     display_id: u32,
-    paused: bool,
-    system_paused: bool,
+    paused: AtomicBool,
+    system_paused: AtomicBool,
+    reference_space: VRReferenceSpace
 }
 
-unsafe impl Send for GoogleVRGamepad {}
-unsafe impl Sync for GoogleVRGamepad {}
+// Controller state updated by the gamepad
+struct ControllerState(*mut gvr::gvr_controller_state);
+
+// The controller state is only used while the GoogleVRContext is locked
+unsafe impl Send for ControllerState {}
 
 impl GoogleVRGamepad {
-    pub unsafe fn new(ctx: *mut gvr::gvr_context,
-                      controller_ctx: *mut gvr::gvr_controller_context,
+    pub unsafe fn new(context: GoogleVRContextPtr,
                       display_id: u32)
-                      -> Result<Arc<RefCell<GoogleVRGamepad>>, String> {
-        let gamepad = Self {
-            ctx: ctx,
-            controller_ctx: controller_ctx,
-            state: gvr::gvr_controller_state_create(),
-            gamepad_id: utils::new_id(),
-            display_id: display_id,
-            paused: false,
-            system_paused: false,
-        };
-        gvr::gvr_controller_state_update(controller_ctx, 0, gamepad.state);
-        let api_status = gvr::gvr_controller_state_get_api_status(gamepad.state);
-        if api_status != GVR_CONTROLLER_API_OK as i32 {
-            let message = CStr::from_ptr(gvr::gvr_controller_api_status_to_string(api_status));
-            return Err(message.to_string_lossy().into());
+                      -> Result<GoogleVRGamepadPtr, String> {
+        let state = ControllerState(gvr::gvr_controller_state_create());
+        {
+            let context = context.lock().unwrap();
+            gvr::gvr_controller_state_update(context.controller_ctx, 0, state.0);
+            let api_status = gvr::gvr_controller_state_get_api_status(state.0);
+            if api_status != GVR_CONTROLLER_API_OK as i32 {
+                let message = CStr::from_ptr(gvr::gvr_controller_api_status_to_string(api_status));
+                return Err(message.to_string_lossy().into());
+            }
         }
 
-        Ok(Arc::new(RefCell::new(gamepad)))
+        Ok(Arc::new(RwLock::new(GoogleVRGamepad {
+            state: Mutex::new(state),
+            context: context,
+            gamepad_id: utils::new_id(),
+            display_id: display_id,
+            paused: AtomicBool::new(false),
+            system_paused: AtomicBool::new(false),
+            reference_space: VRReferenceSpace::Seated
+        })))
     }
 
     // Warning: this function is called from java Main thread
     // The action it's handled in handle_events method for thread safety
     #[allow(dead_code)]
-    pub fn pause(&mut self) {
-        self.system_paused = true;
+    pub fn pause(&self) {
+        self.system_paused.store(true, Ordering::SeqCst);
     }
 
     // Warning: this function is called from java Main thread
     // The action it's handled in handle_events method for thread safety
     #[allow(dead_code)]
-    pub fn resume(&mut self) {
-        self.system_paused = false;
+    pub fn resume(&self) {
+        self.system_paused.store(false, Ordering::SeqCst);
     }
 
-    pub fn handle_events(&mut self) {
-        if self.system_paused == self.paused {
+    pub fn handle_events(&self) {
+        let system_paused = self.system_paused.load(Ordering::SeqCst);
+        if self.paused.swap(system_paused, Ordering::SeqCst) == system_paused {
             return;
         }
-        self.paused = self.system_paused;
+        let context = self.context.lock().unwrap();
         unsafe {
-            if self.paused {
-                gvr::gvr_controller_pause(self.controller_ctx);
+            if system_paused {
+                gvr::gvr_controller_pause(context.controller_ctx);
             }
             else {
-                gvr::gvr_controller_resume(self.controller_ctx);
+                gvr::gvr_controller_resume(context.controller_ctx);
             }
         }
     }
 }
 
-impl Drop for GoogleVRGamepad {
+impl Drop for ControllerState {
     fn drop(&mut self) {
         unsafe {
-            gvr::gvr_controller_state_destroy(mem::transmute(&self.state));
+            gvr::gvr_controller_state_destroy(mem::transmute(&self.0));
         }
     }
 }
@@ -95,7 +103,8 @@ impl VRGamepad for GoogleVRGamepad {
 
     fn data(&self) -> VRGamepadData {
         let handeness = unsafe { 
-            let prefs = gvr::gvr_get_user_prefs(self.ctx);
+            let context = self.context.lock().unwrap();
+            let prefs = gvr::gvr_get_user_prefs(context.ctx);
             gvr::gvr_user_prefs_get_controller_handedness(prefs)
         };
         let hand = if handeness == GVR_CONTROLLER_LEFT_HANDED as i32 {
@@ -115,16 +124,19 @@ impl VRGamepad for GoogleVRGamepad {
         let mut out = VRGamepadState::default();
 
         out.gamepad_id = self.gamepad_id;
+        let context = self.context.lock().unwrap();
+        let controller = self.state.lock().unwrap();
+        let state = controller.0;
         unsafe {
-            gvr::gvr_controller_state_update(self.controller_ctx, 0, self.state);
-            let connection_state = gvr::gvr_controller_state_get_connection_state(self.state);
+            gvr::gvr_controller_state_update(context.controller_ctx, 0, state);
+            let connection_state = gvr::gvr_controller_state_get_connection_state(state);
             out.connected = connection_state == GVR_CONTROLLER_CONNECTED as i32;
 
-            let touchpad_touching = gvr::gvr_controller_state_is_touching(self.state);
+            let touchpad_touching = gvr::gvr_controller_state_is_touching(state);
 
             // Touchpad: (0,0) is the top-left of the touchpad and (1,1)
             // Map to -1 1 for each axis.
-            let pos = gvr::gvr_controller_state_get_touch_pos(self.state);
+            let pos = gvr::gvr_controller_state_get_touch_pos(state);
             out.axes = if touchpad_touching {
                 [pos.x as f64 * 2.0 - 1.0, 
                  pos.y as f64 * 2.0 - 1.0].to_vec()
@@ -134,7 +146,7 @@ impl VRGamepad for GoogleVRGamepad {
 
             // Add touchpad as a button
             out.buttons.push(VRGamepadButton {
-                pressed: gvr::gvr_controller_state_get_button_state(self.state, GVR_CONTROLLER_BUTTON_CLICK as i32),
+                pressed: gvr::gvr_controller_state_get_button_state(state, GVR_CONTROLLER_BUTTON_CLICK as i32),
                 touched: touchpad_touching,
             });
 
@@ -144,29 +156,60 @@ impl VRGamepad for GoogleVRGamepad {
                            GVR_CONTROLLER_BUTTON_VOLUME_UP,
                            GVR_CONTROLLER_BUTTON_VOLUME_DOWN];
             for button in &buttons {
-                let pressed = gvr::gvr_controller_state_get_button_state(self.state, *button as i32);
+                let pressed = gvr::gvr_controller_state_get_button_state(state, *button as i32);
                 out.buttons.push(VRGamepadButton {
                     pressed: pressed,
                     touched: pressed,
                 }); 
             }
 
-            let quat = gvr::gvr_controller_state_get_orientation(self.state);
+            let quat = gvr::gvr_controller_state_get_orientation(state);
             out.pose.orientation = Some([
                 quat.qx, quat.qy, quat.qz, quat.qw
             ]);
 
-            let acc = gvr::gvr_controller_state_get_accel(self.state);
+            let acc = gvr::gvr_controller_state_get_accel(state);
             out.pose.linear_acceleration = Some([
                 acc.x, acc.y, acc.z
             ]);
 
-            let vel = gvr::gvr_controller_state_get_gyro(self.state);
+            let vel = gvr::gvr_controller_state_get_gyro(state);
             out.pose.angular_velocity = Some([
                 vel.x, vel.y, vel.z
             ]);
         }
 
+        // GVR tracks in the seated space. The accelerometer and the gyroscope are reported
+        // in controller space, so only the orientation is transformed.
+        if let Some(transform) = utils::reference_space_transform(VRReferenceSpace::Seated,
+                                                                   self.reference_space,
+                                                                   None) {
+            out.pose.orientation = utils::transform_pose(&transform, &out.pose).orientation;
+        }
+
         out
     }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+
+    fn predicted_state(&self, target_time: f64) -> VRGamepadState {
+        let mut state = self.state();
+        // GVR doesn't predict controller poses, the orientation is extrapolated from the gyroscope
+        if let (Some(orientation), Some(gyro)) = (state.pose.orientation, state.pose.angular_velocity) {
+            let mut pose = VRPose::default();
+            pose.orientation = Some(orientation);
+            // The gyroscope measures the angular velocity in controller space
+            pose.angular_velocity = Some(utils::rotate_vector(&orientation, &gyro));
+            let dt = (target_time - utils::timestamp()) / 1000.0;
+            state.pose.orientation = utils::extrapolate_pose(&pose, dt as f32).orientation;
+        }
+        state.timestamp = target_time;
+        state
+    }
 }
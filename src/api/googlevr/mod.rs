@@ -1,5 +1,6 @@
 #![cfg(feature = "googlevr")]
 
+mod context;
 mod display;
 mod gamepad;
 mod service;
@@ -1,6 +1,7 @@
 #![cfg(feature = "googlevr")]
 
-use {VRService, VRDisplay, VRDisplayPtr, VREvent, VRGamepadPtr};
+use {VRService, VRDisplay, VRDisplayPtr, VRError, VREvent, VRGamepadPtr};
+use super::context::{GoogleVRContext, GoogleVRContextPtr};
 use super::display::{GoogleVRDisplay, GoogleVRDisplayPtr};
 use super::gamepad::{GoogleVRGamepad, GoogleVRGamepadPtr};
 #[cfg(target_os="android")]
@@ -8,44 +9,40 @@ use super::jni_utils::JNIScope;
 #[cfg(target_os="android")]
 use android_injected_glue::ffi as ndk;
 use gvr_sys as gvr;
+#[cfg(target_os="android")]
 use std::mem;
 use std::ptr;
+use std::sync::{Arc, Mutex};
 
 #[cfg(target_os="android")]
 const SERVICE_CLASS_NAME:&'static str = "com/rust/webvr/GVRService";
 
 pub struct GoogleVRService {
-    ctx: *mut gvr::gvr_context,
-    controller_ctx: *mut gvr::gvr_controller_context,
+    // Available once initialized
+    context: Option<GoogleVRContextPtr>,
     displays: Vec<GoogleVRDisplayPtr>,
-    gamepads: Vec<GoogleVRGamepadPtr>,
-    #[cfg(target_os="android")]
-    pub java_object: ndk::jobject,
-    #[cfg(target_os="android")]
-    pub java_class: ndk::jclass,
+    gamepads: Vec<GoogleVRGamepadPtr>
 }
 
-unsafe impl Send for GoogleVRService {}
-
 impl VRService for GoogleVRService {
-    fn initialize(&mut self) -> Result<(), String> { 
+    fn initialize(&mut self) -> Result<(), VRError> { 
         if self.is_initialized() {
             return Ok(());
         }
 
-        unsafe {
-            try!(self.create_context());
-            self.create_controller_context();
+        let mut context = unsafe { try!(self.create_context().map_err(backend_error)) };
+        if context.ctx.is_null() {
+            return Err(backend_error("GoogleVR SDK failed to initialize".into()));
         }
-
-        if self.ctx.is_null() {
-            return Err("GoogleVR SDK failed to initialize".into());
+        unsafe {
+            GoogleVRService::create_controller_context(&mut context);
         }
+        self.context = Some(Arc::new(Mutex::new(context)));
 
         Ok(())
     }
 
-    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>,String> {
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>,VRError> {
         // Return cached displays if available
         if self.is_initialized() && self.displays.len() > 0 {
             return Ok(self.clone_displays());
@@ -53,13 +50,13 @@ impl VRService for GoogleVRService {
 
         // Ensure that there are not initialization errors
         try!(self.initialize());
-        let display = unsafe { GoogleVRDisplay::new(self, self.ctx) };
+        let display = unsafe { GoogleVRDisplay::new(self.context.as_ref().unwrap().clone()) };
         self.displays.push(display);
 
         Ok(self.clone_displays())
     }
 
-    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>,String> {
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>,VRError> {
         // Return cached gamepads if available
         if self.is_initialized() && self.gamepads.len() > 0 {
             return Ok(self.clone_gamepads());
@@ -68,10 +65,11 @@ impl VRService for GoogleVRService {
 
         let gamepad = unsafe {
             let display_id = match self.displays.first() {
-                Some(display) => display.borrow().id(),
+                Some(display) => display.read().unwrap().id(),
                 None => 0
             };
-            try!(GoogleVRGamepad::new(self.ctx, self.controller_ctx, display_id))
+            let context = self.context.as_ref().unwrap().clone();
+            try!(GoogleVRGamepad::new(context, display_id).map_err(backend_error))
         };
         self.gamepads.push(gamepad);
         
@@ -85,35 +83,21 @@ impl VRService for GoogleVRService {
     fn poll_events(&self) -> Vec<VREvent> {
         let mut events = Vec::new();
         for display in &self.displays {
-            display.borrow_mut().poll_events(&mut events);
+            display.read().unwrap().poll_events(&mut events);
         }
         for gamepad in &self.gamepads {
-            gamepad.borrow_mut().handle_events();
+            gamepad.read().unwrap().handle_events();
         }
         events
     }
 }
 
 impl GoogleVRService {
-    #[cfg(target_os="android")]
-    pub fn new() -> GoogleVRService {
-        GoogleVRService {
-            ctx: ptr::null_mut(),
-            controller_ctx: ptr::null_mut(),
-            displays: Vec::new(),
-            gamepads: Vec::new(),
-            java_object: ptr::null_mut(),
-            java_class: ptr::null_mut()
-        }
-    }
-
-    #[cfg(not(target_os="android"))]
     pub fn new() -> GoogleVRService {
         GoogleVRService {
-            ctx: ptr::null_mut(),
-            controller_ctx: ptr::null_mut(),
+            context: None,
             displays: Vec::new(),
-            gamepads: Vec::new(),
+            gamepads: Vec::new()
         }
     }
 
@@ -122,53 +106,60 @@ impl GoogleVRService {
     // Java code is implemented in GVRService. It handles the life cycle of the GvrLayout.
     // JNI code is used to comunicate with that Java code.
     #[cfg(target_os="android")]
-    unsafe fn create_context(&mut self) -> Result<(), String> {
+    unsafe fn create_context(&mut self) -> Result<GoogleVRContext, String> {
         let jni_scope = try!(JNIScope::attach());
 
         let jni = jni_scope.jni;
         let env = jni_scope.env;
 
         // Use NativeActivity's classloader to find our class
-        self.java_class = try!(jni_scope.find_class(SERVICE_CLASS_NAME));
-        if self.java_class.is_null() {
+        let java_class = try!(jni_scope.find_class(SERVICE_CLASS_NAME));
+        if java_class.is_null() {
             return Err("Didn't find GVRService class".into());
         };
-        self.java_class = (jni.NewGlobalRef)(env, self.java_class);
+        let java_class = (jni.NewGlobalRef)(env, java_class);
 
         // Create GVRService instance and own it as a globalRef.
-        let method = jni_scope.get_method(self.java_class, "create", "(Landroid/app/Activity;J)Ljava/lang/Object;", true);
+        let method = jni_scope.get_method(java_class, "create", "(Landroid/app/Activity;J)Ljava/lang/Object;", true);
         let thiz: usize = mem::transmute(self as * mut GoogleVRService);
-        self.java_object = (jni.CallStaticObjectMethod)(env, self.java_class, method, jni_scope.activity, thiz as ndk::jlong);
-        if self.java_object.is_null() {
+        let java_object = (jni.CallStaticObjectMethod)(env, java_class, method, jni_scope.activity, thiz as ndk::jlong);
+        if java_object.is_null() {
             return Err("Failed to create GVRService instance".into());
         };
-        self.java_object = (jni.NewGlobalRef)(env, self.java_object);
+        let java_object = (jni.NewGlobalRef)(env, java_object);
 
         // Finally we have everything required to get the gvr_context pointer from java :)
-        let method = jni_scope.get_method(self.java_class, "getNativeContext", "()J", false);
-        let pointer = (jni.CallLongMethod)(env, self.java_object, method);
-        self.ctx = pointer as *mut gvr::gvr_context;
-        if self.ctx.is_null() {
+        let method = jni_scope.get_method(java_class, "getNativeContext", "()J", false);
+        let pointer = (jni.CallLongMethod)(env, java_object, method);
+        let ctx = pointer as *mut gvr::gvr_context;
+        if ctx.is_null() {
             return Err("Failed to getNativeGvrContext from java GvrLayout".into());
         }
 
-        Ok(())
+        Ok(GoogleVRContext {
+            ctx: ctx,
+            controller_ctx: ptr::null_mut(),
+            java_object: java_object,
+            java_class: java_class
+        })
     }
 
     #[cfg(not(target_os="android"))]
-    unsafe fn create_context(&mut self) -> Result<(), String>  {
-        self.ctx = gvr::gvr_create();
-        Ok(())
+    unsafe fn create_context(&mut self) -> Result<GoogleVRContext, String>  {
+        Ok(GoogleVRContext {
+            ctx: gvr::gvr_create(),
+            controller_ctx: ptr::null_mut()
+        })
     }
 
-    unsafe fn create_controller_context(&mut self) {
+    unsafe fn create_controller_context(context: &mut GoogleVRContext) {
         let options = gvr::gvr_controller_get_default_options();
-        self.controller_ctx = gvr::gvr_controller_create_and_init(options, self.ctx);
-        gvr::gvr_controller_resume(self.controller_ctx);
+        context.controller_ctx = gvr::gvr_controller_create_and_init(options, context.ctx);
+        gvr::gvr_controller_resume(context.controller_ctx);
     }
 
     fn is_initialized(&self) -> bool {
-        return !self.ctx.is_null();
+        return self.context.is_some();
     }
 
     fn clone_displays(&self) -> Vec<VRDisplayPtr> {
@@ -184,15 +175,11 @@ impl GoogleVRService {
     #[cfg(target_os="android")]
     fn on_pause(&mut self) {
         for display in &self.displays {
-            unsafe {
-                (*display.as_ptr()).pause();
-            }
+            display.read().unwrap().pause();
         }
 
         for gamepad in &self.gamepads {
-            unsafe {
-                (*gamepad.as_ptr()).pause();
-            }
+            gamepad.read().unwrap().pause();
         }
     }
 
@@ -201,32 +188,16 @@ impl GoogleVRService {
     #[cfg(target_os="android")]
     fn on_resume(&mut self) {
         for display in &self.displays {
-            unsafe {
-                (*display.as_ptr()).resume();
-            }
+            display.read().unwrap().resume();
         }
         for gamepad in &self.gamepads {
-            unsafe {
-                (*gamepad.as_ptr()).resume();
-            }
+            gamepad.read().unwrap().resume();
         }
     }
 }
 
-impl Drop for GoogleVRService {
-    fn drop(&mut self) {
-        if !self.controller_ctx.is_null() {
-            unsafe {
-                gvr::gvr_controller_destroy(mem::transmute(&self.ctx));
-            }
-        }
-
-        if !self.ctx.is_null() {
-            unsafe {
-                gvr::gvr_destroy(mem::transmute(&self.ctx));
-            }
-        }
-    }
+fn backend_error(message: String) -> VRError {
+    VRError::Backend("GoogleVR".into(), message)
 }
 
 
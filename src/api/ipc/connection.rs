@@ -0,0 +1,140 @@
+use {VRDisplayEvent, VREvent, VRGamepadEvent};
+use super::protocol::{self, Request, Response};
+use vr_wire;
+use super::super::utils;
+use std::collections::HashMap;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex};
+
+pub type RemoteConnectionPtr = Arc<RemoteConnection>;
+
+// Connections to a RemoteVRServer shared by a RemoteVRService and its devices.
+// Each request uses an idle connection or opens a new one, so requests of different threads
+// run in parallel in the server.
+pub struct RemoteConnection {
+    path: PathBuf,
+    // Identifies the connections of this client in the server
+    client_id: u64,
+    idle: Mutex<Vec<UnixStream>>,
+    // Maps the device ids of the server to the ids of the local devices
+    display_ids: Mutex<HashMap<u32, u32>>,
+    gamepad_ids: Mutex<HashMap<u32, u32>>
+}
+
+impl RemoteConnection {
+    // Connects to the server to check that it's available and compatible
+    pub fn open(path: &Path) -> Result<RemoteConnectionPtr, String> {
+        let connection = RemoteConnection {
+            path: path.to_path_buf(),
+            // Unique among the clients of all the processes
+            client_id: (process::id() as u64) << 32 | utils::new_id() as u64,
+            idle: Mutex::new(Vec::new()),
+            display_ids: Mutex::new(HashMap::new()),
+            gamepad_ids: Mutex::new(HashMap::new())
+        };
+        let stream = try!(connection.connect());
+        connection.idle.lock().unwrap().push(stream);
+        Ok(Arc::new(connection))
+    }
+
+    // Sends the request and waits for its response.
+    // Error responses are returned as errors.
+    pub fn request(&self, request: &Request) -> Result<Response, String> {
+        let payload = protocol::encode_request(request);
+        let idle = self.idle.lock().unwrap().pop();
+        let (stream, response) = match idle {
+            // The server may have closed the idle connection. The request is sent again on a
+            // new connection if it didn't reach the server, or if it can safely run twice.
+            Some(mut stream) => match write_request(&mut stream, &payload) {
+                Ok(()) => match read_response(&mut stream) {
+                    Ok(response) => (stream, response),
+                    Err(_) if request.is_idempotent() => try!(self.send_new(&payload)),
+                    Err(message) => return Err(message)
+                },
+                Err(_) => try!(self.send_new(&payload))
+            },
+            None => try!(self.send_new(&payload))
+        };
+        // Connections with transport errors are dropped
+        self.idle.lock().unwrap().push(stream);
+        match response {
+            Response::Error(message) => Err(message),
+            response => Ok(response)
+        }
+    }
+
+    // Local id of a server display. New ids are assigned the first time they are seen.
+    pub fn display_id(&self, remote_id: u32) -> u32 {
+        *self.display_ids.lock().unwrap().entry(remote_id).or_insert_with(utils::new_id)
+    }
+
+    // Local id of a server gamepad. New ids are assigned the first time they are seen.
+    pub fn gamepad_id(&self, remote_id: u32) -> u32 {
+        *self.gamepad_ids.lock().unwrap().entry(remote_id).or_insert_with(utils::new_id)
+    }
+
+    // Replaces the server device ids with the ids of the local devices
+    pub fn remap_event(&self, mut event: VREvent) -> VREvent {
+        match event {
+            VREvent::Display(ref mut event) => match *event {
+                VRDisplayEvent::Connect(ref mut data) |
+                VRDisplayEvent::Activate(ref mut data, _) |
+                VRDisplayEvent::Deactivate(ref mut data, _) |
+                VRDisplayEvent::Change(ref mut data) |
+                VRDisplayEvent::Blur(ref mut data) |
+                VRDisplayEvent::Focus(ref mut data) |
+                VRDisplayEvent::PresentChange(ref mut data, _) => {
+                    data.display_id = self.display_id(data.display_id);
+                },
+                VRDisplayEvent::Disconnect(ref mut id) |
+                VRDisplayEvent::Pause(ref mut id) |
+                VRDisplayEvent::Resume(ref mut id) |
+                VRDisplayEvent::Exit(ref mut id) => {
+                    *id = self.display_id(*id);
+                }
+            },
+            VREvent::Gamepad(ref mut event) => match *event {
+                VRGamepadEvent::Connect(ref mut data, ref mut state) => {
+                    data.display_id = self.display_id(data.display_id);
+                    state.gamepad_id = self.gamepad_id(state.gamepad_id);
+                },
+                VRGamepadEvent::Disconnect(ref mut id) => {
+                    *id = self.gamepad_id(*id);
+                }
+            }
+        }
+        event
+    }
+
+    fn send_new(&self, payload: &[u8]) -> Result<(UnixStream, Response), String> {
+        let mut stream = try!(self.connect());
+        let response = try!(send(&mut stream, payload));
+        Ok((stream, response))
+    }
+
+    fn connect(&self) -> Result<UnixStream, String> {
+        let mut stream = try!(UnixStream::connect(&self.path).map_err(|e| {
+            format!("Can't connect to {}: {}", self.path.display(), e)
+        }));
+        match try!(send(&mut stream, &protocol::encode_handshake(self.client_id))) {
+            Response::Error(message) => Err(message),
+            _ => Ok(stream)
+        }
+    }
+}
+
+fn send(stream: &mut UnixStream, payload: &[u8]) -> Result<Response, String> {
+    try!(write_request(stream, payload));
+    read_response(stream)
+}
+
+fn write_request(stream: &mut UnixStream, payload: &[u8]) -> Result<(), String> {
+    vr_wire::write_message(stream, payload).map_err(|e| e.to_string())
+}
+
+fn read_response(stream: &mut UnixStream) -> Result<Response, String> {
+    let response = try!(vr_wire::read_message(stream).map_err(|e| e.to_string()));
+    Ok(try!(protocol::decode_response(&response)))
+}
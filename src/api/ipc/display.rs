@@ -0,0 +1,137 @@
+use {VRDisplay, VRDisplayData, VRFrameData, VRFrameStats, VRLayer, VRReferenceSpace};
+use super::connection::RemoteConnectionPtr;
+use super::protocol::{Request, Response};
+use std::sync::{Arc, RwLock};
+
+pub type RemoteVRDisplayPtr = Arc<RwLock<RemoteVRDisplay>>;
+
+// Display of a RemoteVRServer. Each call is forwarded to the server display.
+// The reference space and the presentation state are shared by all the clients of the display.
+pub struct RemoteVRDisplay {
+    display_id: u32,
+    remote_id: u32,
+    connection: RemoteConnectionPtr
+}
+
+impl RemoteVRDisplay {
+    pub fn new(remote_id: u32, connection: RemoteConnectionPtr) -> RemoteVRDisplayPtr {
+        Arc::new(RwLock::new(RemoteVRDisplay {
+            display_id: connection.display_id(remote_id),
+            remote_id: remote_id,
+            connection: connection
+        }))
+    }
+
+    // Id of the display in the server
+    pub fn remote_id(&self) -> u32 {
+        self.remote_id
+    }
+
+    fn frame_data(&self, request: Request) -> VRFrameData {
+        match self.connection.request(&request) {
+            Ok(Response::FrameData(data)) => data,
+            result => {
+                self.log_error(&request, result);
+                VRFrameData::default()
+            }
+        }
+    }
+
+    fn send(&self, request: Request) {
+        match self.connection.request(&request) {
+            Ok(Response::Empty) => {},
+            result => self.log_error(&request, result)
+        }
+    }
+
+    fn log_error(&self, request: &Request, result: Result<Response, String>) {
+        match result {
+            Ok(response) => error!("Unexpected IPC response to {:?}: {:?}", request, response),
+            Err(message) => error!("IPC request {:?} of display {} failed: {}", request, self.display_id, message)
+        }
+    }
+}
+
+impl VRDisplay for RemoteVRDisplay {
+    fn id(&self) -> u32 {
+        self.display_id
+    }
+
+    fn data(&self) -> VRDisplayData {
+        let request = Request::DisplayData(self.remote_id);
+        match self.connection.request(&request) {
+            Ok(Response::DisplayData(mut data)) => {
+                data.display_id = self.display_id;
+                data
+            },
+            result => {
+                self.log_error(&request, result);
+                let mut data = VRDisplayData::default();
+                data.display_id = self.display_id;
+                data
+            }
+        }
+    }
+
+    fn inmediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        self.frame_data(Request::InmediateFrameData(self.remote_id, near_z, far_z))
+    }
+
+    fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        self.frame_data(Request::SyncedFrameData(self.remote_id, near_z, far_z))
+    }
+
+    fn predicted_frame_data(&self, near_z: f64, far_z: f64, target_time: f64) -> VRFrameData {
+        self.frame_data(Request::PredictedFrameData(self.remote_id, near_z, far_z, target_time))
+    }
+
+    fn reset_pose(&mut self) {
+        self.send(Request::ResetPose(self.remote_id));
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.send(Request::SetReferenceSpace(self.remote_id, space));
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        let request = Request::ReferenceSpace(self.remote_id);
+        match self.connection.request(&request) {
+            Ok(Response::ReferenceSpace(space)) => space,
+            result => {
+                self.log_error(&request, result);
+                VRReferenceSpace::Seated
+            }
+        }
+    }
+
+    fn sync_poses(&self) {
+        self.send(Request::SyncPoses(self.remote_id));
+    }
+
+    fn submit_frame(&mut self, layer: &VRLayer) {
+        self.send(Request::SubmitFrame(self.remote_id, layer.clone()));
+    }
+
+    fn submit_layers(&mut self, layers: &[VRLayer]) {
+        self.send(Request::SubmitLayers(self.remote_id, layers.to_vec()));
+    }
+
+    fn frame_stats(&self) -> VRFrameStats {
+        let request = Request::FrameStats(self.remote_id);
+        match self.connection.request(&request) {
+            Ok(Response::FrameStats(stats)) => stats,
+            result => {
+                self.log_error(&request, result);
+                VRFrameStats::default()
+            }
+        }
+    }
+
+    fn start_present(&mut self) {
+        self.send(Request::StartPresent(self.remote_id));
+    }
+
+    fn stop_present(&mut self) {
+        self.send(Request::StopPresent(self.remote_id));
+    }
+}
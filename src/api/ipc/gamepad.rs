@@ -0,0 +1,95 @@
+use {VRGamepad, VRGamepadData, VRGamepadState, VRReferenceSpace};
+use super::connection::RemoteConnectionPtr;
+use super::protocol::{Request, Response};
+use std::sync::{Arc, RwLock};
+
+pub type RemoteVRGamepadPtr = Arc<RwLock<RemoteVRGamepad>>;
+
+// Gamepad of a RemoteVRServer. Each call is forwarded to the server gamepad.
+// The reference space is shared by all the clients of the gamepad.
+pub struct RemoteVRGamepad {
+    gamepad_id: u32,
+    remote_id: u32,
+    connection: RemoteConnectionPtr
+}
+
+impl RemoteVRGamepad {
+    pub fn new(remote_id: u32, connection: RemoteConnectionPtr) -> RemoteVRGamepadPtr {
+        Arc::new(RwLock::new(RemoteVRGamepad {
+            gamepad_id: connection.gamepad_id(remote_id),
+            remote_id: remote_id,
+            connection: connection
+        }))
+    }
+
+    // Id of the gamepad in the server
+    pub fn remote_id(&self) -> u32 {
+        self.remote_id
+    }
+
+    fn request_state(&self, request: Request) -> VRGamepadState {
+        let mut state = match self.connection.request(&request) {
+            Ok(Response::GamepadState(state)) => state,
+            result => {
+                self.log_error(&request, result);
+                VRGamepadState::default()
+            }
+        };
+        state.gamepad_id = self.gamepad_id;
+        state
+    }
+
+    fn log_error(&self, request: &Request, result: Result<Response, String>) {
+        match result {
+            Ok(response) => error!("Unexpected IPC response to {:?}: {:?}", request, response),
+            Err(message) => error!("IPC request {:?} of gamepad {} failed: {}", request, self.gamepad_id, message)
+        }
+    }
+}
+
+impl VRGamepad for RemoteVRGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad_id
+    }
+
+    fn data(&self) -> VRGamepadData {
+        let request = Request::GamepadData(self.remote_id);
+        match self.connection.request(&request) {
+            Ok(Response::GamepadData(mut data)) => {
+                data.display_id = self.connection.display_id(data.display_id);
+                data
+            },
+            result => {
+                self.log_error(&request, result);
+                VRGamepadData::default()
+            }
+        }
+    }
+
+    fn state(&self) -> VRGamepadState {
+        self.request_state(Request::GamepadState(self.remote_id))
+    }
+
+    fn predicted_state(&self, target_time: f64) -> VRGamepadState {
+        self.request_state(Request::PredictedGamepadState(self.remote_id, target_time))
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        let request = Request::SetGamepadReferenceSpace(self.remote_id, space);
+        match self.connection.request(&request) {
+            Ok(Response::Empty) => {},
+            result => self.log_error(&request, result)
+        }
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        let request = Request::GamepadReferenceSpace(self.remote_id);
+        match self.connection.request(&request) {
+            Ok(Response::ReferenceSpace(space)) => space,
+            result => {
+                self.log_error(&request, result);
+                VRReferenceSpace::Seated
+            }
+        }
+    }
+}
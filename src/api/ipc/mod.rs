@@ -0,0 +1,33 @@
+mod connection;
+mod display;
+mod gamepad;
+mod protocol;
+mod server;
+mod service;
+
+use {VRService, VRServiceCreator};
+use std::path::{Path, PathBuf};
+
+pub use self::display::{RemoteVRDisplay, RemoteVRDisplayPtr};
+pub use self::gamepad::{RemoteVRGamepad, RemoteVRGamepadPtr};
+pub use self::server::RemoteVRServer;
+pub use self::service::RemoteVRService;
+
+pub struct RemoteServiceCreator {
+    path: PathBuf
+}
+
+impl RemoteServiceCreator {
+    // Creates services that connect to a RemoteVRServer listening at the socket `path`
+    pub fn new<P: AsRef<Path>>(path: P) -> Box<VRServiceCreator> {
+        Box::new(RemoteServiceCreator {
+            path: path.as_ref().to_path_buf()
+        })
+    }
+}
+
+impl VRServiceCreator for RemoteServiceCreator {
+     fn new_service(&self) -> Box<VRService> {
+         Box::new(RemoteVRService::new(self.path.clone()))
+     }
+}
@@ -0,0 +1,391 @@
+use {VRDisplayData, VREvent, VRFrameData, VRFrameStats, VRGamepadData, VRGamepadState, VRLayer, VRReferenceSpace};
+use vr_wire::{self, VRWireError, WireReader};
+
+// Protocol used between RemoteVRService and RemoteVRServer. Little endian.
+//
+// Messages are framed as described in vr_wire.
+// A connection starts with the client sending a handshake message: "WVRIPC" magic,
+// u16 protocol version, u16 wire version and u64 client id. The server answers it with a response.
+// All the connections of a client use the same id. The server keeps the events of each client
+// until it polls them.
+// Then the client sends requests and the server answers each one with a response, in order.
+// Clients open several connections to run requests in parallel, e.g. to read poses while
+// another thread waits in sync_poses.
+//
+// Request payload: u8 kind + arguments. Devices are identified by their ids in the server.
+//   FetchDisplays, FetchGamepads, PollEvents: empty
+//   DisplayData, SyncPoses, ResetPose, FrameStats, StartPresent, StopPresent, ReferenceSpace: u32 display_id
+//   InmediateFrameData, SyncedFrameData: u32 display_id, f64 near, f64 far
+//   PredictedFrameData: u32 display_id, f64 near, f64 far, f64 target time
+//   SetReferenceSpace: u32 display_id, reference space
+//   SubmitFrame: u32 display_id, layer
+//   SubmitLayers: u32 display_id, u32 count + layers
+//   GamepadData, GamepadState, GamepadReferenceSpace: u32 gamepad_id
+//   PredictedGamepadState: u32 gamepad_id, f64 target time
+//   SetGamepadReferenceSpace: u32 gamepad_id, reference space
+//
+// Response payload: u8 kind + value
+//   Empty: empty. Answers requests without results.
+//   Error: error message
+//   Displays: u32 count + (u32 display_id + display data)
+//   Gamepads: u32 count + (u32 gamepad_id + gamepad data)
+//   Events: u32 count + events
+//   DisplayData, FrameData, FrameStats, GamepadData, GamepadState, ReferenceSpace: the value
+//
+// Values use the encoding described in vr_wire, without the wire version header.
+//
+// Version history:
+//   1: initial version
+pub const MAGIC: &'static [u8] = b"WVRIPC";
+pub const PROTOCOL_VERSION: u16 = 1;
+
+const REQUEST_FETCH_DISPLAYS: u8 = 1;
+const REQUEST_FETCH_GAMEPADS: u8 = 2;
+const REQUEST_POLL_EVENTS: u8 = 3;
+const REQUEST_DISPLAY_DATA: u8 = 4;
+const REQUEST_INMEDIATE_FRAME_DATA: u8 = 5;
+const REQUEST_SYNCED_FRAME_DATA: u8 = 6;
+const REQUEST_PREDICTED_FRAME_DATA: u8 = 7;
+const REQUEST_SYNC_POSES: u8 = 8;
+const REQUEST_RESET_POSE: u8 = 9;
+const REQUEST_SET_REFERENCE_SPACE: u8 = 10;
+const REQUEST_SUBMIT_FRAME: u8 = 11;
+const REQUEST_SUBMIT_LAYERS: u8 = 12;
+const REQUEST_FRAME_STATS: u8 = 13;
+const REQUEST_START_PRESENT: u8 = 14;
+const REQUEST_STOP_PRESENT: u8 = 15;
+const REQUEST_GAMEPAD_DATA: u8 = 16;
+const REQUEST_GAMEPAD_STATE: u8 = 17;
+const REQUEST_PREDICTED_GAMEPAD_STATE: u8 = 18;
+const REQUEST_SET_GAMEPAD_REFERENCE_SPACE: u8 = 19;
+const REQUEST_REFERENCE_SPACE: u8 = 20;
+const REQUEST_GAMEPAD_REFERENCE_SPACE: u8 = 21;
+
+const RESPONSE_EMPTY: u8 = 1;
+const RESPONSE_ERROR: u8 = 2;
+const RESPONSE_DISPLAYS: u8 = 3;
+const RESPONSE_GAMEPADS: u8 = 4;
+const RESPONSE_EVENTS: u8 = 5;
+const RESPONSE_DISPLAY_DATA: u8 = 6;
+const RESPONSE_FRAME_DATA: u8 = 7;
+const RESPONSE_FRAME_STATS: u8 = 8;
+const RESPONSE_GAMEPAD_DATA: u8 = 9;
+const RESPONSE_GAMEPAD_STATE: u8 = 10;
+const RESPONSE_REFERENCE_SPACE: u8 = 11;
+
+// A call to a VRService, VRDisplay or VRGamepad method run by the server
+#[derive(Debug, Clone)]
+pub enum Request {
+    FetchDisplays,
+    FetchGamepads,
+    PollEvents,
+    // params: display_id
+    DisplayData(u32),
+    // params: display_id, near, far
+    InmediateFrameData(u32, f64, f64),
+    // params: display_id, near, far
+    SyncedFrameData(u32, f64, f64),
+    // params: display_id, near, far, target time
+    PredictedFrameData(u32, f64, f64, f64),
+    SyncPoses(u32),
+    ResetPose(u32),
+    SetReferenceSpace(u32, VRReferenceSpace),
+    SubmitFrame(u32, VRLayer),
+    SubmitLayers(u32, Vec<VRLayer>),
+    FrameStats(u32),
+    StartPresent(u32),
+    StopPresent(u32),
+    // params: gamepad_id
+    GamepadData(u32),
+    GamepadState(u32),
+    // params: gamepad_id, target time
+    PredictedGamepadState(u32, f64),
+    SetGamepadReferenceSpace(u32, VRReferenceSpace),
+    ReferenceSpace(u32),
+    GamepadReferenceSpace(u32)
+}
+
+impl Request {
+    // True if running the request twice in the server has the same effect as running it once
+    pub fn is_idempotent(&self) -> bool {
+        match *self {
+            Request::FetchDisplays |
+            Request::FetchGamepads |
+            Request::DisplayData(..) |
+            Request::InmediateFrameData(..) |
+            Request::SyncedFrameData(..) |
+            Request::PredictedFrameData(..) |
+            Request::FrameStats(..) |
+            Request::GamepadData(..) |
+            Request::GamepadState(..) |
+            Request::PredictedGamepadState(..) |
+            Request::ReferenceSpace(..) |
+            Request::GamepadReferenceSpace(..) => true,
+            _ => false
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Response {
+    Empty,
+    Error(String),
+    // Displays and gamepads with their ids in the server
+    Displays(Vec<(u32, VRDisplayData)>),
+    Gamepads(Vec<(u32, VRGamepadData)>),
+    Events(Vec<VREvent>),
+    DisplayData(VRDisplayData),
+    FrameData(VRFrameData),
+    FrameStats(VRFrameStats),
+    GamepadData(VRGamepadData),
+    GamepadState(VRGamepadState),
+    ReferenceSpace(VRReferenceSpace)
+}
+
+pub fn encode_handshake(client_id: u64) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    vr_wire::write_u16(&mut out, PROTOCOL_VERSION);
+    vr_wire::write_u16(&mut out, vr_wire::WIRE_VERSION);
+    vr_wire::write_u64(&mut out, client_id);
+    out
+}
+
+// Checks that the client uses the same protocol and wire versions. Returns the client id.
+pub fn check_handshake(payload: &[u8]) -> Result<u64, String> {
+    let mut reader = WireReader::new(payload);
+    if try!(reader.bytes(MAGIC.len())) != MAGIC {
+        return Err("Not a WebVR IPC client".into());
+    }
+    let protocol_version = try!(reader.u16());
+    let wire_version = try!(reader.u16());
+    if protocol_version != PROTOCOL_VERSION || wire_version != vr_wire::WIRE_VERSION {
+        return Err(format!("Unsupported protocol version {} with wire version {}", protocol_version, wire_version));
+    }
+    let client_id = try!(reader.u64());
+    try!(reader.finish());
+    Ok(client_id)
+}
+
+pub fn encode_request(request: &Request) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_request(&mut out, request);
+    out
+}
+
+fn write_request(out: &mut Vec<u8>, request: &Request) {
+    match *request {
+        Request::FetchDisplays => vr_wire::write_u8(out, REQUEST_FETCH_DISPLAYS),
+        Request::FetchGamepads => vr_wire::write_u8(out, REQUEST_FETCH_GAMEPADS),
+        Request::PollEvents => vr_wire::write_u8(out, REQUEST_POLL_EVENTS),
+        Request::DisplayData(id) => write_device(out, REQUEST_DISPLAY_DATA, id),
+        Request::InmediateFrameData(id, near, far) => {
+            write_device(out, REQUEST_INMEDIATE_FRAME_DATA, id);
+            vr_wire::write_f64(out, near);
+            vr_wire::write_f64(out, far);
+        },
+        Request::SyncedFrameData(id, near, far) => {
+            write_device(out, REQUEST_SYNCED_FRAME_DATA, id);
+            vr_wire::write_f64(out, near);
+            vr_wire::write_f64(out, far);
+        },
+        Request::PredictedFrameData(id, near, far, target_time) => {
+            write_device(out, REQUEST_PREDICTED_FRAME_DATA, id);
+            vr_wire::write_f64(out, near);
+            vr_wire::write_f64(out, far);
+            vr_wire::write_f64(out, target_time);
+        },
+        Request::SyncPoses(id) => write_device(out, REQUEST_SYNC_POSES, id),
+        Request::ResetPose(id) => write_device(out, REQUEST_RESET_POSE, id),
+        Request::SetReferenceSpace(id, space) => {
+            write_device(out, REQUEST_SET_REFERENCE_SPACE, id);
+            vr_wire::write_reference_space(out, space);
+        },
+        Request::SubmitFrame(id, ref layer) => {
+            write_device(out, REQUEST_SUBMIT_FRAME, id);
+            vr_wire::write_layer(out, layer);
+        },
+        Request::SubmitLayers(id, ref layers) => {
+            write_device(out, REQUEST_SUBMIT_LAYERS, id);
+            vr_wire::write_u32(out, layers.len() as u32);
+            for layer in layers {
+                vr_wire::write_layer(out, layer);
+            }
+        },
+        Request::FrameStats(id) => write_device(out, REQUEST_FRAME_STATS, id),
+        Request::StartPresent(id) => write_device(out, REQUEST_START_PRESENT, id),
+        Request::StopPresent(id) => write_device(out, REQUEST_STOP_PRESENT, id),
+        Request::GamepadData(id) => write_device(out, REQUEST_GAMEPAD_DATA, id),
+        Request::GamepadState(id) => write_device(out, REQUEST_GAMEPAD_STATE, id),
+        Request::PredictedGamepadState(id, target_time) => {
+            write_device(out, REQUEST_PREDICTED_GAMEPAD_STATE, id);
+            vr_wire::write_f64(out, target_time);
+        },
+        Request::SetGamepadReferenceSpace(id, space) => {
+            write_device(out, REQUEST_SET_GAMEPAD_REFERENCE_SPACE, id);
+            vr_wire::write_reference_space(out, space);
+        },
+        Request::ReferenceSpace(id) => write_device(out, REQUEST_REFERENCE_SPACE, id),
+        Request::GamepadReferenceSpace(id) => write_device(out, REQUEST_GAMEPAD_REFERENCE_SPACE, id)
+    }
+}
+
+pub fn decode_request(payload: &[u8]) -> Result<Request, VRWireError> {
+    let mut reader = WireReader::new(payload);
+    let reader = &mut reader;
+    let request = match try!(reader.u8()) {
+        REQUEST_FETCH_DISPLAYS => Request::FetchDisplays,
+        REQUEST_FETCH_GAMEPADS => Request::FetchGamepads,
+        REQUEST_POLL_EVENTS => Request::PollEvents,
+        REQUEST_DISPLAY_DATA => Request::DisplayData(try!(reader.u32())),
+        REQUEST_INMEDIATE_FRAME_DATA => {
+            Request::InmediateFrameData(try!(reader.u32()), try!(reader.f64()), try!(reader.f64()))
+        },
+        REQUEST_SYNCED_FRAME_DATA => {
+            Request::SyncedFrameData(try!(reader.u32()), try!(reader.f64()), try!(reader.f64()))
+        },
+        REQUEST_PREDICTED_FRAME_DATA => {
+            Request::PredictedFrameData(try!(reader.u32()), try!(reader.f64()), try!(reader.f64()),
+                                        try!(reader.f64()))
+        },
+        REQUEST_SYNC_POSES => Request::SyncPoses(try!(reader.u32())),
+        REQUEST_RESET_POSE => Request::ResetPose(try!(reader.u32())),
+        REQUEST_SET_REFERENCE_SPACE => {
+            Request::SetReferenceSpace(try!(reader.u32()), try!(vr_wire::read_reference_space(reader)))
+        },
+        REQUEST_SUBMIT_FRAME => Request::SubmitFrame(try!(reader.u32()), try!(vr_wire::read_layer(reader))),
+        REQUEST_SUBMIT_LAYERS => {
+            let id = try!(reader.u32());
+            let count = try!(reader.u32());
+            let mut layers = Vec::new();
+            for _ in 0..count {
+                layers.push(try!(vr_wire::read_layer(reader)));
+            }
+            Request::SubmitLayers(id, layers)
+        },
+        REQUEST_FRAME_STATS => Request::FrameStats(try!(reader.u32())),
+        REQUEST_START_PRESENT => Request::StartPresent(try!(reader.u32())),
+        REQUEST_STOP_PRESENT => Request::StopPresent(try!(reader.u32())),
+        REQUEST_GAMEPAD_DATA => Request::GamepadData(try!(reader.u32())),
+        REQUEST_GAMEPAD_STATE => Request::GamepadState(try!(reader.u32())),
+        REQUEST_PREDICTED_GAMEPAD_STATE => Request::PredictedGamepadState(try!(reader.u32()), try!(reader.f64())),
+        REQUEST_SET_GAMEPAD_REFERENCE_SPACE => {
+            Request::SetGamepadReferenceSpace(try!(reader.u32()), try!(vr_wire::read_reference_space(reader)))
+        },
+        REQUEST_REFERENCE_SPACE => Request::ReferenceSpace(try!(reader.u32())),
+        REQUEST_GAMEPAD_REFERENCE_SPACE => Request::GamepadReferenceSpace(try!(reader.u32())),
+        kind => return Err(VRWireError::InvalidValue("request kind", kind as u32))
+    };
+    try!(reader.finish());
+    Ok(request)
+}
+
+pub fn encode_response(response: &Response) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_response(&mut out, response);
+    out
+}
+
+fn write_response(out: &mut Vec<u8>, response: &Response) {
+    match *response {
+        Response::Empty => vr_wire::write_u8(out, RESPONSE_EMPTY),
+        Response::Error(ref message) => {
+            vr_wire::write_u8(out, RESPONSE_ERROR);
+            vr_wire::write_string(out, message);
+        },
+        Response::Displays(ref displays) => {
+            vr_wire::write_u8(out, RESPONSE_DISPLAYS);
+            vr_wire::write_u32(out, displays.len() as u32);
+            for &(id, ref data) in displays {
+                vr_wire::write_u32(out, id);
+                vr_wire::write_display_data(out, data);
+            }
+        },
+        Response::Gamepads(ref gamepads) => {
+            vr_wire::write_u8(out, RESPONSE_GAMEPADS);
+            vr_wire::write_u32(out, gamepads.len() as u32);
+            for &(id, ref data) in gamepads {
+                vr_wire::write_u32(out, id);
+                vr_wire::write_gamepad_data(out, data);
+            }
+        },
+        Response::Events(ref events) => {
+            vr_wire::write_u8(out, RESPONSE_EVENTS);
+            vr_wire::write_u32(out, events.len() as u32);
+            for event in events {
+                vr_wire::write_event(out, event);
+            }
+        },
+        Response::DisplayData(ref data) => {
+            vr_wire::write_u8(out, RESPONSE_DISPLAY_DATA);
+            vr_wire::write_display_data(out, data);
+        },
+        Response::FrameData(ref data) => {
+            vr_wire::write_u8(out, RESPONSE_FRAME_DATA);
+            vr_wire::write_frame_data(out, data);
+        },
+        Response::FrameStats(ref stats) => {
+            vr_wire::write_u8(out, RESPONSE_FRAME_STATS);
+            vr_wire::write_frame_stats(out, stats);
+        },
+        Response::GamepadData(ref data) => {
+            vr_wire::write_u8(out, RESPONSE_GAMEPAD_DATA);
+            vr_wire::write_gamepad_data(out, data);
+        },
+        Response::GamepadState(ref state) => {
+            vr_wire::write_u8(out, RESPONSE_GAMEPAD_STATE);
+            vr_wire::write_gamepad_state(out, state);
+        },
+        Response::ReferenceSpace(space) => {
+            vr_wire::write_u8(out, RESPONSE_REFERENCE_SPACE);
+            vr_wire::write_reference_space(out, space);
+        }
+    }
+}
+
+pub fn decode_response(payload: &[u8]) -> Result<Response, VRWireError> {
+    let mut reader = WireReader::new(payload);
+    let reader = &mut reader;
+    let response = match try!(reader.u8()) {
+        RESPONSE_EMPTY => Response::Empty,
+        RESPONSE_ERROR => Response::Error(try!(reader.string())),
+        RESPONSE_DISPLAYS => {
+            let count = try!(reader.u32());
+            let mut displays = Vec::new();
+            for _ in 0..count {
+                displays.push((try!(reader.u32()), try!(vr_wire::read_display_data(reader))));
+            }
+            Response::Displays(displays)
+        },
+        RESPONSE_GAMEPADS => {
+            let count = try!(reader.u32());
+            let mut gamepads = Vec::new();
+            for _ in 0..count {
+                gamepads.push((try!(reader.u32()), try!(vr_wire::read_gamepad_data(reader))));
+            }
+            Response::Gamepads(gamepads)
+        },
+        RESPONSE_EVENTS => {
+            let count = try!(reader.u32());
+            let mut events = Vec::new();
+            for _ in 0..count {
+                events.push(try!(vr_wire::read_event(reader)));
+            }
+            Response::Events(events)
+        },
+        RESPONSE_DISPLAY_DATA => Response::DisplayData(try!(vr_wire::read_display_data(reader))),
+        RESPONSE_FRAME_DATA => Response::FrameData(try!(vr_wire::read_frame_data(reader))),
+        RESPONSE_FRAME_STATS => Response::FrameStats(try!(vr_wire::read_frame_stats(reader))),
+        RESPONSE_GAMEPAD_DATA => Response::GamepadData(try!(vr_wire::read_gamepad_data(reader))),
+        RESPONSE_GAMEPAD_STATE => Response::GamepadState(try!(vr_wire::read_gamepad_state(reader))),
+        RESPONSE_REFERENCE_SPACE => Response::ReferenceSpace(try!(vr_wire::read_reference_space(reader))),
+        kind => return Err(VRWireError::InvalidValue("response kind", kind as u32))
+    };
+    try!(reader.finish());
+    Ok(response)
+}
+
+fn write_device(out: &mut Vec<u8>, kind: u8, id: u32) {
+    vr_wire::write_u8(out, kind);
+    vr_wire::write_u32(out, id);
+}
@@ -0,0 +1,295 @@
+use {VRDisplayPtr, VREvent, VRError, VRGamepadPtr, VRServiceManager};
+use super::protocol::{self, Request, Response};
+use vr_wire;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+// Hosts a VRServiceManager and serves its displays and gamepads to RemoteVRServices
+// connected to a Unix domain socket. Each connection is served in its own thread.
+// Events are delivered to every connected client.
+pub struct RemoteVRServer {
+    listener: UnixListener,
+    path: PathBuf,
+    // Device and inode of the socket file created by this server
+    socket_id: (u64, u64),
+    manager: Arc<Mutex<VRServiceManager>>,
+    events: Arc<EventQueues>,
+    stopped: AtomicBool
+}
+
+// Events of the served manager, queued for each client until it polls them.
+// Clients are identified by the id sent in the handshake of each of their connections,
+// and are removed when all their connections are closed.
+struct EventQueues {
+    clients: Mutex<HashMap<u64, ClientEvents>>
+}
+
+struct ClientEvents {
+    connections: usize,
+    events: Vec<VREvent>
+}
+
+impl RemoteVRServer {
+    // Binds the socket at `path`. A socket left by a server that is no longer running is replaced.
+    // Fails if another server is listening at `path`.
+    pub fn bind<P: AsRef<Path>>(path: P, manager: VRServiceManager) -> Result<RemoteVRServer, VRError> {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(metadata) = fs::metadata(&path) {
+            if metadata.file_type().is_socket() {
+                match UnixStream::connect(&path) {
+                    Ok(_) => {
+                        let message = format!("Can't bind {}: another server is listening", path.display());
+                        return Err(VRError::Backend("IPC".into(), message));
+                    },
+                    Err(ref e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+                        let _ = fs::remove_file(&path);
+                    },
+                    Err(_) => {}
+                }
+            }
+        }
+        let listener = try!(UnixListener::bind(&path).map_err(|e| {
+            VRError::Backend("IPC".into(), format!("Can't bind {}: {}", path.display(), e))
+        }));
+        let metadata = try!(fs::metadata(&path).map_err(|e| {
+            VRError::Backend("IPC".into(), format!("Can't bind {}: {}", path.display(), e))
+        }));
+
+        Ok(RemoteVRServer {
+            listener: listener,
+            path: path,
+            socket_id: (metadata.dev(), metadata.ino()),
+            manager: Arc::new(Mutex::new(manager)),
+            events: Arc::new(EventQueues {
+                clients: Mutex::new(HashMap::new())
+            }),
+            stopped: AtomicBool::new(false)
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // The served manager, which can also be used by the server process.
+    // Events polled from it are not delivered to the clients.
+    pub fn manager(&self) -> Arc<Mutex<VRServiceManager>> {
+        self.manager.clone()
+    }
+
+    // Accepts clients until stop is called
+    pub fn run(&self) {
+        for stream in self.listener.incoming() {
+            if self.stopped.load(Ordering::SeqCst) {
+                break;
+            }
+            match stream {
+                Ok(stream) => {
+                    let manager = self.manager.clone();
+                    let events = self.events.clone();
+                    thread::spawn(move || serve(stream, manager, events));
+                },
+                Err(e) => error!("Error accepting IPC client: {}", e)
+            }
+        }
+    }
+
+    // Makes run return. Open connections are served until their clients close them.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake up the blocked accept call
+        let _ = UnixStream::connect(&self.path);
+    }
+}
+
+impl Drop for RemoteVRServer {
+    fn drop(&mut self) {
+        // The socket may have been replaced by another server after this one stopped
+        if let Ok(metadata) = fs::metadata(&self.path) {
+            if (metadata.dev(), metadata.ino()) == self.socket_id {
+                let _ = fs::remove_file(&self.path);
+            }
+        }
+    }
+}
+
+impl EventQueues {
+    fn add_connection(&self, client_id: u64) {
+        let mut clients = self.clients.lock().unwrap();
+        let client = clients.entry(client_id).or_insert_with(|| ClientEvents {
+            connections: 0,
+            events: Vec::new()
+        });
+        client.connections += 1;
+    }
+
+    fn remove_connection(&self, client_id: u64) {
+        let mut clients = self.clients.lock().unwrap();
+        let closed = match clients.get_mut(&client_id) {
+            Some(client) => {
+                client.connections -= 1;
+                client.connections == 0
+            },
+            None => false
+        };
+        if closed {
+            clients.remove(&client_id);
+        }
+    }
+
+    // Polls the manager, queues its events for every client and returns the events of `client_id`
+    fn poll(&self, manager: &Mutex<VRServiceManager>, client_id: u64) -> Vec<VREvent> {
+        // Keep the lock while polling, so events are queued in the order they are polled
+        let mut clients = self.clients.lock().unwrap();
+        let events = manager.lock().unwrap().poll_events();
+        for client in clients.values_mut() {
+            client.events.extend(events.iter().cloned());
+        }
+        clients.get_mut(&client_id).map_or(Vec::new(), |client| client.events.drain(..).collect())
+    }
+}
+
+fn serve(mut stream: UnixStream, manager: Arc<Mutex<VRServiceManager>>, events: Arc<EventQueues>) {
+    let handshake = match vr_wire::read_message(&mut stream) {
+        Ok(payload) => protocol::check_handshake(&payload),
+        // Connections closed without handshake, e.g. the one used by stop
+        Err(_) => return
+    };
+    let response = match handshake {
+        Ok(_) => Response::Empty,
+        Err(ref message) => Response::Error(message.clone())
+    };
+    if vr_wire::write_message(&mut stream, &protocol::encode_response(&response)).is_err() {
+        return;
+    }
+    let client_id = match handshake {
+        Ok(client_id) => client_id,
+        Err(_) => return
+    };
+    events.add_connection(client_id);
+
+    loop {
+        let payload = match vr_wire::read_message(&mut stream) {
+            Ok(payload) => payload,
+            Err(_) => break
+        };
+        let response = match protocol::decode_request(&payload) {
+            Ok(request) => handle_request(&manager, &events, client_id, request),
+            Err(e) => Response::Error(e.to_string())
+        };
+        if let Err(e) = vr_wire::write_message(&mut stream, &protocol::encode_response(&response)) {
+            error!("Error sending IPC response: {}", e);
+            break;
+        }
+    }
+    events.remove_connection(client_id);
+}
+
+fn handle_request(manager: &Mutex<VRServiceManager>, events: &EventQueues, client_id: u64, request: Request) -> Response {
+    match request {
+        Request::FetchDisplays => {
+            let displays = manager.lock().unwrap().get_displays();
+            Response::Displays(displays.iter().map(|display| {
+                let display = display.read().unwrap();
+                (display.id(), display.data())
+            }).collect())
+        },
+        Request::FetchGamepads => {
+            let gamepads = manager.lock().unwrap().get_gamepads();
+            Response::Gamepads(gamepads.iter().map(|gamepad| {
+                let gamepad = gamepad.read().unwrap();
+                (gamepad.id(), gamepad.data())
+            }).collect())
+        },
+        Request::PollEvents => Response::Events(events.poll(manager, client_id)),
+        Request::DisplayData(id) => with_display(manager, id, |display| {
+            Response::DisplayData(display.read().unwrap().data())
+        }),
+        Request::InmediateFrameData(id, near, far) => with_display(manager, id, |display| {
+            Response::FrameData(display.read().unwrap().inmediate_frame_data(near, far))
+        }),
+        Request::SyncedFrameData(id, near, far) => with_display(manager, id, |display| {
+            Response::FrameData(display.read().unwrap().synced_frame_data(near, far))
+        }),
+        Request::PredictedFrameData(id, near, far, target_time) => with_display(manager, id, |display| {
+            Response::FrameData(display.read().unwrap().predicted_frame_data(near, far, target_time))
+        }),
+        Request::SyncPoses(id) => with_display(manager, id, |display| {
+            display.read().unwrap().sync_poses();
+            Response::Empty
+        }),
+        Request::ResetPose(id) => with_display(manager, id, |display| {
+            display.write().unwrap().reset_pose();
+            Response::Empty
+        }),
+        Request::SetReferenceSpace(id, space) => with_display(manager, id, |display| {
+            display.write().unwrap().set_reference_space(space);
+            Response::Empty
+        }),
+        Request::SubmitFrame(id, layer) => with_display(manager, id, |display| {
+            display.write().unwrap().submit_frame(&layer);
+            Response::Empty
+        }),
+        Request::SubmitLayers(id, layers) => with_display(manager, id, |display| {
+            display.write().unwrap().submit_layers(&layers);
+            Response::Empty
+        }),
+        Request::FrameStats(id) => with_display(manager, id, |display| {
+            Response::FrameStats(display.read().unwrap().frame_stats())
+        }),
+        Request::StartPresent(id) => with_display(manager, id, |display| {
+            display.write().unwrap().start_present();
+            Response::Empty
+        }),
+        Request::StopPresent(id) => with_display(manager, id, |display| {
+            display.write().unwrap().stop_present();
+            Response::Empty
+        }),
+        Request::GamepadData(id) => with_gamepad(manager, id, |gamepad| {
+            Response::GamepadData(gamepad.read().unwrap().data())
+        }),
+        Request::GamepadState(id) => with_gamepad(manager, id, |gamepad| {
+            Response::GamepadState(gamepad.read().unwrap().state())
+        }),
+        Request::PredictedGamepadState(id, target_time) => with_gamepad(manager, id, |gamepad| {
+            Response::GamepadState(gamepad.read().unwrap().predicted_state(target_time))
+        }),
+        Request::SetGamepadReferenceSpace(id, space) => with_gamepad(manager, id, |gamepad| {
+            gamepad.write().unwrap().set_reference_space(space);
+            Response::Empty
+        }),
+        Request::ReferenceSpace(id) => with_display(manager, id, |display| {
+            Response::ReferenceSpace(display.read().unwrap().reference_space())
+        }),
+        Request::GamepadReferenceSpace(id) => with_gamepad(manager, id, |gamepad| {
+            Response::ReferenceSpace(gamepad.read().unwrap().reference_space())
+        })
+    }
+}
+
+// The manager lock is released before calling `f`, so blocking calls like sync_poses
+// don't block the requests of other connections.
+fn with_display<F>(manager: &Mutex<VRServiceManager>, id: u32, f: F) -> Response
+    where F: FnOnce(&VRDisplayPtr) -> Response {
+    let display = manager.lock().unwrap().get_display(id).cloned();
+    match display {
+        Some(display) => f(&display),
+        None => Response::Error(format!("Unknown display {}", id))
+    }
+}
+
+fn with_gamepad<F>(manager: &Mutex<VRServiceManager>, id: u32, f: F) -> Response
+    where F: FnOnce(&VRGamepadPtr) -> Response {
+    let gamepad = manager.lock().unwrap().get_gamepad(id).cloned();
+    match gamepad {
+        Some(gamepad) => f(&gamepad),
+        None => Response::Error(format!("Unknown gamepad {}", id))
+    }
+}
@@ -0,0 +1,103 @@
+use {VRService, VRDisplayPtr, VRError, VRGamepadPtr, VREvent};
+use super::connection::{RemoteConnection, RemoteConnectionPtr};
+use super::display::{RemoteVRDisplay, RemoteVRDisplayPtr};
+use super::gamepad::{RemoteVRGamepad, RemoteVRGamepadPtr};
+use super::protocol::{Request, Response};
+use std::path::PathBuf;
+
+// Service that exposes the displays and gamepads of a RemoteVRServer running in another process.
+// Device ids are local to the client process.
+pub struct RemoteVRService {
+    path: PathBuf,
+    connection: Option<RemoteConnectionPtr>,
+    displays: Vec<RemoteVRDisplayPtr>,
+    gamepads: Vec<RemoteVRGamepadPtr>
+}
+
+impl VRService for RemoteVRService {
+    fn initialize(&mut self) -> Result<(), VRError> {
+        if self.connection.is_some() {
+            return Ok(());
+        }
+        let connection = try!(RemoteConnection::open(&self.path).map_err(backend_error));
+        self.connection = Some(connection);
+        Ok(())
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, VRError> {
+        let connection = try!(self.connection());
+        let remote_ids = match try!(connection.request(&Request::FetchDisplays).map_err(backend_error)) {
+            Response::Displays(displays) => displays.into_iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            response => return Err(unexpected_response(response))
+        };
+        for remote_id in remote_ids {
+            if !self.displays.iter().any(|d| d.read().unwrap().remote_id() == remote_id) {
+                self.displays.push(RemoteVRDisplay::new(remote_id, connection.clone()));
+            }
+        }
+        Ok(self.displays.iter().map(|d| d.clone() as VRDisplayPtr).collect())
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, VRError> {
+        let connection = try!(self.connection());
+        let remote_ids = match try!(connection.request(&Request::FetchGamepads).map_err(backend_error)) {
+            Response::Gamepads(gamepads) => gamepads.into_iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            response => return Err(unexpected_response(response))
+        };
+        for remote_id in remote_ids {
+            if !self.gamepads.iter().any(|g| g.read().unwrap().remote_id() == remote_id) {
+                self.gamepads.push(RemoteVRGamepad::new(remote_id, connection.clone()));
+            }
+        }
+        Ok(self.gamepads.iter().map(|g| g.clone() as VRGamepadPtr).collect())
+    }
+
+    fn is_available(&self) -> bool {
+        self.connection.is_some() || self.path.exists()
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        let connection = match self.connection {
+            Some(ref connection) => connection,
+            None => return Vec::new()
+        };
+        match connection.request(&Request::PollEvents) {
+            Ok(Response::Events(events)) => {
+                events.into_iter().map(|event| connection.remap_event(event)).collect()
+            },
+            Ok(response) => {
+                error!("{}", unexpected_response(response));
+                Vec::new()
+            },
+            Err(message) => {
+                error!("Error polling IPC events: {}", message);
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl RemoteVRService {
+    // Creates a service that connects to the server socket at `path` when it's initialized
+    pub fn new(path: PathBuf) -> RemoteVRService {
+        RemoteVRService {
+            path: path,
+            connection: None,
+            displays: Vec::new(),
+            gamepads: Vec::new()
+        }
+    }
+
+    fn connection(&mut self) -> Result<RemoteConnectionPtr, VRError> {
+        try!(self.initialize());
+        Ok(self.connection.as_ref().unwrap().clone())
+    }
+}
+
+fn backend_error(message: String) -> VRError {
+    VRError::Backend("IPC".into(), message)
+}
+
+fn unexpected_response(response: Response) -> VRError {
+    backend_error(format!("Unexpected response {:?}", response))
+}
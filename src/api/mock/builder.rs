@@ -0,0 +1,203 @@
+use {VRDisplayCapabilities, VRDisplayData, VREyeParameters, VRFieldOfView, VRStageBounds, VRStageParameters};
+use super::super::utils;
+use super::display::{MockVRDisplay, MockVRDisplayPtr};
+use super::motion::MockMotion;
+
+// Number of layers composited by the simulated displays
+const DEFAULT_MAX_LAYERS: u64 = 4;
+
+// Headset geometries simulated by the mock display.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MockDisplayProfile {
+    // Room scale HTC Vive
+    Vive,
+    // Oculus Rift CV1
+    RiftCV1,
+    // Google Cardboard viewer
+    Cardboard,
+    // Orientation only (3DOF) standalone viewer
+    OrientationOnly
+}
+
+impl MockDisplayProfile {
+    // Returns the display data simulated by the profile.
+    // display_id and connected fields are managed by the mock display.
+    pub fn data(&self) -> VRDisplayData {
+        let mut data = VRDisplayData::default();
+        match *self {
+            MockDisplayProfile::Vive => {
+                data.display_name = "Mock HTC Vive".into();
+                data.capabilities = capabilities(true, true);
+                data.stage_parameters = Some(VRStageParameters {
+                    sitting_to_standing_transform: [-0.9317312, 0.0, 0.36314875, 0.0, 0.0, 0.99999994, 0.0, 0.0,
+                                                    -0.36314875, 0.0, -0.9317312, 0.0, 0.23767996, 1.6813644,
+                                                    0.45370483, 1.0],
+                    size_x: 2.0,
+                    size_z: 2.0,
+                    bounds: utils::rect_stage_bounds(2.0, 2.0)
+                });
+                data.left_eye_parameters = eye([-0.035949998, 0.0, 0.015], (1512, 1680),
+                                               [55.82093048095703, 51.26948547363281,
+                                                55.707801818847656, 54.42263412475586]);
+                data.right_eye_parameters = eye([0.035949998, 0.0, 0.015], (1512, 1680),
+                                                [55.898048400878906, 54.37410354614258,
+                                                 55.614715576171875, 51.304901123046875]);
+            },
+            MockDisplayProfile::RiftCV1 => {
+                data.display_name = "Mock Oculus Rift CV1".into();
+                data.capabilities = capabilities(true, true);
+                data.stage_parameters = Some(VRStageParameters {
+                    sitting_to_standing_transform: [1.0, 0.0, 0.0, 0.0,
+                                                    0.0, 1.0, 0.0, 0.0,
+                                                    0.0, 0.0, 1.0, 0.0,
+                                                    0.0, 1.2, 0.0, 1.0],
+                    size_x: 2.5,
+                    size_z: 2.0,
+                    bounds: utils::rect_stage_bounds(2.5, 2.0)
+                });
+                data.left_eye_parameters = eye([-0.032, 0.0, 0.0], (1344, 1600),
+                                               [41.65, 35.57, 48.0, 43.97]);
+                data.right_eye_parameters = eye([0.032, 0.0, 0.0], (1344, 1600),
+                                                [41.65, 43.97, 48.0, 35.57]);
+            },
+            MockDisplayProfile::Cardboard => {
+                data.display_name = "Mock Cardboard".into();
+                data.capabilities = capabilities(false, false);
+                data.left_eye_parameters = eye([-0.032, 0.0, 0.0], (960, 1080),
+                                               [50.0, 45.0, 50.0, 50.0]);
+                data.right_eye_parameters = eye([0.032, 0.0, 0.0], (960, 1080),
+                                                [50.0, 50.0, 50.0, 45.0]);
+            },
+            MockDisplayProfile::OrientationOnly => {
+                data.display_name = "Mock 3DOF Viewer".into();
+                data.capabilities = capabilities(false, false);
+                data.left_eye_parameters = eye([-0.032, 0.0, 0.0], (1280, 1440),
+                                               [45.0, 45.0, 45.0, 45.0]);
+                data.right_eye_parameters = eye([0.032, 0.0, 0.0], (1280, 1440),
+                                                [45.0, 45.0, 45.0, 45.0]);
+            }
+        }
+        data
+    }
+}
+
+// Configures and creates mock displays.
+// Defaults to the Vive profile.
+pub struct MockDisplayBuilder {
+    data: VRDisplayData,
+    motion: MockMotion
+}
+
+impl MockDisplayBuilder {
+    pub fn new() -> MockDisplayBuilder {
+        MockDisplayBuilder {
+            data: MockDisplayProfile::Vive.data(),
+            motion: MockMotion::default()
+        }
+    }
+
+    // Replaces the display data with the values of a profile
+    pub fn profile(mut self, profile: MockDisplayProfile) -> MockDisplayBuilder {
+        self.data = profile.data();
+        self
+    }
+
+    // Uses a fully custom display data. display_id and connected fields are ignored.
+    pub fn data(mut self, data: VRDisplayData) -> MockDisplayBuilder {
+        self.data = data;
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> MockDisplayBuilder {
+        self.data.display_name = name.into();
+        self
+    }
+
+    pub fn capabilities(mut self, capabilities: VRDisplayCapabilities) -> MockDisplayBuilder {
+        self.data.capabilities = capabilities;
+        self
+    }
+
+    // Maximum number of layers accepted by submit_layers. 0 disables presentation.
+    pub fn max_layers(mut self, max_layers: u64) -> MockDisplayBuilder {
+        self.data.capabilities.max_layers = max_layers;
+        self.data.capabilities.can_present = max_layers > 0;
+        self
+    }
+
+    pub fn eye_parameters(mut self, left: VREyeParameters, right: VREyeParameters) -> MockDisplayBuilder {
+        self.data.left_eye_parameters = left;
+        self.data.right_eye_parameters = right;
+        self
+    }
+
+    pub fn stage_parameters(mut self, stage: Option<VRStageParameters>) -> MockDisplayBuilder {
+        self.data.stage_parameters = stage;
+        self
+    }
+
+    // Play-area bounds reported in the stage parameters. See set_stage_bounds.
+    pub fn stage_bounds(mut self, bounds: VRStageBounds) -> MockDisplayBuilder {
+        set_stage_bounds(&mut self.data, bounds);
+        self
+    }
+
+    pub fn motion(mut self, motion: MockMotion) -> MockDisplayBuilder {
+        self.motion = motion;
+        self
+    }
+
+    pub fn build(self) -> MockVRDisplayPtr {
+        MockVRDisplay::with_data(self.data, self.motion)
+    }
+}
+
+fn capabilities(has_position: bool, has_external_display: bool) -> VRDisplayCapabilities {
+    VRDisplayCapabilities {
+        has_position: has_position,
+        has_orientation: true,
+        has_external_display: has_external_display,
+        can_present: true,
+        max_layers: DEFAULT_MAX_LAYERS
+    }
+}
+
+// fov: [up, right, down, left] in degrees
+fn eye(offset: [f32; 3], render_size: (u32, u32), fov: [f64; 4]) -> VREyeParameters {
+    VREyeParameters {
+        offset: offset,
+        render_width: render_size.0,
+        render_height: render_size.1,
+        field_of_view: VRFieldOfView {
+            up_degrees: fov[0],
+            right_degrees: fov[1],
+            down_degrees: fov[2],
+            left_degrees: fov[3]
+        }
+    }
+}
+
+// Sets the play-area bounds and updates the play-area size to the extents of the polygon.
+// Displays without stage parameters get the default sitting to standing transform.
+pub fn set_stage_bounds(data: &mut VRDisplayData, bounds: VRStageBounds) {
+    let (size_x, size_z) = match bounds {
+        VRStageBounds::Polygon(ref points) if !points.is_empty() => {
+            let extent = |axis: usize| {
+                let min = points.iter().fold(points[0][axis], |min, p| min.min(p[axis]));
+                let max = points.iter().fold(points[0][axis], |max, p| max.max(p[axis]));
+                max - min
+            };
+            (extent(0), extent(2))
+        },
+        _ => (0.0, 0.0)
+    };
+    let stage = data.stage_parameters.get_or_insert(VRStageParameters {
+        sitting_to_standing_transform: utils::DEFAULT_SITTING_TO_STANDING_TRANSFORM,
+        size_x: 0.0,
+        size_z: 0.0,
+        bounds: VRStageBounds::NotAvailable
+    });
+    stage.size_x = size_x;
+    stage.size_z = size_z;
+    stage.bounds = bounds;
+}
@@ -0,0 +1,101 @@
+use super::super::utils;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Time source used by the mock backend to animate poses and simulate vsync.
+// The handle can be cloned to share the same clock between displays and tests.
+#[derive(Clone)]
+pub enum MockClock {
+    // Real monotonic clock. Waiting for a time blocks the calling thread.
+    Monotonic {
+        start: Instant,
+        // Wall time in milliseconds when the clock was created
+        start_timestamp: f64
+    },
+
+    // Clock manually driven by the tests. Time only advances when `advance` is called
+    // or when a display waits for the next vblank, so frame pacing is fully deterministic.
+    Manual(Arc<Mutex<f64>>)
+}
+
+impl MockClock {
+    pub fn monotonic() -> MockClock {
+        MockClock::Monotonic {
+            start: Instant::now(),
+            start_timestamp: utils::timestamp()
+        }
+    }
+
+    // Creates a manual clock starting at 0 seconds
+    pub fn manual() -> MockClock {
+        MockClock::Manual(Arc::new(Mutex::new(0.0)))
+    }
+
+    // Seconds elapsed since the clock was created
+    pub fn now(&self) -> f64 {
+        match *self {
+            MockClock::Monotonic { start, .. } => {
+                let elapsed = start.elapsed();
+                elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9
+            },
+            MockClock::Manual(ref time) => *time.lock().unwrap()
+        }
+    }
+
+    // Converts a clock time in seconds to a VRFrameData timestamp in milliseconds.
+    pub fn to_timestamp(&self, time: f64) -> f64 {
+        match *self {
+            MockClock::Monotonic { start_timestamp, .. } => start_timestamp + time * 1000.0,
+            MockClock::Manual(_) => time * 1000.0
+        }
+    }
+
+    // Converts a VRFrameData timestamp in milliseconds to a clock time in seconds.
+    pub fn from_timestamp(&self, timestamp: f64) -> f64 {
+        match *self {
+            MockClock::Monotonic { start_timestamp, .. } => (timestamp - start_timestamp) / 1000.0,
+            MockClock::Manual(_) => timestamp / 1000.0
+        }
+    }
+
+    // Current time as a VRFrameData timestamp in milliseconds
+    pub fn timestamp(&self) -> f64 {
+        self.to_timestamp(self.now())
+    }
+
+    // Advances the clock by the given number of seconds.
+    // Monotonic clocks sleep the calling thread.
+    pub fn advance(&self, seconds: f64) {
+        if seconds <= 0.0 {
+            return;
+        }
+        match *self {
+            MockClock::Monotonic { .. } => thread::sleep(to_duration(seconds)),
+            MockClock::Manual(ref time) => *time.lock().unwrap() += seconds
+        }
+    }
+
+    // Blocks until the clock reaches `time` seconds. Manual clocks jump directly to that time.
+    pub fn wait_until(&self, time: f64) {
+        match *self {
+            MockClock::Monotonic { .. } => {
+                let now = self.now();
+                if time > now {
+                    thread::sleep(to_duration(time - now));
+                }
+            },
+            MockClock::Manual(ref current) => {
+                let mut current = current.lock().unwrap();
+                if time > *current {
+                    *current = time;
+                }
+            }
+        }
+    }
+}
+
+fn to_duration(seconds: f64) -> Duration {
+    let secs = seconds.floor();
+    Duration::new(secs as u64, ((seconds - secs) * 1e9) as u32)
+}
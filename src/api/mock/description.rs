@@ -0,0 +1,132 @@
+#![cfg(feature = "mock-file")]
+
+use {VRDisplayCapabilities, VRDisplayData, VREyeParameters, VRGamepadHand, VRStageParameters};
+use super::builder::MockDisplayBuilder;
+use super::motion::{MockMotion, MockPoseKeyframe};
+use super::service::MockVRService;
+use serde_json;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use toml;
+
+// Mock device described in a JSON or TOML file.
+//
+// JSON example:
+// {
+//   "display_name": "Custom HMD",
+//   "capabilities": { "has_position": true, "has_orientation": true, "has_external_display": true,
+//                     "can_present": true, "max_layers": 1 },
+//   "left_eye_parameters": { "offset": [-0.032, 0.0, 0.0], "render_width": 1200, "render_height": 1300,
+//                            "field_of_view": { "up_degrees": 45.0, "right_degrees": 45.0,
+//                                               "down_degrees": 45.0, "left_degrees": 45.0 } },
+//   "right_eye_parameters": { ... },
+//   "stage_parameters": { "sitting_to_standing_transform": [...], "size_x": 2.0, "size_z": 2.0,
+//                         "bounds": { "Polygon": [[-1.0, 0.0, -1.0], [1.0, 0.0, -1.0], [1.0, 0.0, 1.0]] } },
+//   "refresh_rate": 90.0,
+//   "gamepads": [{ "name": "Left controller", "hand": "Left", "buttons": 4, "axes": 3 }],
+//   "timeline": { "looping": true, "keyframes": [{ "time": 0.0, "position": [0.0, 1.6, 0.0],
+//                                                   "orientation": [0.0, 0.0, 0.0, 1.0] }] }
+// }
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockDisplayDescription {
+    pub display_name: String,
+    pub capabilities: VRDisplayCapabilities,
+    pub left_eye_parameters: VREyeParameters,
+    pub right_eye_parameters: VREyeParameters,
+    #[serde(default)]
+    pub stage_parameters: Option<VRStageParameters>,
+    #[serde(default)]
+    pub refresh_rate: Option<f64>,
+    #[serde(default)]
+    pub gamepads: Vec<MockGamepadDescription>,
+    #[serde(default)]
+    pub timeline: Option<MockTimelineDescription>
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockGamepadDescription {
+    pub name: String,
+    pub hand: VRGamepadHand,
+    pub buttons: usize,
+    pub axes: usize
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockTimelineDescription {
+    #[serde(default)]
+    pub looping: bool,
+    pub keyframes: Vec<MockPoseKeyframe>
+}
+
+impl MockDisplayDescription {
+    // Loads a description file. Files with the .toml extension are parsed as TOML, any other file as JSON.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<MockDisplayDescription, String> {
+        let path = path.as_ref();
+        let mut content = String::new();
+        let mut file = try!(File::open(path).map_err(|e| format!("Error opening {:?}: {}", path, e)));
+        try!(file.read_to_string(&mut content).map_err(|e| format!("Error reading {:?}: {}", path, e)));
+
+        let is_toml = path.extension().map_or(false, |ext| ext == "toml");
+        let description: MockDisplayDescription = if is_toml {
+            try!(toml::from_str(&content).map_err(|e| format!("Error parsing {:?}: {}", path, e)))
+        } else {
+            try!(serde_json::from_str(&content).map_err(|e| format!("Error parsing {:?}: {}", path, e)))
+        };
+        try!(description.validate().map_err(|e| format!("Invalid description {:?}: {}", path, e)));
+        Ok(description)
+    }
+
+    // Checks the values that the parsers accept but the mock display can't simulate
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(refresh_rate) = self.refresh_rate {
+            if !(refresh_rate > 0.0 && refresh_rate.is_finite()) {
+                return Err(format!("Invalid refresh rate {}", refresh_rate));
+            }
+        }
+        if let Some(ref timeline) = self.timeline {
+            if let Some(keyframe) = timeline.keyframes.iter().find(|keyframe| !keyframe.time.is_finite()) {
+                return Err(format!("Invalid keyframe time {}", keyframe.time));
+            }
+        }
+        Ok(())
+    }
+
+    // Creates a mock service with a display and gamepads matching the description.
+    // Fails with the validate error if the description has values the mock display can't simulate.
+    pub fn to_service(&self) -> Result<MockVRService, String> {
+        try!(self.validate());
+
+        let mut data = VRDisplayData::default();
+        data.display_name = self.display_name.clone();
+        data.capabilities = self.capabilities.clone();
+        data.left_eye_parameters = self.left_eye_parameters.clone();
+        data.right_eye_parameters = self.right_eye_parameters.clone();
+        data.stage_parameters = self.stage_parameters.clone();
+
+        let mut builder = MockDisplayBuilder::new().data(data);
+        if let Some(ref timeline) = self.timeline {
+            let mut keyframes = timeline.keyframes.clone();
+            keyframes.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+            builder = builder.motion(MockMotion::Timeline {
+                keyframes: keyframes,
+                looping: timeline.looping
+            });
+        }
+
+        let mut service = MockVRService::new();
+        let display = service.set_display(builder);
+        if let Some(refresh_rate) = self.refresh_rate {
+            try!(display.write().unwrap().set_refresh_rate(refresh_rate).map_err(|e| e.description().to_owned()));
+        }
+
+        for description in &self.gamepads {
+            let gamepad = service.add_gamepad(description.hand.clone(), description.buttons, description.axes);
+            gamepad.write().unwrap().set_name(&description.name);
+        }
+
+        Ok(service)
+    }
+}
@@ -1,24 +1,365 @@
-use {VRDisplay, VRDisplayData, VRFrameData, VRStageParameters, VRLayer};
+use {VRDisplay, VRDisplayData, VRError, VRFrameData, VRFrameStats, VRLayer, VRPose, VRReferenceSpace, VRStageBounds};
+use super::builder::{self, MockDisplayBuilder};
+use super::clock::MockClock;
+use super::failures::{MockPoseFailure, MockPoseFailureWindow};
+use super::motion::MockMotion;
+use super::noise::MockTrackingNoise;
 use super::super::utils;
-use std::sync::Arc;
-use std::cell::RefCell;
-pub type MockVRDisplayPtr = Arc<RefCell<MockVRDisplay>>;
-use std::time::Duration;
-use std::thread;
+use std::sync::{Arc, Mutex, RwLock};
+pub type MockVRDisplayPtr = Arc<RwLock<MockVRDisplay>>;
+use std::collections::VecDeque;
+use std::f32;
+
+// Number of submitted frames kept by default
+const DEFAULT_SUBMITTED_FRAMES_CAPACITY: usize = 100;
+
+// Refresh rate in Hz used by default
+const DEFAULT_REFRESH_RATE: f64 = 90.0;
+
+// A layer stack submitted to the mock display
+#[derive(Debug, Clone)]
+pub struct MockSubmittedFrame {
+    // Index of the frame started by the last sync_poses call. The first frame index is 1.
+    pub frame_index: u64,
+    // Pose synced for this frame.
+    // None if submit_frame was not paired with a previous sync_poses call.
+    pub pose: Option<VRPose>,
+    // Bottom layer of the stack
+    pub layer: VRLayer,
+    // All the submitted layers sorted from bottom to top
+    pub layers: Vec<VRLayer>
+}
+
+impl MockSubmittedFrame {
+    // Difference between the pose synced for this frame and the render pose of the bottom layer:
+    // (distance in meters, rotation angle in radians).
+    // Useful to detect content rendered with a different pose than the one returned by sync_poses.
+    // None if any of the poses is missing.
+    pub fn render_pose_delta(&self) -> Option<(f32, f32)> {
+        let (synced, rendered) = match (self.pose.as_ref(), self.layer.render_pose.as_ref()) {
+            (Some(synced), Some(rendered)) => (synced, rendered),
+            _ => return None
+        };
+
+        let distance = match (synced.position, rendered.position) {
+            (Some(a), Some(b)) => {
+                let (dx, dy, dz) = (a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+                (dx * dx + dy * dy + dz * dz).sqrt()
+            },
+            _ => 0.0
+        };
+        let angle = match (synced.orientation, rendered.orientation) {
+            (Some(a), Some(b)) => {
+                let dot = a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+                2.0 * dot.abs().min(1.0).acos()
+            },
+            _ => 0.0
+        };
+        Some((distance, angle))
+    }
+}
+
+// Reasons why the mock display rejects a submitted layer stack
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockLayerError {
+    NoLayers,
+    // The number of layers exceeds VRDisplayCapabilities::max_layers
+    TooManyLayers {
+        count: usize,
+        max_layers: u64
+    },
+    // Opacity out of the [0, 1] range. Contains the index of the layer in the submitted slice.
+    InvalidOpacity(usize),
+    // Empty bounds or bounds outside of the texture. Contains the index of the layer in the submitted slice.
+    InvalidBounds(usize),
+    // Depth range with near <= 0 or far <= near. Contains the index of the layer in the submitted slice.
+    InvalidDepth(usize)
+}
 
 pub struct MockVRDisplay {
-    display_id: u32
+    display_id: u32,
+    data: VRDisplayData,
+    motion: MockMotion,
+    clock: MockClock,
+    // Clock time when the motion started
+    motion_start: f64,
+    refresh_rate: f64,
+    // Frame pacing state. sync_poses updates it through a shared reference.
+    sync: Mutex<MockSyncState>,
+    submitted_frames: VecDeque<MockSubmittedFrame>,
+    submitted_frames_capacity: usize,
+    layer_errors: Vec<MockLayerError>,
+    connected: bool,
+    pose_failures: Vec<MockPoseFailureWindow>,
+    tracking_noise: MockTrackingNoise,
+    reference_space: VRReferenceSpace
 }
 
-unsafe impl Send for MockVRDisplay {}
-unsafe impl Sync for MockVRDisplay {}
+#[derive(Default)]
+struct MockSyncState {
+    // Index of the vblank waited in the last sync_poses call
+    last_vblank: Option<u64>,
+    missed_frames: u64,
+    frame_index: u64,
+    // Predicted display time of the synced frame
+    synced_time: Option<f64>,
+    synced_pose: Option<VRPose>,
+    // Clock time when the last sync_poses call returned
+    frame_start: Option<f64>,
+    // Seconds between the last two sync_poses calls
+    frame_interval: f64,
+    // Seconds between sync_poses and submit_frame in the last submitted frame
+    cpu_frame_time: f64
+}
 
 impl MockVRDisplay {
+    // Creates a display using the default mock profile
     pub fn new() -> MockVRDisplayPtr {
-        Arc::new(RefCell::new(MockVRDisplay {
+        MockDisplayBuilder::new().build()
+    }
+
+    pub fn with_data(data: VRDisplayData, motion: MockMotion) -> MockVRDisplayPtr {
+        let clock = MockClock::monotonic();
+        Arc::new(RwLock::new(MockVRDisplay {
             display_id: utils::new_id(),
+            data: data,
+            motion: motion,
+            motion_start: clock.now(),
+            clock: clock,
+            refresh_rate: DEFAULT_REFRESH_RATE,
+            sync: Mutex::new(MockSyncState::default()),
+            submitted_frames: VecDeque::new(),
+            submitted_frames_capacity: DEFAULT_SUBMITTED_FRAMES_CAPACITY,
+            layer_errors: Vec::new(),
+            connected: true,
+            pose_failures: Vec::new(),
+            tracking_noise: MockTrackingNoise::default(),
+            reference_space: VRReferenceSpace::Seated
         }))
     }
+
+    // Replaces the simulated display data. display_id and connected fields are ignored.
+    // Use set_connected to change the connection state.
+    pub fn set_data(&mut self, data: VRDisplayData) {
+        self.data = data;
+    }
+
+    // Sets the play-area bounds reported in the stage parameters.
+    // The play-area size is updated to the extents of the polygon.
+    pub fn set_stage_bounds(&mut self, bounds: VRStageBounds) {
+        builder::set_stage_bounds(&mut self.data, bounds);
+    }
+
+    // Sets the motion used to animate the pose of the display.
+    // The animation starts from the beginning.
+    pub fn set_motion(&mut self, motion: MockMotion) {
+        self.motion = motion;
+        self.motion_start = self.clock.now();
+    }
+
+    pub fn motion(&self) -> &MockMotion {
+        &self.motion
+    }
+
+    // Index of the frame started by the last sync_poses call. 0 if sync_poses has not been called yet.
+    pub fn frame_index(&self) -> u64 {
+        self.sync.lock().unwrap().frame_index
+    }
+
+    // Returns the history of submitted frames, from oldest to newest.
+    pub fn submitted_frames(&self) -> Vec<MockSubmittedFrame> {
+        self.submitted_frames.iter().cloned().collect()
+    }
+
+    pub fn last_submitted_frame(&self) -> Option<MockSubmittedFrame> {
+        self.submitted_frames.back().cloned()
+    }
+
+    pub fn clear_submitted_frames(&mut self) {
+        self.submitted_frames.clear();
+    }
+
+    // Sets the maximum number of submitted frames kept in the history.
+    // The oldest frames are discarded first.
+    pub fn set_submitted_frames_capacity(&mut self, capacity: usize) {
+        self.submitted_frames_capacity = capacity;
+        while self.submitted_frames.len() > capacity {
+            self.submitted_frames.pop_front();
+        }
+    }
+
+    // Returns the errors of the rejected layer stacks, from oldest to newest.
+    // Rejected stacks are not added to the submitted frames.
+    pub fn layer_errors(&self) -> Vec<MockLayerError> {
+        self.layer_errors.clone()
+    }
+
+    pub fn clear_layer_errors(&mut self) {
+        self.layer_errors.clear();
+    }
+
+    pub fn clock(&self) -> MockClock {
+        self.clock.clone()
+    }
+
+    // Sets the time source used for animation and vsync. The animation and vsync state are restarted.
+    pub fn set_clock(&mut self, clock: MockClock) {
+        self.motion_start = clock.now();
+        self.clock = clock;
+        let mut sync = self.sync.lock().unwrap();
+        sync.last_vblank = None;
+        sync.synced_time = None;
+        sync.frame_start = None;
+    }
+
+    pub fn refresh_rate(&self) -> f64 {
+        self.refresh_rate
+    }
+
+    // Sets the simulated refresh rate in Hz (e.g. 60, 72, 90 or 120).
+    // Fails if the rate is not a finite positive number.
+    pub fn set_refresh_rate(&mut self, hz: f64) -> Result<(), VRError> {
+        if !(hz > 0.0 && hz.is_finite()) {
+            return Err(VRError::Backend("Mock".into(), format!("Invalid refresh rate {}", hz)));
+        }
+        self.refresh_rate = hz;
+        // vblank indices are relative to the frame duration
+        self.sync.lock().unwrap().last_vblank = None;
+        Ok(())
+    }
+
+    // Duration of a frame in seconds
+    pub fn frame_duration(&self) -> f64 {
+        1.0 / self.refresh_rate
+    }
+
+    // Total number of vblanks missed between consecutive sync_poses calls
+    pub fn missed_frames(&self) -> u64 {
+        self.sync.lock().unwrap().missed_frames
+    }
+
+    // Clock time in seconds of the vblank waited in the last sync_poses call
+    pub fn last_vblank_time(&self) -> Option<f64> {
+        self.sync.lock().unwrap().last_vblank.map(|index| index as f64 * self.frame_duration())
+    }
+
+    // Predicted time, as a VRFrameData timestamp in milliseconds, when the synced frame
+    // will be presented on the display. It's the vblank following the one waited in sync_poses.
+    pub fn predicted_display_time(&self) -> Option<f64> {
+        self.sync.lock().unwrap().synced_time.map(|time| self.clock.to_timestamp(time))
+    }
+
+    // Sets the value of VRDisplayData::connected
+    pub fn set_connected(&mut self, connected: bool) {
+        self.connected = connected;
+    }
+
+    // Breaks the poses during a time window of the display clock
+    pub fn add_pose_failure(&mut self, window: MockPoseFailureWindow) {
+        self.pose_failures.push(window);
+    }
+
+    // Breaks the poses from now until `duration` seconds have elapsed in the display clock
+    pub fn fail_poses(&mut self, failure: MockPoseFailure, duration: f64) {
+        let now = self.clock.now();
+        self.add_pose_failure(MockPoseFailureWindow {
+            start: now,
+            end: now + duration,
+            failure: failure
+        });
+    }
+
+    pub fn clear_pose_failures(&mut self) {
+        self.pose_failures.clear();
+    }
+
+    // Sets the jitter, drift and latency applied to the simulated tracking
+    pub fn set_tracking_noise(&mut self, noise: MockTrackingNoise) {
+        self.tracking_noise = noise;
+    }
+
+    pub fn tracking_noise(&self) -> MockTrackingNoise {
+        self.tracking_noise
+    }
+
+    fn validate_layers(&self, layers: &[VRLayer]) -> Result<(), MockLayerError> {
+        if layers.is_empty() {
+            return Err(MockLayerError::NoLayers);
+        }
+        let max_layers = self.data.capabilities.max_layers;
+        if layers.len() as u64 > max_layers {
+            return Err(MockLayerError::TooManyLayers {
+                count: layers.len(),
+                max_layers: max_layers
+            });
+        }
+        for (index, layer) in layers.iter().enumerate() {
+            if !(layer.opacity >= 0.0 && layer.opacity <= 1.0) {
+                return Err(MockLayerError::InvalidOpacity(index));
+            }
+            if !valid_bounds(&layer.left_bounds) || !valid_bounds(&layer.right_bounds) {
+                return Err(MockLayerError::InvalidBounds(index));
+            }
+            if let Some(ref depth) = layer.depth {
+                if !(depth.near > 0.0 && depth.far > depth.near) {
+                    return Err(MockLayerError::InvalidDepth(index));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn pose_failure(&self, time: f64) -> Option<MockPoseFailure> {
+        self.pose_failures.iter().find(|w| w.contains(time)).map(|w| w.failure)
+    }
+
+    fn fetch_frame_data(&self, time: f64, near: f32, far: f32, out: &mut VRFrameData) {
+        out.timestamp = self.clock.to_timestamp(time);
+
+        let data = &self.data;
+        out.left_projection_matrix = utils::fov_to_projection_matrix(&data.left_eye_parameters.field_of_view,
+                                                                     near, far);
+        out.right_projection_matrix = utils::fov_to_projection_matrix(&data.right_eye_parameters.field_of_view,
+                                                                      near, far);
+
+        let noise = &self.tracking_noise;
+        let elapsed = time - self.motion_start;
+        let mut sample = self.motion.sample(noise.sensor_time(elapsed));
+        noise.apply(elapsed, &mut sample.position, &mut sample.orientation);
+        match self.pose_failure(time) {
+            Some(MockPoseFailure::Missing) => {
+                // Same behaviour as real backends when the pose is not valid
+                return;
+            },
+            Some(MockPoseFailure::Invalid) => {
+                sample.position = [f32::NAN; 3];
+                sample.orientation = [f32::NAN; 4];
+            },
+            None => {}
+        }
+        if data.capabilities.has_position {
+            out.pose.position = Some(sample.position);
+            out.pose.linear_velocity = Some(sample.linear_velocity);
+            out.pose.linear_acceleration = Some(sample.linear_acceleration);
+        } else {
+            // Orientation only displays don't track the position
+            sample.position = [0.0, 0.0, 0.0];
+        }
+        out.pose.orientation = Some(sample.orientation);
+        out.pose.angular_velocity = Some(sample.angular_velocity);
+        out.pose.angular_acceleration = Some(sample.angular_acceleration);
+
+        let head = utils::pose_to_matrix(&sample.position, &sample.orientation);
+        out.left_view_matrix = utils::eye_view_matrix(&head, &data.left_eye_parameters.offset);
+        out.right_view_matrix = utils::eye_view_matrix(&head, &data.right_eye_parameters.offset);
+
+        // The motion is simulated in the seated space
+        if let Some(transform) = utils::reference_space_transform(VRReferenceSpace::Seated,
+                                                                   self.reference_space,
+                                                                   data.stage_parameters.as_ref()) {
+            utils::transform_frame_data(&transform, out);
+        }
+    }
 }
 
 impl VRDisplay for MockVRDisplay {
@@ -28,95 +369,135 @@ impl VRDisplay for MockVRDisplay {
     }
 
     fn data(&self) -> VRDisplayData {
-        let mut data = VRDisplayData::default();
-        
-        // Mock display data
-        // Simulates a virtual HTC Vive
-
-        data.display_name = "Mock VRDisplay".into();
+        let mut data = self.data.clone();
         data.display_id = self.display_id;
-        data.connected = true;
-
-        data.capabilities.can_present = true;
-        data.capabilities.has_orientation = true;
-        data.capabilities.has_external_display = true;
-        data.capabilities.has_position = true;
-
-        data.stage_parameters = Some(VRStageParameters {
-            sitting_to_standing_transform: [-0.9317312, 0.0, 0.36314875, 0.0, 0.0, 0.99999994, 0.0, 0.0, -0.36314875, 
-                                            0.0, -0.9317312, 0.0, 0.23767996, 1.6813644, 0.45370483, 1.0],
-            size_x: 2.0,
-            size_z: 2.0
-        });
-
-        data.left_eye_parameters.offset = [0.035949998, 0.0, 0.015];
-        data.left_eye_parameters.render_width = 1512;
-        data.left_eye_parameters.render_height = 1680;
-        data.left_eye_parameters.field_of_view.up_degrees = 55.82093048095703;
-        data.left_eye_parameters.field_of_view.right_degrees = 51.26948547363281;
-        data.left_eye_parameters.field_of_view.down_degrees = 55.707801818847656;
-        data.left_eye_parameters.field_of_view.left_degrees = 54.42263412475586;
-
-        data.right_eye_parameters.offset = [-0.035949998, 0.0, 0.015];
-        data.right_eye_parameters.render_width = 1512;
-        data.right_eye_parameters.render_height = 1680;
-        data.right_eye_parameters.field_of_view.up_degrees = 55.898048400878906;
-        data.right_eye_parameters.field_of_view.right_degrees = 54.37410354614258;
-        data.right_eye_parameters.field_of_view.down_degrees = 55.614715576171875;
-        data.right_eye_parameters.field_of_view.left_degrees = 51.304901123046875;
-        
+        data.connected = self.connected;
         data
     }
 
-    fn inmediate_frame_data(&self, _near_z: f64, _far_z: f64) -> VRFrameData {
+    fn inmediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
         let mut data = VRFrameData::default();
-        // Position vector
-        data.pose.position = Some([0.5, -0.7, -0.3]);
-        // Orientation quaternion
-        // TODO: Add animation
-        data.pose.orientation = Some([0.9385081, -0.08066622, -0.3347714, 0.024972256]);
-
-        // Simulates HTC Vive projections
-        data.left_projection_matrix = [0.75620246, 0.0, 0.0, 0.0,
-                                       0.0, 0.68050665, 0.0, 0.0,
-                                      -0.05713458, -0.0021225351, -1.0000999, -1.0, 
-                                       0.0, 0.0, -0.10000999, 0.0];
-
-        data.left_view_matrix = [1.0, 0.0, 0.0, 0.0, 
-                                 0.0, 1.0, 0.0, 0.0, 
-                                 0.0, 0.0, 1.0, 0.0, 
-                                -0.035949998, 0.0, 0.015, 1.0];
-
-        data.right_projection_matrix = [0.75646526, 0.0, 0.0, 0.0, 
-                                        0.0, 0.68069947, 0.0, 0.0, 
-                                        0.055611316, -0.005315368, -1.0000999, -1.0, 
-                                        0.0, 0.0, -0.10000999, 0.0];
-
-        data.right_view_matrix = [1.0, 0.0, 0.0, 0.0,
-                                  0.0, 1.0, 0.0, 0.0,
-                                  0.0, 0.0, 1.0, 0.0,
-                                  0.035949998, 0.0, 0.015, 1.0];
-
-        data.timestamp = utils::timestamp();
+        self.fetch_frame_data(self.clock.now(), near_z as f32, far_z as f32, &mut data);
 
         data
     }
 
     fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
-        self.inmediate_frame_data(near_z, far_z)
+        let time = match self.sync.lock().unwrap().synced_time {
+            Some(time) => time,
+            None => self.clock.now()
+        };
+        let mut data = VRFrameData::default();
+        self.fetch_frame_data(time, near_z as f32, far_z as f32, &mut data);
+
+        data
+    }
+
+    fn predicted_frame_data(&self, near_z: f64, far_z: f64, target_time: f64) -> VRFrameData {
+        // The motion is sampled at the target time, so predictions are exact
+        let mut data = VRFrameData::default();
+        self.fetch_frame_data(self.clock.from_timestamp(target_time), near_z as f32, far_z as f32, &mut data);
+
+        data
     }
 
     fn reset_pose(&mut self) {
+        // Restart the animation
+        self.motion_start = self.clock.now();
+        self.sync.lock().unwrap().synced_time = None;
+    }
 
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
     }
 
-    fn sync_poses(&mut self) {
-        // Simulate Vsync
-        thread::sleep(Duration::from_millis(1));
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
     }
 
-    fn submit_frame(&mut self, _layer: &VRLayer) {
+    fn sync_poses(&self) {
+        // Simulate Vsync: block until the next vblank
+        let frame_duration = self.frame_duration();
+        // Small epsilon to avoid waiting twice for the same vblank due to rounding errors
+        let mut vblank = (self.clock.now() / frame_duration + 1e-6).floor() as u64 + 1;
+        {
+            let mut sync = self.sync.lock().unwrap();
+            if let Some(last) = sync.last_vblank {
+                if vblank <= last {
+                    vblank = last + 1;
+                } else {
+                    sync.missed_frames += vblank - last - 1;
+                }
+            }
+            sync.last_vblank = Some(vblank);
+        }
+        // The sync state is not locked while waiting, so pose reads are not blocked
+        self.clock.wait_until(vblank as f64 * frame_duration);
+
+        // Predict the pose for the time when the frame will be displayed
+        let time = (vblank + 1) as f64 * frame_duration;
+        let mut data = VRFrameData::default();
+        self.fetch_frame_data(time, 0.1, 1000.0, &mut data);
+        let now = self.clock.now();
+        let mut sync = self.sync.lock().unwrap();
+        sync.synced_time = Some(time);
+        sync.synced_pose = Some(data.pose);
+        sync.frame_index += 1;
+        if let Some(start) = sync.frame_start {
+            sync.frame_interval = now - start;
+        }
+        sync.frame_start = Some(now);
+    }
 
+    fn frame_stats(&self) -> VRFrameStats {
+        let sync = self.sync.lock().unwrap();
+        VRFrameStats {
+            frame_index: sync.frame_index,
+            frame_interval: sync.frame_interval * 1000.0,
+            cpu_frame_time: sync.cpu_frame_time * 1000.0,
+            missed_frames: sync.missed_frames,
+            // The mock display doesn't simulate a compositor
+            reprojected_frames: 0,
+            refresh_rate: self.refresh_rate
+        }
+    }
+
+    fn submit_frame(&mut self, layer: &VRLayer) {
+        self.submit_layers(&[layer.clone()]);
+    }
+
+    fn submit_layers(&mut self, layers: &[VRLayer]) {
+        if let Err(error) = self.validate_layers(layers) {
+            self.layer_errors.push(error);
+            return;
+        }
+        {
+            let mut sync = self.sync.lock().unwrap();
+            if let Some(start) = sync.frame_start {
+                sync.cpu_frame_time = self.clock.now() - start;
+            }
+        }
+        if self.submitted_frames_capacity == 0 {
+            return;
+        }
+        let mut layers = layers.to_vec();
+        layers.sort_by_key(|layer| layer.order);
+        if self.submitted_frames.len() == self.submitted_frames_capacity {
+            self.submitted_frames.pop_front();
+        }
+        let mut sync = self.sync.lock().unwrap();
+        self.submitted_frames.push_back(MockSubmittedFrame {
+            frame_index: sync.frame_index,
+            // Each synced pose can only be used by a single submitted frame
+            pose: sync.synced_pose.take(),
+            layer: layers[0].clone(),
+            layers: layers
+        });
     }
 }
 
+// Bounds in UV space: [x, y, w, h]
+fn valid_bounds(bounds: &[f32; 4]) -> bool {
+    bounds[0] >= 0.0 && bounds[1] >= 0.0 && bounds[2] > 0.0 && bounds[3] > 0.0 &&
+    bounds[0] + bounds[2] <= 1.0 && bounds[1] + bounds[3] <= 1.0
+}
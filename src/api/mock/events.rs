@@ -0,0 +1,43 @@
+use VREvent;
+use std::sync::{Arc, Mutex};
+
+// Handle used to inject fake events into a MockVRService.
+// Queued events are delivered on the next VRService::poll_events call.
+// The handle can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct MockEventQueue {
+    events: Arc<Mutex<Vec<VREvent>>>
+}
+
+impl MockEventQueue {
+    pub fn new() -> MockEventQueue {
+        MockEventQueue {
+            events: Arc::new(Mutex::new(Vec::new()))
+        }
+    }
+
+    // Queues a VREvent, VRDisplayEvent or VRGamepadEvent
+    pub fn push<E: Into<VREvent>>(&self, event: E) {
+        self.events.lock().unwrap().push(event.into());
+    }
+
+    // Discards the events that have not been delivered yet
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    // Number of events waiting to be delivered
+    pub fn len(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.lock().unwrap().is_empty()
+    }
+
+    // Removes and returns all the queued events in insertion order
+    pub fn drain(&self) -> Vec<VREvent> {
+        let mut events = self.events.lock().unwrap();
+        events.drain(..).collect()
+    }
+}
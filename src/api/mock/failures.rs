@@ -0,0 +1,89 @@
+use VRError;
+use std::sync::{Arc, Mutex};
+
+// Handle used to make a MockVRService fail.
+// It can be used after the service has been registered in a VRServiceManager.
+#[derive(Clone)]
+pub struct MockServiceFailures {
+    state: Arc<Mutex<MockServiceFailuresState>>
+}
+
+#[derive(Default)]
+struct MockServiceFailuresState {
+    initialize: Option<VRError>,
+    fetch_displays: Option<VRError>,
+    fetch_gamepads: Option<VRError>,
+    unavailable: bool
+}
+
+impl MockServiceFailures {
+    pub fn new() -> MockServiceFailures {
+        MockServiceFailures {
+            state: Arc::new(Mutex::new(MockServiceFailuresState::default()))
+        }
+    }
+
+    // VRService::initialize returns the error while set
+    pub fn set_initialize_error(&self, error: Option<VRError>) {
+        self.state.lock().unwrap().initialize = error;
+    }
+
+    // VRService::fetch_displays returns the error while set
+    pub fn set_fetch_displays_error(&self, error: Option<VRError>) {
+        self.state.lock().unwrap().fetch_displays = error;
+    }
+
+    // VRService::fetch_gamepads returns the error while set
+    pub fn set_fetch_gamepads_error(&self, error: Option<VRError>) {
+        self.state.lock().unwrap().fetch_gamepads = error;
+    }
+
+    // Sets the value returned by VRService::is_available
+    pub fn set_available(&self, available: bool) {
+        self.state.lock().unwrap().unavailable = !available;
+    }
+
+    // Restores the happy path
+    pub fn clear(&self) {
+        *self.state.lock().unwrap() = MockServiceFailuresState::default();
+    }
+
+    pub fn initialize_error(&self) -> Option<VRError> {
+        self.state.lock().unwrap().initialize.clone()
+    }
+
+    pub fn fetch_displays_error(&self) -> Option<VRError> {
+        self.state.lock().unwrap().fetch_displays.clone()
+    }
+
+    pub fn fetch_gamepads_error(&self) -> Option<VRError> {
+        self.state.lock().unwrap().fetch_gamepads.clone()
+    }
+
+    pub fn is_available(&self) -> bool {
+        !self.state.lock().unwrap().unavailable
+    }
+}
+
+// How poses are broken during a failure window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MockPoseFailure {
+    // Position, orientation, velocities and accelerations are None.
+    Missing,
+    // Position and orientation contain NaN values.
+    Invalid
+}
+
+// Time window, in seconds of the display clock, where poses are broken
+#[derive(Debug, Clone, Copy)]
+pub struct MockPoseFailureWindow {
+    pub start: f64,
+    pub end: f64,
+    pub failure: MockPoseFailure
+}
+
+impl MockPoseFailureWindow {
+    pub fn contains(&self, time: f64) -> bool {
+        time >= self.start && time < self.end
+    }
+}
@@ -0,0 +1,196 @@
+use {VRDisplay, VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton, VRPose, VRReferenceSpace};
+use super::clock::MockClock;
+use super::display::MockVRDisplayPtr;
+use super::motion::MockMotion;
+use super::noise::MockTrackingNoise;
+use super::super::utils;
+use std::sync::{Arc, RwLock};
+
+pub type MockVRGamepadPtr = Arc<RwLock<MockVRGamepad>>;
+
+pub struct MockVRGamepad {
+    gamepad_id: u32,
+    data: VRGamepadData,
+    state: VRGamepadState,
+    // When set, the pose is generated by the motion instead of using the pose set by set_pose
+    motion: Option<MockMotion>,
+    clock: MockClock,
+    // Clock time when the motion started
+    motion_start: f64,
+    tracking_noise: MockTrackingNoise,
+    // Display whose stage parameters are used to compute standing poses
+    display: Option<MockVRDisplayPtr>,
+    reference_space: VRReferenceSpace
+}
+
+impl MockVRGamepad {
+    pub fn new(data: VRGamepadData, button_count: usize, axis_count: usize) -> MockVRGamepadPtr {
+        let gamepad_id = utils::new_id();
+        let mut state = VRGamepadState::default();
+        state.gamepad_id = gamepad_id;
+        state.connected = true;
+        state.axes = vec![0.0; axis_count];
+        state.buttons = (0..button_count).map(|_| VRGamepadButton {
+            pressed: false,
+            touched: false
+        }).collect();
+
+        let clock = MockClock::monotonic();
+        Arc::new(RwLock::new(MockVRGamepad {
+            gamepad_id: gamepad_id,
+            data: data,
+            state: state,
+            motion: None,
+            motion_start: clock.now(),
+            clock: clock,
+            tracking_noise: MockTrackingNoise::default(),
+            display: None,
+            reference_space: VRReferenceSpace::Seated
+        }))
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.data.name = name.into();
+    }
+
+    pub fn set_hand(&mut self, hand: VRGamepadHand) {
+        self.data.hand = hand;
+    }
+
+    pub fn set_connected(&mut self, connected: bool) {
+        self.state.connected = connected;
+    }
+
+    // Panics if the index is out of the configured button count
+    pub fn set_button(&mut self, index: usize, pressed: bool, touched: bool) {
+        self.state.buttons[index] = VRGamepadButton {
+            pressed: pressed,
+            touched: touched
+        };
+    }
+
+    // Panics if the index is out of the configured axis count
+    pub fn set_axis(&mut self, index: usize, value: f64) {
+        self.state.axes[index] = value;
+    }
+
+    pub fn set_pose(&mut self, pose: VRPose) {
+        self.state.pose = pose;
+    }
+
+    // Animates the pose of the gamepad. The animation starts from the beginning.
+    // Use None to go back to the pose set by set_pose.
+    pub fn set_motion(&mut self, motion: Option<MockMotion>) {
+        self.motion = motion;
+        self.motion_start = self.clock.now();
+    }
+
+    pub fn clock(&self) -> MockClock {
+        self.clock.clone()
+    }
+
+    // Links the gamepad to a display. The gamepad shares the clock of the display,
+    // and its standing poses use the stage parameters of the display.
+    pub fn set_display(&mut self, display: MockVRDisplayPtr) {
+        {
+            let display = display.read().unwrap();
+            self.data.display_id = display.id();
+            self.set_clock(display.clock());
+        }
+        self.display = Some(display);
+    }
+
+    // Sets the time source used for the animation and the state timestamps. The animation is restarted.
+    pub fn set_clock(&mut self, clock: MockClock) {
+        self.motion_start = clock.now();
+        self.clock = clock;
+    }
+
+    // Sets the jitter, drift and latency applied to the simulated tracking
+    pub fn set_tracking_noise(&mut self, noise: MockTrackingNoise) {
+        self.tracking_noise = noise;
+    }
+
+    pub fn tracking_noise(&self) -> MockTrackingNoise {
+        self.tracking_noise
+    }
+
+    // Replaces the whole state. The gamepad_id is always kept.
+    pub fn set_state(&mut self, state: VRGamepadState) {
+        self.state = state;
+        self.state.gamepad_id = self.gamepad_id;
+    }
+}
+
+impl VRGamepad for MockVRGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad_id
+    }
+
+    fn data(&self) -> VRGamepadData {
+        self.data.clone()
+    }
+
+    fn state(&self) -> VRGamepadState {
+        self.state_at(self.clock.now())
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+
+    fn predicted_state(&self, target_time: f64) -> VRGamepadState {
+        let time = self.clock.from_timestamp(target_time);
+        let mut state = self.state_at(time);
+        if self.motion.is_none() {
+            // The pose set by set_pose is extrapolated using its velocities
+            let dt = time - self.clock.now();
+            state.pose = utils::extrapolate_pose(&state.pose, dt as f32);
+        }
+        state
+    }
+}
+
+impl MockVRGamepad {
+    // State reported at the given clock time
+    fn state_at(&self, now: f64) -> VRGamepadState {
+        let mut state = self.state.clone();
+        state.timestamp = self.clock.to_timestamp(now);
+
+        let noise = &self.tracking_noise;
+        let elapsed = now - self.motion_start;
+        if let Some(ref motion) = self.motion {
+            let sample = motion.sample(noise.sensor_time(elapsed));
+            state.pose.position = Some(sample.position);
+            state.pose.orientation = Some(sample.orientation);
+            state.pose.linear_velocity = Some(sample.linear_velocity);
+            state.pose.linear_acceleration = Some(sample.linear_acceleration);
+            state.pose.angular_velocity = Some(sample.angular_velocity);
+            state.pose.angular_acceleration = Some(sample.angular_acceleration);
+        }
+
+        let mut position = state.pose.position.unwrap_or([0.0, 0.0, 0.0]);
+        let mut orientation = state.pose.orientation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+        noise.apply(elapsed, &mut position, &mut orientation);
+        if state.pose.position.is_some() {
+            state.pose.position = Some(position);
+        }
+        if state.pose.orientation.is_some() {
+            state.pose.orientation = Some(orientation);
+        }
+
+        // Poses are simulated in the seated space
+        let stage = self.display.as_ref().and_then(|display| display.read().unwrap().data().stage_parameters);
+        if let Some(transform) = utils::reference_space_transform(VRReferenceSpace::Seated,
+                                                                   self.reference_space,
+                                                                   stage.as_ref()) {
+            state.pose = utils::transform_pose(&transform, &state.pose);
+        }
+
+        state
+    }
+}
@@ -1,8 +1,29 @@
+mod builder;
+mod clock;
+mod description;
 mod display;
+mod events;
+mod failures;
+mod gamepad;
+mod motion;
+mod noise;
+mod rng;
 mod service;
 
 use {VRService, VRServiceCreator};
 
+pub use self::builder::{MockDisplayBuilder, MockDisplayProfile};
+pub use self::clock::MockClock;
+#[cfg(feature = "mock-file")]
+pub use self::description::{MockDisplayDescription, MockGamepadDescription, MockTimelineDescription};
+pub use self::display::{MockVRDisplay, MockVRDisplayPtr, MockSubmittedFrame, MockLayerError};
+pub use self::events::MockEventQueue;
+pub use self::failures::{MockServiceFailures, MockPoseFailure, MockPoseFailureWindow};
+pub use self::gamepad::{MockVRGamepad, MockVRGamepadPtr};
+pub use self::motion::{MockMotion, MockMotionSample, MockPoseKeyframe};
+pub use self::noise::MockTrackingNoise;
+pub use self::service::MockVRService;
+
 pub struct MockServiceCreator;
 
 impl MockServiceCreator {
@@ -15,4 +36,4 @@ impl VRServiceCreator for MockServiceCreator {
      fn new_service(&self) -> Box<VRService> {
          Box::new(service::MockVRService::new())
      }
-}
\ No newline at end of file
+}
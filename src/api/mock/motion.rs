@@ -0,0 +1,274 @@
+use super::rng;
+use std::f64::consts::PI;
+
+// Time delta, in seconds, used to derive velocities and accelerations from the motion.
+const DERIVATIVE_STEP: f64 = 0.001;
+
+// Describes how the pose of a mock device evolves over time.
+// Positions are given in meters and periods in seconds.
+#[derive(Debug, Clone)]
+pub enum MockMotion {
+    // The device doesn't move.
+    Static {
+        position: [f32; 3],
+        orientation: [f32; 4]
+    },
+
+    // The device circles around `center` at the given radius, always looking at the center.
+    Orbit {
+        center: [f32; 3],
+        radius: f32,
+        period: f64
+    },
+
+    // The device moves up and down around `position`, simulating a walking user.
+    HeadBob {
+        position: [f32; 3],
+        amplitude: f32,
+        period: f64
+    },
+
+    // The device looks left and right, between -amplitude_degrees and +amplitude_degrees.
+    YawSweep {
+        position: [f32; 3],
+        amplitude_degrees: f32,
+        period: f64
+    },
+
+    // The device wanders around `position` within `radius`.
+    // A new random target is picked every `step` seconds. The same seed always generates the same motion.
+    RandomWalk {
+        position: [f32; 3],
+        radius: f32,
+        step: f64,
+        seed: u64
+    },
+
+    // The device follows a list of keyframes sorted by time.
+    // Position and orientation are interpolated between keyframes.
+    Timeline {
+        keyframes: Vec<MockPoseKeyframe>,
+        // Restart from the first keyframe when the last one is reached
+        looping: bool
+    }
+}
+
+// Pose of a device at a given time in seconds
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+pub struct MockPoseKeyframe {
+    pub time: f64,
+    pub position: [f32; 3],
+    pub orientation: [f32; 4]
+}
+
+impl Default for MockMotion {
+    fn default() -> MockMotion {
+        MockMotion::Static {
+            position: [0.5, -0.7, -0.3],
+            orientation: [0.9385081, -0.08066622, -0.3347714, 0.024972256]
+        }
+    }
+}
+
+// Pose generated by a MockMotion at a given time.
+// Velocities and accelerations are derived from the motion itself, so they are always consistent with the positions.
+#[derive(Debug, Clone, Copy)]
+pub struct MockMotionSample {
+    pub position: [f32; 3],
+    pub orientation: [f32; 4],
+    pub linear_velocity: [f32; 3],
+    pub linear_acceleration: [f32; 3],
+    pub angular_velocity: [f32; 3],
+    pub angular_acceleration: [f32; 3]
+}
+
+impl MockMotion {
+    // Evaluates the motion at `time` seconds
+    pub fn sample(&self, time: f64) -> MockMotionSample {
+        let h = DERIVATIVE_STEP;
+        let (p0, q0) = self.evaluate(time - h);
+        let (p1, q1) = self.evaluate(time);
+        let (p2, q2) = self.evaluate(time + h);
+
+        let mut linear_velocity = [0.0; 3];
+        let mut linear_acceleration = [0.0; 3];
+        for i in 0..3 {
+            linear_velocity[i] = ((p2[i] - p0[i]) / (2.0 * h)) as f32;
+            linear_acceleration[i] = ((p2[i] - 2.0 * p1[i] + p0[i]) / (h * h)) as f32;
+        }
+
+        let w0 = angular_velocity(&q0, &q1, h);
+        let w1 = angular_velocity(&q1, &q2, h);
+        let mut angular_acceleration = [0.0; 3];
+        for i in 0..3 {
+            angular_acceleration[i] = ((w1[i] - w0[i]) / h) as f32;
+        }
+
+        MockMotionSample {
+            position: to_f32_3(&p1),
+            orientation: [q1[0] as f32, q1[1] as f32, q1[2] as f32, q1[3] as f32],
+            linear_velocity: linear_velocity,
+            linear_acceleration: linear_acceleration,
+            angular_velocity: to_f32_3(&angular_velocity(&q0, &q2, 2.0 * h)),
+            angular_acceleration: angular_acceleration
+        }
+    }
+
+    // Returns the position and orientation quaternion at `time` seconds
+    fn evaluate(&self, time: f64) -> ([f64; 3], [f64; 4]) {
+        match *self {
+            MockMotion::Static { position, orientation } => {
+                (to_f64_3(&position),
+                 [orientation[0] as f64, orientation[1] as f64, orientation[2] as f64, orientation[3] as f64])
+            },
+            MockMotion::Orbit { center, radius, period } => {
+                let angle = phase(time, period);
+                let radius = radius as f64;
+                let position = [center[0] as f64 + radius * angle.sin(),
+                                center[1] as f64,
+                                center[2] as f64 + radius * angle.cos()];
+                // Rotating -Z by the orbit angle looks at the center
+                (position, yaw_pitch_quat(angle, 0.0))
+            },
+            MockMotion::HeadBob { position, amplitude, period } => {
+                let mut position = to_f64_3(&position);
+                position[1] += amplitude as f64 * phase(time, period).sin();
+                (position, [0.0, 0.0, 0.0, 1.0])
+            },
+            MockMotion::YawSweep { position, amplitude_degrees, period } => {
+                let yaw = (amplitude_degrees as f64).to_radians() * phase(time, period).sin();
+                (to_f64_3(&position), yaw_pitch_quat(yaw, 0.0))
+            },
+            MockMotion::RandomWalk { position, radius, step, seed } => {
+                let radius = radius as f64;
+                let mut position = to_f64_3(&position);
+                for i in 0..3 {
+                    position[i] += radius * value_noise(seed, i as u64, time, step);
+                }
+                let yaw = value_noise(seed, 3, time, step) * PI / 4.0;
+                let pitch = value_noise(seed, 4, time, step) * PI / 12.0;
+                (position, yaw_pitch_quat(yaw, pitch))
+            },
+            MockMotion::Timeline { ref keyframes, looping } => {
+                interpolate_keyframes(keyframes, time, looping)
+            }
+        }
+    }
+}
+
+fn interpolate_keyframes(keyframes: &[MockPoseKeyframe], time: f64, looping: bool) -> ([f64; 3], [f64; 4]) {
+    let (first, last) = match (keyframes.first(), keyframes.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return ([0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0])
+    };
+
+    let duration = last.time - first.time;
+    let mut time = time;
+    if looping && duration > 0.0 && time > last.time {
+        time = first.time + (time - first.time) % duration;
+    }
+
+    if time <= first.time {
+        return keyframe_pose(first);
+    }
+    if time >= last.time {
+        return keyframe_pose(last);
+    }
+
+    let next = keyframes.iter().position(|k| k.time > time).unwrap_or(keyframes.len() - 1);
+    let (a, b) = (&keyframes[next - 1], &keyframes[next]);
+    let t = (time - a.time) / (b.time - a.time);
+
+    let mut position = [0.0; 3];
+    for i in 0..3 {
+        position[i] = a.position[i] as f64 + (b.position[i] - a.position[i]) as f64 * t;
+    }
+
+    (position, nlerp(&keyframe_pose(a).1, &keyframe_pose(b).1, t))
+}
+
+fn keyframe_pose(keyframe: &MockPoseKeyframe) -> ([f64; 3], [f64; 4]) {
+    let q = keyframe.orientation;
+    (to_f64_3(&keyframe.position), [q[0] as f64, q[1] as f64, q[2] as f64, q[3] as f64])
+}
+
+// Normalized linear interpolation between two quaternions, using the shortest path
+fn nlerp(a: &[f64; 4], b: &[f64; 4], t: f64) -> [f64; 4] {
+    let dot = a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+    let sign = if dot < 0.0 { -1.0 } else { 1.0 };
+    let mut q = [0.0; 4];
+    for i in 0..4 {
+        q[i] = a[i] * (1.0 - t) + b[i] * sign * t;
+    }
+    let len = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    if len == 0.0 {
+        return *a;
+    }
+    [q[0] / len, q[1] / len, q[2] / len, q[3] / len]
+}
+
+#[inline]
+fn phase(time: f64, period: f64) -> f64 {
+    if period <= 0.0 {
+        return 0.0;
+    }
+    2.0 * PI * time / period
+}
+
+// Quaternion that applies a pitch (around X) followed by a yaw (around Y)
+fn yaw_pitch_quat(yaw: f64, pitch: f64) -> [f64; 4] {
+    let (sy, cy) = (yaw * 0.5).sin_cos();
+    let (sp, cp) = (pitch * 0.5).sin_cos();
+    [cy * sp, sy * cp, -sy * sp, cy * cp]
+}
+
+// Angular velocity in radians per second required to rotate from `a` to `b` in `dt` seconds.
+fn angular_velocity(a: &[f64; 4], b: &[f64; 4], dt: f64) -> [f64; 3] {
+    // delta = b * conjugate(a)
+    let (ax, ay, az, aw) = (-a[0], -a[1], -a[2], a[3]);
+    let (bx, by, bz, bw) = (b[0], b[1], b[2], b[3]);
+    let mut x = bw * ax + bx * aw + by * az - bz * ay;
+    let mut y = bw * ay - bx * az + by * aw + bz * ax;
+    let mut z = bw * az + bx * ay - by * ax + bz * aw;
+    let mut w = bw * aw - bx * ax - by * ay - bz * az;
+    // Use the shortest path
+    if w < 0.0 {
+        x = -x;
+        y = -y;
+        z = -z;
+        w = -w;
+    }
+    let sin_half = (x * x + y * y + z * z).sqrt();
+    if sin_half < 1e-12 {
+        return [0.0, 0.0, 0.0];
+    }
+    let angle = 2.0 * sin_half.atan2(w);
+    let scale = angle / (sin_half * dt);
+    [x * scale, y * scale, z * scale]
+}
+
+// Smooth noise in the [-1, 1] range. Random values are placed every `step` seconds
+// and interpolated with a smoothstep curve, so the result is continuous and derivable.
+fn value_noise(seed: u64, channel: u64, time: f64, step: f64) -> f64 {
+    if step <= 0.0 {
+        return 0.0;
+    }
+    let t = time / step;
+    let index = t.floor();
+    let frac = t - index;
+    let a = rng::signed(seed, channel, index as i64);
+    let b = rng::signed(seed, channel, index as i64 + 1);
+    let s = frac * frac * (3.0 - 2.0 * frac);
+    a + (b - a) * s
+}
+
+#[inline]
+fn to_f64_3(v: &[f32; 3]) -> [f64; 3] {
+    [v[0] as f64, v[1] as f64, v[2] as f64]
+}
+
+#[inline]
+fn to_f32_3(v: &[f64; 3]) -> [f32; 3] {
+    [v[0] as f32, v[1] as f32, v[2] as f32]
+}
@@ -0,0 +1,64 @@
+use super::rng;
+use super::super::utils;
+
+// Tracking errors simulated by mock displays and gamepads.
+// Jitter is generated from the seed and the sample time, so the same seed
+// and the same clock times always generate the same noise.
+#[derive(Debug, Clone, Copy)]
+pub struct MockTrackingNoise {
+    // Standard deviation of the position jitter in meters
+    pub position_jitter: f32,
+    // Standard deviation of the orientation jitter in radians
+    pub orientation_jitter: f32,
+    // Slow position drift in meters per second
+    pub position_drift: [f32; 3],
+    // Slow yaw drift in radians per second
+    pub yaw_drift: f32,
+    // Fixed sensor latency in seconds. Reported poses are the ones sampled `latency` seconds ago.
+    pub latency: f64,
+    pub seed: u64
+}
+
+impl Default for MockTrackingNoise {
+    fn default() -> MockTrackingNoise {
+        MockTrackingNoise {
+            position_jitter: 0.0,
+            orientation_jitter: 0.0,
+            position_drift: [0.0, 0.0, 0.0],
+            yaw_drift: 0.0,
+            latency: 0.0,
+            seed: 0
+        }
+    }
+}
+
+impl MockTrackingNoise {
+    // Returns the time when the sensors sampled the pose reported at `time`
+    pub fn sensor_time(&self, time: f64) -> f64 {
+        time - self.latency
+    }
+
+    // Applies jitter and drift to a pose reported at `time` seconds since the tracking started
+    pub fn apply(&self, time: f64, position: &mut [f32; 3], orientation: &mut [f32; 4]) {
+        // Jitter is sampled with microsecond granularity
+        let index = (time * 1e6).round() as i64;
+
+        for i in 0..3 {
+            position[i] += self.position_drift[i] * time as f32;
+            if self.position_jitter > 0.0 {
+                position[i] += self.position_jitter * rng::gaussian(self.seed, i as u64, index) as f32;
+            }
+        }
+
+        let mut rotation = [0.0f32; 3];
+        rotation[1] = self.yaw_drift * time as f32;
+        if self.orientation_jitter > 0.0 {
+            for i in 0..3 {
+                rotation[i] += self.orientation_jitter * rng::gaussian(self.seed, 3 + i as u64, index) as f32;
+            }
+        }
+        if rotation != [0.0, 0.0, 0.0] {
+            *orientation = utils::multiply_quat(&utils::rotation_vector_to_quat(&rotation), orientation);
+        }
+    }
+}
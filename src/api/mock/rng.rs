@@ -0,0 +1,30 @@
+// Stateless pseudo-random generator used by the mock backend.
+// Values are derived from a hash of (seed, channel, index) so the same inputs
+// always generate the same output, regardless of the call order.
+
+use std::f64::consts::PI;
+
+// Uniform value in the [0, 1) range
+pub fn uniform(seed: u64, channel: u64, index: i64) -> f64 {
+    let hash = splitmix64(seed ^ splitmix64(channel ^ splitmix64(index as u64)));
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// Uniform value in the [-1, 1) range
+pub fn signed(seed: u64, channel: u64, index: i64) -> f64 {
+    uniform(seed, channel, index) * 2.0 - 1.0
+}
+
+// Normally distributed value with mean 0 and standard deviation 1 (Box-Muller transform)
+pub fn gaussian(seed: u64, channel: u64, index: i64) -> f64 {
+    let u1 = 1.0 - uniform(seed, channel, index.wrapping_mul(2));
+    let u2 = uniform(seed, channel, index.wrapping_mul(2).wrapping_add(1));
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
@@ -1,36 +1,46 @@
-use {VRService, VRDisplayPtr, VREvent, VRGamepadPtr};
+use {VRService, VRDisplay, VRDisplayPtr, VRError, VREvent, VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadPtr};
+use super::builder::MockDisplayBuilder;
 use super::display::{MockVRDisplay, MockVRDisplayPtr};
+use super::events::MockEventQueue;
+use super::failures::MockServiceFailures;
+use super::gamepad::{MockVRGamepad, MockVRGamepadPtr};
 
 pub struct MockVRService {
     displays: Vec<MockVRDisplayPtr>,
+    gamepads: Vec<MockVRGamepadPtr>,
+    events: MockEventQueue,
+    failures: MockServiceFailures,
 }
 
-unsafe impl Send for MockVRService {}
-
 impl VRService for MockVRService {
-    fn initialize(&mut self) -> Result<(), String> { 
-        Ok(())
+    fn initialize(&mut self) -> Result<(), VRError> { 
+        match self.failures.initialize_error() {
+            Some(error) => Err(error),
+            None => Ok(())
+        }
     }
 
-    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>,String> {
-        if self.displays.len() == 0 {
-            self.displays.push(MockVRDisplay::new())
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>,VRError> {
+        if let Some(error) = self.failures.fetch_displays_error() {
+            return Err(error);
         }
-
+        self.display();
         Ok(self.clone_displays())
     }
 
-    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>,String> {
-        Ok(Vec::new())
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>,VRError> {
+        if let Some(error) = self.failures.fetch_gamepads_error() {
+            return Err(error);
+        }
+        Ok(self.clone_gamepads())
     }
 
     fn is_available(&self) -> bool {
-        true   
+        self.failures.is_available()
     }
 
     fn poll_events(&self) -> Vec<VREvent> {
-        // TODO: fake mock events
-        Vec::new()
+        self.events.drain()
     }
 }
 
@@ -38,9 +48,88 @@ impl MockVRService {
     pub fn new() -> MockVRService {
         MockVRService {
             displays: Vec::new(),
+            gamepads: Vec::new(),
+            events: MockEventQueue::new(),
+            failures: MockServiceFailures::new(),
+        }
+    }
+
+    // Returns a handle to make the service methods fail.
+    pub fn failures(&self) -> MockServiceFailures {
+        self.failures.clone()
+    }
+
+    // Returns a handle to queue the events returned by poll_events.
+    pub fn event_queue(&self) -> MockEventQueue {
+        self.events.clone()
+    }
+
+    // Returns the first mock display, creating it if required.
+    // Can be used to configure the display before registering the service.
+    pub fn display(&mut self) -> MockVRDisplayPtr {
+        if self.displays.len() == 0 {
+            self.displays.push(MockVRDisplay::new())
         }
+        self.displays[0].clone()
     }
+
+    // Returns all the mock displays in creation order
+    pub fn displays(&self) -> Vec<MockVRDisplayPtr> {
+        self.displays.clone()
+    }
+
+    // Adds a new display created from the builder. Each display gets a distinct display_id.
+    pub fn add_display(&mut self, builder: MockDisplayBuilder) -> MockVRDisplayPtr {
+        let display = builder.build();
+        self.displays.push(display.clone());
+        display
+    }
+
+    // Replaces all the mock displays with one created from the builder.
+    // Must be called before registering the service.
+    pub fn set_display(&mut self, builder: MockDisplayBuilder) -> MockVRDisplayPtr {
+        self.displays.clear();
+        self.add_display(builder)
+    }
+
+    // Adds a gamepad linked to the first mock display.
+    // Buttons and axes are created in the released and centered state.
+    pub fn add_gamepad(&mut self, hand: VRGamepadHand, button_count: usize, axis_count: usize) -> MockVRGamepadPtr {
+        let display_id = self.display().read().unwrap().id();
+        self.add_gamepad_for_display(display_id, hand, button_count, axis_count)
+    }
+
+    // Adds a gamepad linked to a specific display
+    pub fn add_gamepad_for_display(&mut self,
+                                   display_id: u32,
+                                   hand: VRGamepadHand,
+                                   button_count: usize,
+                                   axis_count: usize)
+                                   -> MockVRGamepadPtr {
+        let data = VRGamepadData {
+            display_id: display_id,
+            name: "Mock VRGamepad".into(),
+            hand: hand
+        };
+        let gamepad = MockVRGamepad::new(data, button_count, axis_count);
+        // Gamepads share the clock of their display, so both are animated in sync
+        if let Some(display) = self.displays.iter().find(|d| d.read().unwrap().id() == display_id) {
+            gamepad.write().unwrap().set_display(display.clone());
+        }
+        self.gamepads.push(gamepad.clone());
+        gamepad
+    }
+
+    // Returns the gamepads linked to a display
+    pub fn gamepads_for_display(&self, display_id: u32) -> Vec<MockVRGamepadPtr> {
+        self.gamepads.iter().filter(|g| g.read().unwrap().data().display_id == display_id).cloned().collect()
+    }
+
     fn clone_displays(&self) -> Vec<VRDisplayPtr> {
         self.displays.iter().map(|d| d.clone() as VRDisplayPtr).collect()
     }
+
+    fn clone_gamepads(&self) -> Vec<VRGamepadPtr> {
+        self.gamepads.iter().map(|d| d.clone() as VRGamepadPtr).collect()
+    }
 }
\ No newline at end of file
@@ -11,7 +11,34 @@ pub use self::openvr::OpenVRServiceCreator;
 #[cfg(feature = "mock")]
 mod mock;
 #[cfg(feature = "mock")]
-pub use self::mock::MockServiceCreator;
+pub use self::mock::{MockServiceCreator, MockVRService, MockVRDisplay, MockVRDisplayPtr,
+                     MockSubmittedFrame, MockLayerError, MockClock, MockDisplayBuilder, MockDisplayProfile,
+                     MockVRGamepad, MockVRGamepadPtr, MockEventQueue, MockServiceFailures, MockPoseFailure,
+                     MockPoseFailureWindow, MockMotion, MockMotionSample, MockPoseKeyframe,
+                     MockTrackingNoise};
+#[cfg(feature = "mock-file")]
+pub use self::mock::{MockDisplayDescription, MockGamepadDescription, MockTimelineDescription};
+
+#[cfg(feature = "replay")]
+mod replay;
+#[cfg(feature = "replay")]
+pub use self::replay::{ReplayServiceCreator, ReplayVRService, ReplayDisplay, ReplayDisplayPtr, ReplayGamepad,
+                       ReplayGamepadPtr, ReplayControls};
+
+#[cfg(unix)]
+#[cfg(feature = "ipc")]
+mod ipc;
+#[cfg(unix)]
+#[cfg(feature = "ipc")]
+pub use self::ipc::{RemoteServiceCreator, RemoteVRServer, RemoteVRService, RemoteVRDisplay, RemoteVRDisplayPtr,
+                    RemoteVRGamepad, RemoteVRGamepadPtr};
+
+#[cfg(feature = "network")]
+mod network;
+#[cfg(feature = "network")]
+pub use self::network::{NetworkServiceCreator, NetworkVRService, NetworkVRDisplay, NetworkVRDisplayPtr,
+                        NetworkVRGamepad, NetworkVRGamepadPtr, NetworkVRClient, NetworkDeviceInfo,
+                        NetworkFrameDone};
 
 #[cfg(feature = "googlevr")]
 mod googlevr;
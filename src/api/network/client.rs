@@ -0,0 +1,180 @@
+use {VRError, VRGamepadState, VRPose};
+use super::protocol::{self, DeviceMessage, NetworkDeviceInfo, NetworkFrameDone, ServiceMessage};
+use super::super::utils;
+use std::collections::VecDeque;
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+use vr_wire;
+
+// Device side of the protocol used by NetworkVRService. Streams poses and gamepad states,
+// answers the clock synchronisation pings and collects the frame acknowledgements.
+// Useful to exercise the service in the same process, or as a reference for device apps.
+pub struct NetworkVRClient {
+    stream: Arc<Mutex<TcpStream>>,
+    display_id: u32,
+    clock_offset: f64,
+    shared: Arc<ClientShared>
+}
+
+struct ClientShared {
+    state: Mutex<ClientState>,
+    frame_done: Condvar
+}
+
+struct ClientState {
+    connected: bool,
+    frames_done: VecDeque<NetworkFrameDone>,
+    reset_requested: bool
+}
+
+impl NetworkVRClient {
+    // Connects to a NetworkVRService and sends the description of the device
+    pub fn connect<A: ToSocketAddrs>(address: A, info: NetworkDeviceInfo) -> Result<NetworkVRClient, VRError> {
+        NetworkVRClient::connect_with_clock_offset(address, info, 0.0)
+    }
+
+    // Same as connect, simulating a device whose clock is `clock_offset` milliseconds
+    // ahead of the local clock
+    pub fn connect_with_clock_offset<A: ToSocketAddrs>(address: A,
+                                                       info: NetworkDeviceInfo,
+                                                       clock_offset: f64)
+                                                       -> Result<NetworkVRClient, VRError> {
+        let mut stream = try!(TcpStream::connect(address).map_err(network_error));
+        let _ = stream.set_nodelay(true);
+        let hello = protocol::encode_device_message(&DeviceMessage::Hello(info));
+        try!(vr_wire::write_message(&mut stream, &hello).map_err(network_error));
+        let payload = try!(vr_wire::read_message(&mut stream).map_err(network_error));
+        let display_id = match try!(protocol::decode_service_message(&payload).map_err(network_error)) {
+            ServiceMessage::Welcome(display_id) => display_id,
+            ServiceMessage::Error(message) => return Err(VRError::Backend("Network".into(), message)),
+            message => {
+                return Err(VRError::Backend("Network".into(), format!("Unexpected message {:?}", message)))
+            }
+        };
+
+        let reader = try!(stream.try_clone().map_err(network_error));
+        let client = NetworkVRClient {
+            stream: Arc::new(Mutex::new(stream)),
+            display_id: display_id,
+            clock_offset: clock_offset,
+            shared: Arc::new(ClientShared {
+                state: Mutex::new(ClientState {
+                    connected: true,
+                    frames_done: VecDeque::new(),
+                    reset_requested: false
+                }),
+                frame_done: Condvar::new()
+            })
+        };
+        let writer = client.stream.clone();
+        let shared = client.shared.clone();
+        thread::spawn(move || read_messages(reader, writer, shared, clock_offset));
+        Ok(client)
+    }
+
+    // Id of the display of the service
+    pub fn display_id(&self) -> u32 {
+        self.display_id
+    }
+
+    // Current time of the device clock, in milliseconds
+    pub fn timestamp(&self) -> f64 {
+        utils::timestamp() + self.clock_offset
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.shared.state.lock().unwrap().connected
+    }
+
+    // Sends the current pose of the device, in the seated space
+    pub fn send_pose(&self, pose: &VRPose) -> Result<(), VRError> {
+        let timestamp = self.timestamp();
+        self.send_pose_at(pose, timestamp)
+    }
+
+    // Sends a pose sampled at `timestamp`, given in the device clock
+    pub fn send_pose_at(&self, pose: &VRPose, timestamp: f64) -> Result<(), VRError> {
+        self.send(&DeviceMessage::Pose(timestamp, *pose))
+    }
+
+    // Sends the state of the gamepad at `index` in the device description.
+    // The timestamp of the state must be given in the device clock.
+    pub fn send_gamepad_state(&self, index: u32, state: &VRGamepadState) -> Result<(), VRError> {
+        self.send(&DeviceMessage::GamepadState(index, state.clone()))
+    }
+
+    // Returns the frame acknowledgements received since the last call
+    pub fn frames_done(&self) -> Vec<NetworkFrameDone> {
+        self.shared.state.lock().unwrap().frames_done.drain(..).collect()
+    }
+
+    // Waits for the next frame acknowledgement
+    pub fn wait_frame_done(&self, timeout: Duration) -> Option<NetworkFrameDone> {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.frames_done.is_empty() && state.connected {
+            state = self.shared.frame_done.wait_timeout(state, timeout).unwrap().0;
+        }
+        state.frames_done.pop_front()
+    }
+
+    // True if the service asked to recenter the tracking since the last call
+    pub fn take_reset_request(&self) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+        let requested = state.reset_requested;
+        state.reset_requested = false;
+        requested
+    }
+
+    fn send(&self, message: &DeviceMessage) -> Result<(), VRError> {
+        let mut stream = self.stream.lock().unwrap();
+        vr_wire::write_message(&mut *stream, &protocol::encode_device_message(message)).map_err(network_error)
+    }
+}
+
+impl Drop for NetworkVRClient {
+    fn drop(&mut self) {
+        let _ = self.stream.lock().unwrap().shutdown(Shutdown::Both);
+    }
+}
+
+fn read_messages(mut reader: TcpStream, writer: Arc<Mutex<TcpStream>>, shared: Arc<ClientShared>, clock_offset: f64) {
+    loop {
+        let message = match vr_wire::read_message(&mut reader) {
+            Ok(payload) => protocol::decode_service_message(&payload),
+            Err(_) => break
+        };
+        match message {
+            Ok(ServiceMessage::ClockPing(ping_time)) => {
+                let pong = DeviceMessage::ClockPong(ping_time, utils::timestamp() + clock_offset);
+                let mut writer = writer.lock().unwrap();
+                if vr_wire::write_message(&mut *writer, &protocol::encode_device_message(&pong)).is_err() {
+                    break;
+                }
+            },
+            Ok(ServiceMessage::FrameDone(frame)) => {
+                shared.state.lock().unwrap().frames_done.push_back(frame);
+                shared.frame_done.notify_all();
+            },
+            Ok(ServiceMessage::ResetPose) => {
+                shared.state.lock().unwrap().reset_requested = true;
+            },
+            Ok(ServiceMessage::Error(message)) => {
+                error!("Network VR service error: {}", message);
+                break;
+            },
+            Ok(ServiceMessage::Welcome(_)) => {},
+            Err(e) => {
+                error!("Invalid message from network VR service: {}", e);
+                break;
+            }
+        }
+    }
+    shared.state.lock().unwrap().connected = false;
+    shared.frame_done.notify_all();
+}
+
+fn network_error<E: ToString>(error: E) -> VRError {
+    VRError::Backend("Network".into(), error.to_string())
+}
@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+
+// Number of ping exchanges used to estimate the clock offset
+const MAX_SAMPLES: usize = 8;
+
+// Estimates the offset between the clock of a remote device and the local clock from
+// ping exchanges. The exchange with the lowest round trip time of the last samples is used,
+// because it's the one less affected by network jitter.
+pub struct ClockSync {
+    // (round trip time, device clock - local clock)
+    samples: VecDeque<(f64, f64)>
+}
+
+impl ClockSync {
+    pub fn new() -> ClockSync {
+        ClockSync {
+            samples: VecDeque::new()
+        }
+    }
+
+    // Adds an exchange: ping sent at `ping_time`, received by the device at `device_time` and
+    // answer received at `pong_time`. Latencies are assumed to be symmetric.
+    pub fn add_sample(&mut self, ping_time: f64, device_time: f64, pong_time: f64) {
+        let round_trip = pong_time - ping_time;
+        if round_trip < 0.0 {
+            return;
+        }
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((round_trip, device_time - (ping_time + round_trip * 0.5)));
+    }
+
+    // Device clock minus local clock, in milliseconds. None until the first exchange.
+    pub fn offset(&self) -> Option<f64> {
+        self.best_sample().map(|sample| sample.1)
+    }
+
+    // Round trip time of the exchange used to estimate the offset
+    pub fn round_trip(&self) -> Option<f64> {
+        self.best_sample().map(|sample| sample.0)
+    }
+
+    pub fn to_local(&self, device_time: f64) -> Option<f64> {
+        self.offset().map(|offset| device_time - offset)
+    }
+
+    pub fn to_device(&self, local_time: f64) -> Option<f64> {
+        self.offset().map(|offset| local_time + offset)
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    fn best_sample(&self) -> Option<&(f64, f64)> {
+        self.samples.iter().fold(None, |best: Option<&(f64, f64)>, sample| match best {
+            Some(best) if best.0 <= sample.0 => Some(best),
+            _ => Some(sample)
+        })
+    }
+}
@@ -0,0 +1,336 @@
+use {VRDisplayData, VRDisplayEvent, VREvent, VRGamepadData, VRGamepadEvent, VRGamepadState, VRPose};
+use super::clock::ClockSync;
+use super::protocol::{self, DeviceMessage, NetworkDeviceInfo, ServiceMessage};
+use super::super::utils;
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use vr_wire;
+
+pub type NetworkDevicePtr = Arc<NetworkDevice>;
+
+// Interval between clock synchronisation pings, in milliseconds
+const CLOCK_SYNC_INTERVAL: f64 = 1000.0;
+
+// State of the remote device, shared by the NetworkVRService, its display and gamepads
+// and the thread that reads the device connection. Only one device can be connected at a time.
+// A device that reconnects keeps the same display and gamepads.
+pub struct NetworkDevice {
+    display_id: u32,
+    state: Mutex<DeviceState>,
+    pose_received: Condvar
+}
+
+// Writes to the device connection. The lock serializes the messages sent by different threads.
+// Messages are written after releasing the DeviceState lock, so a slow device doesn't block
+// the threads that only read the state.
+type DeviceWriter = Arc<Mutex<TcpStream>>;
+
+struct DeviceState {
+    // Connection of the current device, used to close it
+    connection: Option<TcpStream>,
+    // Used to send messages to the device
+    writer: Option<DeviceWriter>,
+    // Description sent by the last connected device
+    info: Option<NetworkDeviceInfo>,
+    pose: VRPose,
+    // Local timestamp of the pose
+    pose_time: f64,
+    // Number of poses received, used to wait for new poses
+    pose_count: u64,
+    gamepads: Vec<GamepadSlot>,
+    clock: ClockSync,
+    last_ping: f64,
+    events: Vec<VREvent>
+}
+
+struct GamepadSlot {
+    gamepad_id: u32,
+    data: VRGamepadData,
+    state: VRGamepadState
+}
+
+impl NetworkDevice {
+    pub fn new() -> NetworkDevicePtr {
+        Arc::new(NetworkDevice {
+            display_id: utils::new_id(),
+            state: Mutex::new(DeviceState {
+                connection: None,
+                writer: None,
+                info: None,
+                pose: VRPose::default(),
+                pose_time: 0.0,
+                pose_count: 0,
+                gamepads: Vec::new(),
+                clock: ClockSync::new(),
+                last_ping: 0.0,
+                events: Vec::new()
+            }),
+            pose_received: Condvar::new()
+        })
+    }
+
+    pub fn display_id(&self) -> u32 {
+        self.display_id
+    }
+
+    // True once a device has connected, even if it's disconnected now
+    pub fn has_display(&self) -> bool {
+        self.state.lock().unwrap().info.is_some()
+    }
+
+    pub fn display_data(&self) -> VRDisplayData {
+        let state = self.state.lock().unwrap();
+        state.display_data(self.display_id)
+    }
+
+    pub fn refresh_rate(&self) -> f64 {
+        self.state.lock().unwrap().info.as_ref().map_or(0.0, |info| info.refresh_rate)
+    }
+
+    // Latest pose and its local timestamp
+    pub fn pose(&self) -> (VRPose, f64) {
+        let state = self.state.lock().unwrap();
+        (state.pose, state.pose_time)
+    }
+
+    // Waits until a pose newer than `pose_count` is received or the timeout expires.
+    // Returns the latest pose, its local timestamp and the number of poses received.
+    pub fn wait_pose(&self, pose_count: u64, timeout: Duration) -> (VRPose, f64, u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.pose_count <= pose_count && state.connection.is_some() {
+            state = self.pose_received.wait_timeout(state, timeout).unwrap().0;
+        }
+        (state.pose, state.pose_time, state.pose_count)
+    }
+
+    // Ids of the gamepads declared by the devices
+    pub fn gamepad_ids(&self) -> Vec<u32> {
+        self.state.lock().unwrap().gamepads.iter().map(|slot| slot.gamepad_id).collect()
+    }
+
+    pub fn gamepad_data(&self, index: usize) -> VRGamepadData {
+        let state = self.state.lock().unwrap();
+        let mut data = state.gamepads.get(index).map(|slot| slot.data.clone()).unwrap_or_default();
+        data.display_id = self.display_id;
+        data
+    }
+
+    pub fn gamepad_state(&self, index: usize) -> VRGamepadState {
+        let state = self.state.lock().unwrap();
+        state.gamepads.get(index).map(|slot| slot.state.clone()).unwrap_or_default()
+    }
+
+    pub fn poll_events(&self) -> Vec<VREvent> {
+        let mut state = self.state.lock().unwrap();
+        state.events.drain(..).collect()
+    }
+
+    // Converts a local timestamp to the device clock
+    pub fn to_device_time(&self, local_time: f64) -> f64 {
+        let state = self.state.lock().unwrap();
+        state.clock.to_device(local_time).unwrap_or(local_time)
+    }
+
+    // Estimated offset between the device clock and the local clock, in milliseconds
+    pub fn clock_offset(&self) -> Option<f64> {
+        self.state.lock().unwrap().clock.offset()
+    }
+
+    // Round trip time of the connection, in milliseconds
+    pub fn round_trip(&self) -> Option<f64> {
+        self.state.lock().unwrap().clock.round_trip()
+    }
+
+    pub fn send(&self, message: &ServiceMessage) {
+        let writer = self.state.lock().unwrap().writer.clone();
+        if let Some(writer) = writer {
+            send(&writer, message);
+        }
+    }
+
+    // Closes the connection of the current device
+    pub fn disconnect(&self) {
+        if let Some(ref connection) = self.state.lock().unwrap().connection {
+            let _ = connection.shutdown(Shutdown::Both);
+        }
+    }
+
+    // Reads the messages of a device connection until it's closed
+    pub fn serve(&self, mut stream: TcpStream) {
+        let _ = stream.set_nodelay(true);
+        let hello = match vr_wire::read_message(&mut stream) {
+            Ok(payload) => protocol::decode_device_message(&payload),
+            Err(_) => return
+        };
+        let info = match hello {
+            Ok(DeviceMessage::Hello(info)) => info,
+            Ok(_) => return reject(stream, "Expected Hello message".into()),
+            Err(message) => return reject(stream, message)
+        };
+        let writer = Arc::new(Mutex::new(match stream.try_clone() {
+            Ok(writer) => writer,
+            Err(e) => return reject(stream, e.to_string())
+        }));
+        {
+            // Hold the writer until the Welcome is sent, so that it's the first message
+            // received by the device even if other threads send messages once it's connected
+            let mut writer_stream = writer.lock().unwrap();
+            if let Err(message) = self.connect(&stream, info, writer.clone()) {
+                drop(writer_stream);
+                return reject(stream, message);
+            }
+            write(&mut writer_stream, &ServiceMessage::Welcome(self.display_id));
+            write(&mut writer_stream, &ServiceMessage::ClockPing(utils::timestamp()));
+        }
+
+        loop {
+            let message = match vr_wire::read_message(&mut stream) {
+                Ok(payload) => protocol::decode_device_message(&payload),
+                Err(_) => break
+            };
+            let message = match message {
+                Ok(DeviceMessage::Hello(_)) => {
+                    error!("Network VR device sent Hello twice");
+                    break;
+                },
+                Ok(message) => message,
+                Err(message) => {
+                    error!("Invalid message from network VR device: {}", message);
+                    break;
+                }
+            };
+            if let Some(ping_time) = self.handle_message(message) {
+                send(&writer, &ServiceMessage::ClockPing(ping_time));
+            }
+        }
+
+        let _ = stream.shutdown(Shutdown::Both);
+        self.disconnected();
+    }
+
+    // Updates the state with a message of the device.
+    // Returns the time of the clock synchronisation ping to send, if it's due.
+    fn handle_message(&self, message: DeviceMessage) -> Option<f64> {
+        let mut state = self.state.lock().unwrap();
+        let now = utils::timestamp();
+        match message {
+            // Rejected by serve
+            DeviceMessage::Hello(_) => {},
+            DeviceMessage::Pose(timestamp, pose) => {
+                state.pose = pose;
+                state.pose_time = state.clock.to_local(timestamp).unwrap_or(now);
+                state.pose_count += 1;
+                self.pose_received.notify_all();
+            },
+            DeviceMessage::GamepadState(index, mut gamepad_state) => {
+                gamepad_state.timestamp = state.clock.to_local(gamepad_state.timestamp).unwrap_or(now);
+                // Slots of gamepads declared by previously connected devices are ignored
+                let count = state.info.as_ref().map_or(0, |info| info.gamepads.len());
+                let slot = if (index as usize) < count {
+                    state.gamepads.get_mut(index as usize)
+                } else {
+                    None
+                };
+                match slot {
+                    Some(slot) => {
+                        gamepad_state.gamepad_id = slot.gamepad_id;
+                        slot.state = gamepad_state;
+                    },
+                    None => warn!("Network VR device sent the state of unknown gamepad {}", index)
+                }
+            },
+            DeviceMessage::ClockPong(ping_time, device_time) => {
+                state.clock.add_sample(ping_time, device_time, now);
+            }
+        }
+        if now - state.last_ping >= CLOCK_SYNC_INTERVAL {
+            state.last_ping = now;
+            Some(now)
+        } else {
+            None
+        }
+    }
+
+    fn connect(&self, stream: &TcpStream, info: NetworkDeviceInfo, writer: DeviceWriter) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if state.connection.is_some() {
+            return Err("A device is already connected".into());
+        }
+        let connection = try!(stream.try_clone().map_err(|e| e.to_string()));
+        state.connection = Some(connection);
+        state.writer = Some(writer);
+
+        for (index, data) in info.gamepads.iter().enumerate() {
+            if index == state.gamepads.len() {
+                state.gamepads.push(GamepadSlot {
+                    gamepad_id: utils::new_id(),
+                    data: VRGamepadData::default(),
+                    state: VRGamepadState::default()
+                });
+            }
+            let slot = &mut state.gamepads[index];
+            slot.data = data.clone();
+            slot.state = VRGamepadState::default();
+            slot.state.gamepad_id = slot.gamepad_id;
+            slot.state.connected = true;
+        }
+        state.info = Some(info);
+        state.clock.reset();
+        state.pose = VRPose::default();
+
+        let display = state.display_data(self.display_id);
+        state.events.push(VRDisplayEvent::Connect(display).into());
+        let gamepad_count = state.info.as_ref().unwrap().gamepads.len();
+        for index in 0..gamepad_count {
+            let mut data = state.gamepads[index].data.clone();
+            data.display_id = self.display_id;
+            let event = VRGamepadEvent::Connect(data, state.gamepads[index].state.clone());
+            state.events.push(event.into());
+        }
+
+        // The first ping is sent by serve along with the Welcome message
+        state.last_ping = utils::timestamp();
+        Ok(())
+    }
+
+    fn disconnected(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.connection = None;
+        state.writer = None;
+        state.events.push(VRDisplayEvent::Disconnect(self.display_id).into());
+        let gamepad_count = state.info.as_ref().map_or(0, |info| info.gamepads.len());
+        for index in 0..gamepad_count {
+            state.gamepads[index].state.connected = false;
+            let event = VRGamepadEvent::Disconnect(state.gamepads[index].gamepad_id);
+            state.events.push(event.into());
+        }
+        // Release the threads waiting for poses
+        self.pose_received.notify_all();
+    }
+}
+
+impl DeviceState {
+    fn display_data(&self, display_id: u32) -> VRDisplayData {
+        let mut data = self.info.as_ref().map(|info| info.display.clone()).unwrap_or_default();
+        data.display_id = display_id;
+        data.connected = self.connection.is_some();
+        data
+    }
+}
+
+fn send(writer: &Mutex<TcpStream>, message: &ServiceMessage) {
+    write(&mut writer.lock().unwrap(), message);
+}
+
+fn write(stream: &mut TcpStream, message: &ServiceMessage) {
+    if let Err(e) = vr_wire::write_message(stream, &protocol::encode_service_message(message)) {
+        error!("Error sending message to network VR device: {}", e);
+    }
+}
+
+fn reject(mut stream: TcpStream, message: String) {
+    warn!("Network VR device rejected: {}", message);
+    let _ = vr_wire::write_message(&mut stream, &protocol::encode_service_message(&ServiceMessage::Error(message)));
+    let _ = stream.shutdown(Shutdown::Both);
+}
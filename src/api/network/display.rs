@@ -0,0 +1,176 @@
+use {VRDisplay, VRDisplayData, VRFrameData, VRFrameStats, VRLayer, VRPose, VRReferenceSpace};
+use super::device::NetworkDevicePtr;
+use super::protocol::{NetworkFrameDone, ServiceMessage};
+use super::super::utils;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+pub type NetworkVRDisplayPtr = Arc<RwLock<NetworkVRDisplay>>;
+
+// Maximum time sync_poses waits for a new pose from the device, in milliseconds
+const SYNC_TIMEOUT: u64 = 100;
+
+// Display whose poses are streamed by a remote device
+pub struct NetworkVRDisplay {
+    device: NetworkDevicePtr,
+    reference_space: VRReferenceSpace,
+    sync: Mutex<SyncState>
+}
+
+struct SyncState {
+    // Number of device poses received when the last frame was synced
+    pose_count: u64,
+    // Pose and local timestamp used to render the current frame
+    synced_pose: Option<(VRPose, f64)>,
+    frame_index: u64,
+    // Local timestamps of the frame, in milliseconds
+    frame_start: Option<f64>,
+    frame_interval: f64,
+    cpu_frame_time: f64
+}
+
+impl NetworkVRDisplay {
+    pub fn new(device: NetworkDevicePtr) -> NetworkVRDisplayPtr {
+        Arc::new(RwLock::new(NetworkVRDisplay {
+            device: device,
+            reference_space: VRReferenceSpace::Seated,
+            sync: Mutex::new(SyncState {
+                pose_count: 0,
+                synced_pose: None,
+                frame_index: 0,
+                frame_start: None,
+                frame_interval: 0.0,
+                cpu_frame_time: 0.0
+            })
+        }))
+    }
+
+    // Estimated offset between the clock of the connected device and the local clock,
+    // in milliseconds. None until the first clock synchronisation.
+    pub fn clock_offset(&self) -> Option<f64> {
+        self.device.clock_offset()
+    }
+
+    // Round trip time of the connection with the device, in milliseconds
+    pub fn round_trip_time(&self) -> Option<f64> {
+        self.device.round_trip()
+    }
+
+    // Closes the connection of the current device, which is allowed to reconnect
+    pub fn disconnect_device(&self) {
+        self.device.disconnect();
+    }
+
+    fn frame_data(&self, pose: &VRPose, timestamp: f64, near: f32, far: f32) -> VRFrameData {
+        let data = self.device.display_data();
+        let mut out = VRFrameData::default();
+        out.timestamp = timestamp;
+        out.pose = *pose;
+        out.left_projection_matrix = utils::fov_to_projection_matrix(&data.left_eye_parameters.field_of_view,
+                                                                     near, far);
+        out.right_projection_matrix = utils::fov_to_projection_matrix(&data.right_eye_parameters.field_of_view,
+                                                                      near, far);
+        let position = pose.position.unwrap_or([0.0, 0.0, 0.0]);
+        let orientation = pose.orientation.unwrap_or([0.0, 0.0, 0.0, 1.0]);
+        let head = utils::pose_to_matrix(&position, &orientation);
+        out.left_view_matrix = utils::eye_view_matrix(&head, &data.left_eye_parameters.offset);
+        out.right_view_matrix = utils::eye_view_matrix(&head, &data.right_eye_parameters.offset);
+
+        // Devices stream poses in the seated space
+        if let Some(transform) = utils::reference_space_transform(VRReferenceSpace::Seated,
+                                                                   self.reference_space,
+                                                                   data.stage_parameters.as_ref()) {
+            utils::transform_frame_data(&transform, &mut out);
+        }
+        out
+    }
+}
+
+impl VRDisplay for NetworkVRDisplay {
+    fn id(&self) -> u32 {
+        self.device.display_id()
+    }
+
+    fn data(&self) -> VRDisplayData {
+        self.device.display_data()
+    }
+
+    fn inmediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let (pose, time) = self.device.pose();
+        self.frame_data(&pose, time, near_z as f32, far_z as f32)
+    }
+
+    fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let synced = self.sync.lock().unwrap().synced_pose;
+        let (pose, time) = synced.unwrap_or_else(|| self.device.pose());
+        self.frame_data(&pose, time, near_z as f32, far_z as f32)
+    }
+
+    fn predicted_frame_data(&self, near_z: f64, far_z: f64, target_time: f64) -> VRFrameData {
+        // The latest streamed pose is extrapolated to the target time
+        let (pose, time) = self.device.pose();
+        let pose = utils::extrapolate_pose(&pose, ((target_time - time) / 1000.0) as f32);
+        self.frame_data(&pose, target_time, near_z as f32, far_z as f32)
+    }
+
+    fn reset_pose(&mut self) {
+        self.device.send(&ServiceMessage::ResetPose);
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+
+    fn sync_poses(&self) {
+        // Keep in step with the device: wait for its next pose
+        let pose_count = self.sync.lock().unwrap().pose_count;
+        let (pose, time, pose_count) = self.device.wait_pose(pose_count, Duration::from_millis(SYNC_TIMEOUT));
+
+        let now = utils::timestamp();
+        let mut sync = self.sync.lock().unwrap();
+        sync.pose_count = pose_count;
+        sync.synced_pose = Some((pose, time));
+        sync.frame_index += 1;
+        if let Some(start) = sync.frame_start {
+            sync.frame_interval = now - start;
+        }
+        sync.frame_start = Some(now);
+    }
+
+    fn submit_frame(&mut self, layer: &VRLayer) {
+        self.submit_layers(&[layer.clone()]);
+    }
+
+    fn submit_layers(&mut self, layers: &[VRLayer]) {
+        let now = utils::timestamp();
+        let frame_index = {
+            let mut sync = self.sync.lock().unwrap();
+            if let Some(start) = sync.frame_start {
+                sync.cpu_frame_time = now - start;
+            }
+            sync.frame_index
+        };
+        self.device.send(&ServiceMessage::FrameDone(NetworkFrameDone {
+            frame_index: frame_index,
+            timestamp: self.device.to_device_time(now),
+            layer_count: layers.len() as u32
+        }));
+    }
+
+    fn frame_stats(&self) -> VRFrameStats {
+        let sync = self.sync.lock().unwrap();
+        VRFrameStats {
+            frame_index: sync.frame_index,
+            frame_interval: sync.frame_interval,
+            cpu_frame_time: sync.cpu_frame_time,
+            // Frames are not presented by this process
+            missed_frames: 0,
+            reprojected_frames: 0,
+            refresh_rate: self.device.refresh_rate()
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use {VRGamepad, VRGamepadData, VRGamepadState, VRReferenceSpace};
+use super::device::NetworkDevicePtr;
+use super::super::utils;
+use std::sync::{Arc, RwLock};
+
+pub type NetworkVRGamepadPtr = Arc<RwLock<NetworkVRGamepad>>;
+
+// Gamepad whose state is streamed by a remote device
+pub struct NetworkVRGamepad {
+    gamepad_id: u32,
+    // Index of the gamepad in the description sent by the device
+    index: usize,
+    device: NetworkDevicePtr,
+    reference_space: VRReferenceSpace
+}
+
+impl NetworkVRGamepad {
+    pub fn new(gamepad_id: u32, index: usize, device: NetworkDevicePtr) -> NetworkVRGamepadPtr {
+        Arc::new(RwLock::new(NetworkVRGamepad {
+            gamepad_id: gamepad_id,
+            index: index,
+            device: device,
+            reference_space: VRReferenceSpace::Seated
+        }))
+    }
+}
+
+impl VRGamepad for NetworkVRGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad_id
+    }
+
+    fn data(&self) -> VRGamepadData {
+        self.device.gamepad_data(self.index)
+    }
+
+    fn state(&self) -> VRGamepadState {
+        let mut state = self.device.gamepad_state(self.index);
+        state.gamepad_id = self.gamepad_id;
+        // Devices stream poses in the seated space
+        let stage = self.device.display_data().stage_parameters;
+        if let Some(transform) = utils::reference_space_transform(VRReferenceSpace::Seated,
+                                                                   self.reference_space,
+                                                                   stage.as_ref()) {
+            state.pose = utils::transform_pose(&transform, &state.pose);
+        }
+        state
+    }
+
+    fn predicted_state(&self, target_time: f64) -> VRGamepadState {
+        // The latest streamed state is extrapolated to the target time
+        let mut state = self.state();
+        let dt = (target_time - state.timestamp) / 1000.0;
+        state.pose = utils::extrapolate_pose(&state.pose, dt as f32);
+        state.timestamp = target_time;
+        state
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+}
@@ -0,0 +1,35 @@
+mod client;
+mod clock;
+mod device;
+mod display;
+mod gamepad;
+mod protocol;
+mod service;
+
+use {VRService, VRServiceCreator};
+use std::net::SocketAddr;
+
+pub use self::client::NetworkVRClient;
+pub use self::display::{NetworkVRDisplay, NetworkVRDisplayPtr};
+pub use self::gamepad::{NetworkVRGamepad, NetworkVRGamepadPtr};
+pub use self::protocol::{NetworkDeviceInfo, NetworkFrameDone};
+pub use self::service::NetworkVRService;
+
+pub struct NetworkServiceCreator {
+    address: SocketAddr
+}
+
+impl NetworkServiceCreator {
+    // Creates services that listen on `address` for a device streaming its poses
+    pub fn new(address: SocketAddr) -> Box<VRServiceCreator> {
+        Box::new(NetworkServiceCreator {
+            address: address
+        })
+    }
+}
+
+impl VRServiceCreator for NetworkServiceCreator {
+     fn new_service(&self) -> Box<VRService> {
+         Box::new(NetworkVRService::new(self.address))
+     }
+}
@@ -0,0 +1,218 @@
+use {VRDisplayData, VRGamepadData, VRGamepadState, VRPose};
+use vr_wire::{self, VRWireError, WireReader};
+
+// Protocol between a NetworkVRService and the remote device that streams its poses,
+// e.g. a phone used as a headset. Little endian, over TCP.
+//
+// Messages are framed as described in vr_wire. The device connects to the service and sends
+// Hello. The service answers with Welcome, or with Error and closes the connection.
+// After that both ends send messages at any time.
+//
+// Device messages: u8 kind + payload
+//   Hello: "WVRNET" magic, u16 protocol version, u16 wire version, display data,
+//          f64 refresh rate in Hz, u32 count + gamepad data. Device ids in the data are ignored.
+//   Pose: f64 device timestamp, pose in the seated space
+//   GamepadState: u32 gamepad index in Hello, gamepad state with a device timestamp
+//   ClockPong: f64 service timestamp of the ping, f64 device timestamp when the ping arrived
+//
+// Service messages: u8 kind + payload
+//   Welcome: u32 display_id
+//   Error: error message
+//   ClockPing: f64 service timestamp
+//   FrameDone: u64 frame index, f64 device timestamp of the submission, u32 layer count
+//   ResetPose: empty. Asks the device to recenter its tracking.
+//
+// Timestamps are in milliseconds, each one in the clock of its sender.
+// The service sends a ClockPing after Welcome and then about once per second while the device
+// sends messages. The device must answer each ping with a ClockPong as soon as possible.
+// The clock offset is estimated from the exchanges with the lowest round trip times, assuming
+// symmetric latencies, and device timestamps are converted to the service clock.
+//
+// Submitted frames are not streamed, their textures only exist in the service process.
+// Applications stream the rendered images by their own means and FrameDone lets the device
+// know when each frame was submitted.
+//
+// Values use the encoding described in vr_wire, without the wire version header.
+//
+// Version history:
+//   1: initial version
+pub const MAGIC: &'static [u8] = b"WVRNET";
+pub const PROTOCOL_VERSION: u16 = 1;
+
+const DEVICE_HELLO: u8 = 1;
+const DEVICE_POSE: u8 = 2;
+const DEVICE_GAMEPAD_STATE: u8 = 3;
+const DEVICE_CLOCK_PONG: u8 = 4;
+
+const SERVICE_WELCOME: u8 = 1;
+const SERVICE_ERROR: u8 = 2;
+const SERVICE_CLOCK_PING: u8 = 3;
+const SERVICE_FRAME_DONE: u8 = 4;
+const SERVICE_RESET_POSE: u8 = 5;
+
+// Description of a streaming device, sent when it connects to the service
+#[derive(Debug, Clone)]
+pub struct NetworkDeviceInfo {
+    pub display: VRDisplayData,
+    // Refresh rate of the device screen in Hz
+    pub refresh_rate: f64,
+    pub gamepads: Vec<VRGamepadData>
+}
+
+// Acknowledgement of a frame submitted to a NetworkVRDisplay
+#[derive(Debug, Clone)]
+pub struct NetworkFrameDone {
+    pub frame_index: u64,
+    // Submission time in the device clock
+    pub timestamp: f64,
+    pub layer_count: u32
+}
+
+#[derive(Debug, Clone)]
+pub enum DeviceMessage {
+    Hello(NetworkDeviceInfo),
+    // params: device timestamp, pose
+    Pose(f64, VRPose),
+    // params: gamepad index, state
+    GamepadState(u32, VRGamepadState),
+    // params: service timestamp of the ping, device timestamp
+    ClockPong(f64, f64)
+}
+
+#[derive(Debug, Clone)]
+pub enum ServiceMessage {
+    // params: display_id
+    Welcome(u32),
+    Error(String),
+    // params: service timestamp
+    ClockPing(f64),
+    FrameDone(NetworkFrameDone),
+    ResetPose
+}
+
+pub fn encode_device_message(message: &DeviceMessage) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_device_message(&mut out, message);
+    out
+}
+
+fn write_device_message(out: &mut Vec<u8>, message: &DeviceMessage) {
+    match *message {
+        DeviceMessage::Hello(ref info) => {
+            vr_wire::write_u8(out, DEVICE_HELLO);
+            out.extend_from_slice(MAGIC);
+            vr_wire::write_u16(out, PROTOCOL_VERSION);
+            vr_wire::write_u16(out, vr_wire::WIRE_VERSION);
+            vr_wire::write_display_data(out, &info.display);
+            vr_wire::write_f64(out, info.refresh_rate);
+            vr_wire::write_u32(out, info.gamepads.len() as u32);
+            for gamepad in &info.gamepads {
+                vr_wire::write_gamepad_data(out, gamepad);
+            }
+        },
+        DeviceMessage::Pose(timestamp, ref pose) => {
+            vr_wire::write_u8(out, DEVICE_POSE);
+            vr_wire::write_f64(out, timestamp);
+            vr_wire::write_pose(out, pose);
+        },
+        DeviceMessage::GamepadState(index, ref state) => {
+            vr_wire::write_u8(out, DEVICE_GAMEPAD_STATE);
+            vr_wire::write_u32(out, index);
+            vr_wire::write_gamepad_state(out, state);
+        },
+        DeviceMessage::ClockPong(ping_time, device_time) => {
+            vr_wire::write_u8(out, DEVICE_CLOCK_PONG);
+            vr_wire::write_f64(out, ping_time);
+            vr_wire::write_f64(out, device_time);
+        }
+    }
+}
+
+// Hello messages are validated before decoding the device description,
+// so incompatible devices get a meaningful error
+pub fn decode_device_message(payload: &[u8]) -> Result<DeviceMessage, String> {
+    let mut reader = WireReader::new(payload);
+    let reader = &mut reader;
+    let message = match try!(reader.u8()) {
+        DEVICE_HELLO => {
+            if try!(reader.bytes(MAGIC.len())) != MAGIC {
+                return Err("Not a WebVR network device".into());
+            }
+            let protocol_version = try!(reader.u16());
+            let wire_version = try!(reader.u16());
+            if protocol_version != PROTOCOL_VERSION || wire_version != vr_wire::WIRE_VERSION {
+                return Err(format!("Unsupported protocol version {} with wire version {}",
+                                   protocol_version, wire_version));
+            }
+            let display = try!(vr_wire::read_display_data(reader));
+            let refresh_rate = try!(reader.f64());
+            let count = try!(reader.u32());
+            let mut gamepads = Vec::new();
+            for _ in 0..count {
+                gamepads.push(try!(vr_wire::read_gamepad_data(reader)));
+            }
+            DeviceMessage::Hello(NetworkDeviceInfo {
+                display: display,
+                refresh_rate: refresh_rate,
+                gamepads: gamepads
+            })
+        },
+        DEVICE_POSE => DeviceMessage::Pose(try!(reader.f64()), try!(vr_wire::read_pose(reader))),
+        DEVICE_GAMEPAD_STATE => {
+            DeviceMessage::GamepadState(try!(reader.u32()), try!(vr_wire::read_gamepad_state(reader)))
+        },
+        DEVICE_CLOCK_PONG => DeviceMessage::ClockPong(try!(reader.f64()), try!(reader.f64())),
+        kind => return Err(VRWireError::InvalidValue("device message kind", kind as u32).to_string())
+    };
+    try!(reader.finish());
+    Ok(message)
+}
+
+pub fn encode_service_message(message: &ServiceMessage) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_service_message(&mut out, message);
+    out
+}
+
+fn write_service_message(out: &mut Vec<u8>, message: &ServiceMessage) {
+    match *message {
+        ServiceMessage::Welcome(display_id) => {
+            vr_wire::write_u8(out, SERVICE_WELCOME);
+            vr_wire::write_u32(out, display_id);
+        },
+        ServiceMessage::Error(ref message) => {
+            vr_wire::write_u8(out, SERVICE_ERROR);
+            vr_wire::write_string(out, message);
+        },
+        ServiceMessage::ClockPing(time) => {
+            vr_wire::write_u8(out, SERVICE_CLOCK_PING);
+            vr_wire::write_f64(out, time);
+        },
+        ServiceMessage::FrameDone(ref frame) => {
+            vr_wire::write_u8(out, SERVICE_FRAME_DONE);
+            vr_wire::write_u64(out, frame.frame_index);
+            vr_wire::write_f64(out, frame.timestamp);
+            vr_wire::write_u32(out, frame.layer_count);
+        },
+        ServiceMessage::ResetPose => vr_wire::write_u8(out, SERVICE_RESET_POSE)
+    }
+}
+
+pub fn decode_service_message(payload: &[u8]) -> Result<ServiceMessage, VRWireError> {
+    let mut reader = WireReader::new(payload);
+    let reader = &mut reader;
+    let message = match try!(reader.u8()) {
+        SERVICE_WELCOME => ServiceMessage::Welcome(try!(reader.u32())),
+        SERVICE_ERROR => ServiceMessage::Error(try!(reader.string())),
+        SERVICE_CLOCK_PING => ServiceMessage::ClockPing(try!(reader.f64())),
+        SERVICE_FRAME_DONE => ServiceMessage::FrameDone(NetworkFrameDone {
+            frame_index: try!(reader.u64()),
+            timestamp: try!(reader.f64()),
+            layer_count: try!(reader.u32())
+        }),
+        SERVICE_RESET_POSE => ServiceMessage::ResetPose,
+        kind => return Err(VRWireError::InvalidValue("service message kind", kind as u32))
+    };
+    try!(reader.finish());
+    Ok(message)
+}
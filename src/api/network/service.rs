@@ -0,0 +1,114 @@
+use {VRService, VRDisplayPtr, VRError, VRGamepadPtr, VREvent};
+use super::device::{NetworkDevice, NetworkDevicePtr};
+use super::display::{NetworkVRDisplay, NetworkVRDisplayPtr};
+use super::gamepad::{NetworkVRGamepad, NetworkVRGamepadPtr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+// Service that listens for a remote device streaming display poses and gamepad input over TCP,
+// e.g. a phone used as a headset. The protocol is described in network/protocol.rs.
+// The display is found once the first device connects.
+pub struct NetworkVRService {
+    address: SocketAddr,
+    // Address the listener is bound to, once initialized
+    local_address: Option<SocketAddr>,
+    stopped: Arc<AtomicBool>,
+    device: NetworkDevicePtr,
+    display: Option<NetworkVRDisplayPtr>,
+    gamepads: Vec<NetworkVRGamepadPtr>
+}
+
+impl VRService for NetworkVRService {
+    fn initialize(&mut self) -> Result<(), VRError> {
+        if self.local_address.is_some() {
+            return Ok(());
+        }
+        let listener = try!(TcpListener::bind(self.address).map_err(|e| {
+            VRError::Backend("Network".into(), format!("Can't listen on {}: {}", self.address, e))
+        }));
+        let local_address = try!(listener.local_addr().map_err(|e| VRError::Backend("Network".into(), e.to_string())));
+        self.local_address = Some(local_address);
+
+        let stopped = self.stopped.clone();
+        let device = self.device.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(stream) => {
+                        let device = device.clone();
+                        thread::spawn(move || device.serve(stream));
+                    },
+                    Err(e) => error!("Error accepting network VR device: {}", e)
+                }
+            }
+        });
+        Ok(())
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, VRError> {
+        try!(self.initialize());
+        if self.display.is_none() && self.device.has_display() {
+            self.display = Some(NetworkVRDisplay::new(self.device.clone()));
+        }
+        Ok(self.display.iter().map(|d| d.clone() as VRDisplayPtr).collect())
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, VRError> {
+        try!(self.initialize());
+        let ids = self.device.gamepad_ids();
+        for index in self.gamepads.len()..ids.len() {
+            self.gamepads.push(NetworkVRGamepad::new(ids[index], index, self.device.clone()));
+        }
+        Ok(self.gamepads.iter().map(|g| g.clone() as VRGamepadPtr).collect())
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        self.device.poll_events()
+    }
+}
+
+impl Drop for NetworkVRService {
+    fn drop(&mut self) {
+        self.device.disconnect();
+        if let Some(mut address) = self.local_address {
+            self.stopped.store(true, Ordering::SeqCst);
+            // Wake up the blocked accept call
+            if address.ip().is_unspecified() {
+                address.set_ip(match address.ip() {
+                    IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
+                    IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1))
+                });
+            }
+            let _ = TcpStream::connect(address);
+        }
+    }
+}
+
+impl NetworkVRService {
+    // Creates a service that listens on `address` when it's initialized.
+    // Port 0 selects any free port, see local_address.
+    pub fn new(address: SocketAddr) -> NetworkVRService {
+        NetworkVRService {
+            address: address,
+            local_address: None,
+            stopped: Arc::new(AtomicBool::new(false)),
+            device: NetworkDevice::new(),
+            display: None,
+            gamepads: Vec::new()
+        }
+    }
+
+    // Address devices connect to. None until the service is initialized.
+    pub fn local_address(&self) -> Option<SocketAddr> {
+        self.local_address
+    }
+}
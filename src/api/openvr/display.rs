@@ -5,48 +5,50 @@ use super::binding::EVREye::*;
 use super::binding::EVRInitError::*;
 use super::binding::ETrackingUniverseOrigin::*;
 use super::binding::EGraphicsAPIConvention::*;
-use super::library::OpenVRLibrary;
+use super::binding::EVRCompositorError::*;
 use super::constants;
+use super::runtime::{OpenVRRuntime, OpenVRRuntimePtr};
+use super::submit;
 use super::super::utils;
 use std::ffi::CString;
-use std::sync::Arc;
-use std::cell::RefCell;
+use std::sync::{Arc, RwLock};
 use std::slice;
 use std::str;
 use std::ptr;
 use std::mem;
 use {VRDisplay, VRDisplayData, VRDisplayCapabilities, VREyeParameters,
-    VRFrameData, VRPose, VRStageParameters, VRFieldOfView, VRLayer};
+    VRFrameData, VRFrameStats, VRPose, VRReferenceSpace, VRStageBounds, VRStageParameters, VRFieldOfView,
+    VRLayer};
 
-pub type OpenVRDisplayPtr = Arc<RefCell<OpenVRDisplay>>;
+pub type OpenVRDisplayPtr = Arc<RwLock<OpenVRDisplay>>;
 
 pub struct OpenVRDisplay {
     display_id: u32,
-    lib: *const OpenVRLibrary,
     index: openvr::TrackedDeviceIndex_t,
-    system: *mut openvr::VR_IVRSystem_FnTable,
-    chaperone: *mut openvr::VR_IVRChaperone_FnTable,
-    compositor: *mut openvr::VR_IVRCompositor_FnTable
+    runtime: OpenVRRuntimePtr,
+    reference_space: VRReferenceSpace
 }
 
-unsafe impl Send for OpenVRDisplay {}
-unsafe impl Sync for OpenVRDisplay {}
-
 impl OpenVRDisplay {
-    pub fn new(lib: *const OpenVRLibrary,
-               index: openvr::TrackedDeviceIndex_t,
-               system: *mut openvr::VR_IVRSystem_FnTable,
-               chaperone: *mut openvr::VR_IVRChaperone_FnTable) 
-               -> Arc<RefCell<OpenVRDisplay>> {
-        Arc::new(RefCell::new(OpenVRDisplay {
+    pub fn new(index: openvr::TrackedDeviceIndex_t,
+               runtime: OpenVRRuntimePtr)
+               -> Arc<RwLock<OpenVRDisplay>> {
+        Arc::new(RwLock::new(OpenVRDisplay {
             display_id: utils::new_id(),
-            lib: lib,
             index: index,
-            system: system,
-            chaperone: chaperone,
-            compositor: ptr::null_mut()
+            runtime: runtime,
+            reference_space: VRReferenceSpace::Seated
         }))
     }
+
+    // OpenVR tracking universe matching a reference space
+    pub fn tracking_universe(space: VRReferenceSpace) -> openvr::ETrackingUniverseOrigin {
+        match space {
+            VRReferenceSpace::Seated => ETrackingUniverseOrigin_TrackingUniverseSeated,
+            VRReferenceSpace::Standing => ETrackingUniverseOrigin_TrackingUniverseStanding,
+            VRReferenceSpace::Raw => ETrackingUniverseOrigin_TrackingUniverseRawAndUncalibrated
+        }
+    }
 }
 
 impl Drop for OpenVRDisplay {
@@ -64,110 +66,160 @@ impl VRDisplay for OpenVRDisplay {
     // Returns the current display data.
     fn data(&self) -> VRDisplayData {
         let mut data = VRDisplayData::default();
+        let runtime = self.runtime.lock().unwrap();
         
         OpenVRDisplay::fetch_capabilities(&mut data.capabilities);
-        self.fetch_eye_parameters(&mut data.left_eye_parameters, &mut data.right_eye_parameters);
-        self.fetch_stage_parameters(&mut data);
+        self.fetch_eye_parameters(&runtime, &mut data.left_eye_parameters, &mut data.right_eye_parameters);
+        self.fetch_stage_parameters(&runtime, &mut data);
         data.display_id = self.display_id;
         data.display_name = format!("{} {}",
-                            self.get_string_property(ETrackedDeviceProperty_Prop_ManufacturerName_String),
-                            self.get_string_property(ETrackedDeviceProperty_Prop_ModelNumber_String));
-        data.connected = self.is_connected();
+                            self.get_string_property(&runtime, ETrackedDeviceProperty_Prop_ManufacturerName_String),
+                            self.get_string_property(&runtime, ETrackedDeviceProperty_Prop_ModelNumber_String));
+        data.connected = self.is_connected(&runtime);
 
         data
     }
 
     fn inmediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
-        let mut data = VRFrameData::default();
-
-        let mut tracked_poses: [openvr::TrackedDevicePose_t; openvr::k_unMaxTrackedDeviceCount as usize]
-                              = unsafe { mem::uninitialized() };
-        unsafe {
-            // Calculates updated poses for all displays
-            (*self.system).GetDeviceToAbsoluteTrackingPose.unwrap()(ETrackingUniverseOrigin_TrackingUniverseSeated,
-                                                                    self.get_seconds_to_photons(),
-                                                                    &mut tracked_poses[0],
-                                                                    openvr::k_unMaxTrackedDeviceCount);
-        };
+        let runtime = self.runtime.lock().unwrap();
+        self.fetch_predicted_frame_data(&runtime, near_z, far_z, self.get_seconds_to_photons(&runtime))
+    }
 
-        let display_pose = &tracked_poses[self.index as usize];
-        self.fetch_frame_data(near_z as f32, far_z as f32, &display_pose, &mut data);
+    fn predicted_frame_data(&self, near_z: f64, far_z: f64, target_time: f64) -> VRFrameData {
+        let seconds = (target_time - utils::timestamp()) / 1000.0;
+        let runtime = self.runtime.lock().unwrap();
+        let mut data = self.fetch_predicted_frame_data(&runtime, near_z, far_z, seconds as f32);
+        data.timestamp = target_time;
 
         data
     }
 
      fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
-         if self.compositor == ptr::null_mut() {
+         let runtime = self.runtime.lock().unwrap();
+         let compositor = runtime.compositor;
+         if compositor == ptr::null_mut() {
              // Fallback to inmediate mode if compositor not available
-             self.inmediate_frame_data(near_z, far_z);
+             return self.fetch_predicted_frame_data(&runtime, near_z, far_z, self.get_seconds_to_photons(&runtime));
          }
 
          let mut display_pose: openvr::TrackedDevicePose_t = unsafe { mem::uninitialized() };
          unsafe {
-             (*self.compositor).GetLastPoseForTrackedDeviceIndex.unwrap()(self.index,
+             (*compositor).GetLastPoseForTrackedDeviceIndex.unwrap()(self.index,
                                                                           &mut display_pose,
                                                                           ptr::null_mut());
          }
          let mut data = VRFrameData::default();
-         self.fetch_frame_data(near_z as f32, far_z as f32, &display_pose, &mut data);
+         self.fetch_frame_data(&runtime, near_z as f32, far_z as f32, &display_pose, &mut data);
 
          data
       }
 
     // Resets the pose for this display
     fn reset_pose(&mut self) {
+        let runtime = self.runtime.lock().unwrap();
         unsafe {
-            (*self.system).ResetSeatedZeroPose.unwrap()();
+            (*runtime.system).ResetSeatedZeroPose.unwrap()();
         }
     }
 
-    fn sync_poses(&mut self) {
-        if !self.ensure_compositor_ready() {
-            return;
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+        let runtime = self.runtime.lock().unwrap();
+        let compositor = runtime.compositor;
+        if compositor != ptr::null_mut() {
+            // Poses returned by WaitGetPoses use the tracking space of the compositor
+            unsafe {
+                (*compositor).SetTrackingSpace.unwrap()(OpenVRDisplay::tracking_universe(space));
+            }
         }
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+
+    fn sync_poses(&self) {
+        let compositor = {
+            let mut runtime = self.runtime.lock().unwrap();
+            if !self.ensure_compositor_ready(&mut runtime) {
+                return;
+            }
+            runtime.compositor
+        };
+        // WaitGetPoses blocks until vsync. It's called without the runtime lock
+        // so that other threads can keep reading poses while the render thread waits.
         unsafe {
-            (*self.compositor).WaitGetPoses.unwrap()(ptr::null_mut(), 0, ptr::null_mut(), 0);
+            (*compositor).WaitGetPoses.unwrap()(ptr::null_mut(), 0, ptr::null_mut(), 0);
         }
     }
 
     fn submit_frame(&mut self, layer: &VRLayer) {
-        if !self.ensure_compositor_ready() {
+        let mut runtime = self.runtime.lock().unwrap();
+        if !self.ensure_compositor_ready(&mut runtime) {
             return;
         }
-        let mut texture: openvr::Texture_t = unsafe { mem::uninitialized() };
-        texture.handle = unsafe { mem::transmute(layer.texture_id as u64) };
-        texture.eColorSpace = openvr::EColorSpace::EColorSpace_ColorSpace_Auto;
-        texture.eType = EGraphicsAPIConvention_API_OpenGL;
+        // Each eye may be rendered to a different texture
+        let left_depth = layer.depth.as_ref().map(|depth| depth.texture_id);
+        let right_depth = layer.depth.as_ref().map(|depth| depth.right_texture());
+        unsafe {
+            self.submit_eye(&mut runtime, EVREye_Eye_Left, layer, layer.left_texture(), &layer.left_bounds, left_depth);
+            self.submit_eye(&mut runtime, EVREye_Eye_Right, layer, layer.right_texture(), &layer.right_bounds, right_depth);
+            (*runtime.compositor).PostPresentHandoff.unwrap()();
+        }
+    }
 
-        let mut left_bounds = texture_bounds_to_openvr(&layer.left_bounds);
-        let mut right_bounds = texture_bounds_to_openvr(&layer.right_bounds);
-        let flags = openvr::EVRSubmitFlags::EVRSubmitFlags_Submit_Default;
+    fn frame_stats(&self) -> VRFrameStats {
+        let mut stats = VRFrameStats::default();
+        let mut runtime = self.runtime.lock().unwrap();
+        stats.refresh_rate = self.get_float_property(&runtime, ETrackedDeviceProperty_Prop_DisplayFrequency_Float)
+                                 .unwrap_or(90.0) as f64;
+        if !self.ensure_compositor_ready(&mut runtime) {
+            return stats;
+        }
 
         unsafe {
-            (*self.compositor).Submit.unwrap()(EVREye_Eye_Left, &mut texture, &mut left_bounds, flags);
-            (*self.compositor).Submit.unwrap()(EVREye_Eye_Right, &mut texture, &mut right_bounds, flags);
-            (*self.compositor).PostPresentHandoff.unwrap()();
+            let compositor = runtime.compositor;
+            let mut timing: openvr::Compositor_FrameTiming = mem::zeroed();
+            timing.m_nSize = mem::size_of::<openvr::Compositor_FrameTiming>() as u32;
+            // Timing of the most recent frame
+            if (*compositor).GetFrameTiming.unwrap()(&mut timing, 0) {
+                stats.frame_index = timing.m_nFrameIndex as u64;
+                stats.frame_interval = timing.m_flClientFrameIntervalMs as f64;
+                // Times are relative to the vsync that started the frame
+                stats.cpu_frame_time = (timing.m_flNewFrameReadyMs - timing.m_flNewPosesReadyMs).max(0.0) as f64;
+            }
+
+            let mut cumulative: openvr::Compositor_CumulativeStats = mem::zeroed();
+            (*compositor).GetCumulativeStats.unwrap()(&mut cumulative,
+                                                      mem::size_of::<openvr::Compositor_CumulativeStats>() as u32);
+            // OpenVR reports the refreshes that presented a previous frame again as dropped frames
+            stats.missed_frames = cumulative.m_nNumDroppedFrames as u64;
+            stats.reprojected_frames = cumulative.m_nNumReprojectedFrames as u64;
         }
+
+        stats
     }
 
     fn stop_present(&mut self) {
-         if self.compositor != ptr::null_mut() {
+         let runtime = self.runtime.lock().unwrap();
+         let compositor = runtime.compositor;
+         if compositor != ptr::null_mut() {
              println!("ClearLastSubmittedFrame");
              unsafe {
-                (*self.compositor).ClearLastSubmittedFrame.unwrap()();
+                (*compositor).ClearLastSubmittedFrame.unwrap()();
              }
          }
     }
 }
 
 impl OpenVRDisplay {
-    fn get_string_property(&self, name: openvr::ETrackedDeviceProperty) -> String {
+    fn get_string_property(&self, runtime: &OpenVRRuntime, name: openvr::ETrackedDeviceProperty) -> String {
         let max_size = 256;
         let result = String::with_capacity(max_size);
         let mut error = ETrackedPropertyError_TrackedProp_Success;
         let size;
         unsafe {
-            size = (*self.system).GetStringTrackedDeviceProperty.unwrap()(self.index, name, 
+            size = (*runtime.system).GetStringTrackedDeviceProperty.unwrap()(self.index, name, 
                                                                           result.as_ptr() as *mut i8, 
                                                                           max_size as u32, 
                                                                           &mut error)
@@ -183,10 +235,10 @@ impl OpenVRDisplay {
         }
     }
 
-    fn get_float_property(&self, name: openvr::ETrackedDeviceProperty) -> Option<f32> {
+    fn get_float_property(&self, runtime: &OpenVRRuntime, name: openvr::ETrackedDeviceProperty) -> Option<f32> {
         let mut error = ETrackedPropertyError_TrackedProp_Success;
         let result = unsafe {
-            (*self.system).GetFloatTrackedDeviceProperty.unwrap()(self.index, name, &mut error)
+            (*runtime.system).GetFloatTrackedDeviceProperty.unwrap()(self.index, name, &mut error)
         };
         if error as u32 == ETrackedPropertyError_TrackedProp_Success as u32 {
             Some(result)
@@ -200,12 +252,14 @@ impl OpenVRDisplay {
         capabilities.has_orientation = true;
         capabilities.has_external_display = true;
         capabilities.has_position = true;
+        // The compositor presents a single eye texture per frame
+        capabilities.max_layers = 1;
     }
 
-    fn fetch_field_of_view(&self, eye: openvr::EVREye, fov: &mut VRFieldOfView) {
+    fn fetch_field_of_view(&self, runtime: &OpenVRRuntime, eye: openvr::EVREye, fov: &mut VRFieldOfView) {
         let (mut up, mut right, mut down, mut left) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
         unsafe {
-            (*self.system).GetProjectionRaw.unwrap()(eye, &mut left, &mut right, &mut up, &mut down);
+            (*runtime.system).GetProjectionRaw.unwrap()(eye, &mut left, &mut right, &mut up, &mut down);
         }
         // OpenVR returns clipping plane coordinates in raw tangent units
         // WebVR expects degrees, so we have to convert tangent units to degrees
@@ -215,19 +269,19 @@ impl OpenVRDisplay {
         fov.left_degrees = -left.atan().to_degrees() as f64;
     }
 
-    fn is_connected(&self) -> bool {
+    fn is_connected(&self, runtime: &OpenVRRuntime) -> bool {
         unsafe {
-            (*self.system).IsTrackedDeviceConnected.unwrap()(self.index)
+            (*runtime.system).IsTrackedDeviceConnected.unwrap()(self.index)
         }
     }
 
-    fn fetch_eye_parameters(&self, left: &mut VREyeParameters, right: &mut VREyeParameters) {
-        self.fetch_field_of_view(EVREye_Eye_Left, &mut left.field_of_view);
-        self.fetch_field_of_view(EVREye_Eye_Right, &mut right.field_of_view);
+    fn fetch_eye_parameters(&self, runtime: &OpenVRRuntime, left: &mut VREyeParameters, right: &mut VREyeParameters) {
+        self.fetch_field_of_view(runtime, EVREye_Eye_Left, &mut left.field_of_view);
+        self.fetch_field_of_view(runtime, EVREye_Eye_Right, &mut right.field_of_view);
 
         let (left_matrix, right_matrix) = unsafe {
-            ((*self.system).GetEyeToHeadTransform.unwrap()(EVREye_Eye_Left),
-             (*self.system).GetEyeToHeadTransform.unwrap()(EVREye_Eye_Right))
+            ((*runtime.system).GetEyeToHeadTransform.unwrap()(EVREye_Eye_Left),
+             (*runtime.system).GetEyeToHeadTransform.unwrap()(EVREye_Eye_Right))
         };
         
         left.offset = [left_matrix.m[0][3], left_matrix.m[1][3], left_matrix.m[2][3]];
@@ -235,7 +289,7 @@ impl OpenVRDisplay {
 
         let (mut width, mut height) = (0, 0);
         unsafe {
-            (*self.system).GetRecommendedRenderTargetSize.unwrap()(&mut width, &mut height);
+            (*runtime.system).GetRecommendedRenderTargetSize.unwrap()(&mut width, &mut height);
         }
         left.render_width = width;
         left.render_height = height;
@@ -243,43 +297,110 @@ impl OpenVRDisplay {
         right.render_height = height;
     }
 
-    fn fetch_stage_parameters(&self, data: &mut VRDisplayData) {
-        // Play area size
+    fn fetch_stage_parameters(&self, runtime: &OpenVRRuntime, data: &mut VRDisplayData) {
+        let matrix: openvr::HmdMatrix34_t = unsafe {
+            (*runtime.system).GetSeatedZeroPoseToStandingAbsoluteTrackingPose.unwrap()()
+        };
+
+        // Error states make the chaperone data unreliable
+        let state = unsafe { (*runtime.chaperone).GetCalibrationState.unwrap()() };
+        if (state as i32) >= openvr::ChaperoneCalibrationState::ChaperoneCalibrationState_Error as i32 {
+            // Chaperone data not ready yet. HMD might be deactivated.
+            // The play area is reported as not available until the data is ready, but the
+            // seated to standing transform still comes from the runtime, not from the defaults.
+            data.stage_parameters = Some(VRStageParameters {
+                sitting_to_standing_transform: openvr_matrix34_to_array(&matrix),
+                size_x: 0.0,
+                size_z: 0.0,
+                bounds: VRStageBounds::NotAvailable
+            });
+            return;
+        }
+
+        // Play area size and rect, in standing space
         let mut size_x = 0f32;
         let mut size_z = 0f32;
+        let mut rect: openvr::HmdQuad_t = unsafe { mem::uninitialized() };
+        let play_area = unsafe {
+            (*runtime.chaperone).GetPlayAreaSize.unwrap()(&mut size_x, &mut size_z) &&
+            size_x > 0.0 && size_z > 0.0 &&
+            (*runtime.chaperone).GetPlayAreaRect.unwrap()(&mut rect)
+        };
+
+        let bounds = if play_area {
+            VRStageBounds::Polygon(rect.vCorners.iter().map(|corner| [corner.v[0], 0.0, corner.v[2]]).collect())
+        } else {
+            // The play area is not set up, use the collision bounds drawn by the user instead
+            let bounds = self.fetch_collision_bounds(runtime);
+            let (width, depth) = match bounds {
+                VRStageBounds::Polygon(ref points) => bounds_extent(points),
+                VRStageBounds::NotAvailable => (0.0, 0.0)
+            };
+            size_x = width;
+            size_z = depth;
+            bounds
+        };
 
+        data.stage_parameters = Some(VRStageParameters {
+            sitting_to_standing_transform: openvr_matrix34_to_array(&matrix),
+            size_x: size_x,
+            size_z: size_z,
+            bounds: bounds
+        });
+    }
+
+    // Boundary of the live collision bounds, in standing space.
+    fn fetch_collision_bounds(&self, runtime: &OpenVRRuntime) -> VRStageBounds {
+        if runtime.chaperone_setup.is_null() {
+            return VRStageBounds::NotAvailable;
+        }
+
+        let mut count = 0u32;
         unsafe {
-            (*self.chaperone).GetPlayAreaSize.unwrap()(&mut size_x, &mut size_z);
+            // Query the number of quads first
+            (*runtime.chaperone_setup).GetLiveCollisionBoundsInfo.unwrap()(ptr::null_mut(), &mut count);
+        }
+        if count == 0 {
+            return VRStageBounds::NotAvailable;
         }
 
-        if size_x > 0.0 && size_z > 0.0 {
-            let matrix: openvr::HmdMatrix34_t = unsafe {
-                (*self.system).GetSeatedZeroPoseToStandingAbsoluteTrackingPose.unwrap()()
-            };
+        let mut quads: Vec<openvr::HmdQuad_t> = Vec::with_capacity(count as usize);
+        let fetched = unsafe {
+            let fetched = (*runtime.chaperone_setup).GetLiveCollisionBoundsInfo.unwrap()(quads.as_mut_ptr(), &mut count);
+            quads.set_len(if fetched { count as usize } else { 0 });
+            fetched
+        };
+        if !fetched || quads.len() < 3 {
+            return VRStageBounds::NotAvailable;
+        }
 
-            data.stage_parameters = Some(VRStageParameters {
-                sitting_to_standing_transform: openvr_matrix34_to_array(&matrix),
-                size_x: size_x,
-                size_z: size_z
-            });
-        } else {
-            
-            // Chaperone data not ready yet. HMD might be deactivated.
-            // Use some default average transform until data is ready.
-            let matrix = [1.0, 0.0, 0.0, 0.0,
-                          0.0, 1.0, 0.0, 0.0,
-                          0.0, 0.0, 1.0, 0.0,
-                          0.0, 0.75, 0.0, 1.0];
+        // Each quad is a wall of the bounds, ordered around the play area.
+        // The first corner of each wall lies on the floor.
+        VRStageBounds::Polygon(quads.iter().map(|quad| [quad.vCorners[0].v[0], 0.0, quad.vCorners[0].v[2]]).collect())
+    }
 
-            data.stage_parameters = Some(VRStageParameters {
-                sitting_to_standing_transform: matrix,
-                size_x: 2.0,
-                size_z: 2.0
-            });
-        }
+    // Frame data with the pose predicted by OpenVR for `seconds` from now
+    fn fetch_predicted_frame_data(&self, runtime: &OpenVRRuntime, near_z: f64, far_z: f64, seconds: f32) -> VRFrameData {
+        let mut data = VRFrameData::default();
+
+        let mut tracked_poses: [openvr::TrackedDevicePose_t; openvr::k_unMaxTrackedDeviceCount as usize]
+                              = unsafe { mem::uninitialized() };
+        unsafe {
+            // Calculates updated poses for all displays
+            (*runtime.system).GetDeviceToAbsoluteTrackingPose.unwrap()(OpenVRDisplay::tracking_universe(self.reference_space),
+                                                                    seconds,
+                                                                    &mut tracked_poses[0],
+                                                                    openvr::k_unMaxTrackedDeviceCount);
+        };
+
+        let display_pose = &tracked_poses[self.index as usize];
+        self.fetch_frame_data(runtime, near_z as f32, far_z as f32, &display_pose, &mut data);
+
+        data
     }
 
     fn fetch_frame_data(&self,
+                        runtime: &OpenVRRuntime,
                         near_z: f32,
                         far_z: f32,
                         display_pose: &openvr::TrackedDevicePose_t,
@@ -287,8 +408,8 @@ impl OpenVRDisplay {
         let near_z = near_z as f32;
         let far_z = far_z as f32;
         OpenVRDisplay::fetch_pose(&display_pose, &mut out.pose);
-        self.fetch_projection_matrix(EVREye_Eye_Left, near_z, far_z, &mut out.left_projection_matrix);
-        self.fetch_projection_matrix(EVREye_Eye_Right, near_z, far_z, &mut out.right_projection_matrix);
+        self.fetch_projection_matrix(runtime, EVREye_Eye_Left, near_z, far_z, &mut out.left_projection_matrix);
+        self.fetch_projection_matrix(runtime, EVREye_Eye_Right, near_z, far_z, &mut out.right_projection_matrix);
 
         let mut view_matrix: [f32; 16] = unsafe { mem::uninitialized() };
         self.fetch_view_matrix(&display_pose, &mut view_matrix);
@@ -297,8 +418,8 @@ impl OpenVRDisplay {
         let mut right_eye:[f32; 16] = unsafe { mem::uninitialized() };
         
         // Fech the transform of each eye
-        self.fetch_eye_to_head_matrix(EVREye_Eye_Left, &mut left_eye);
-        self.fetch_eye_to_head_matrix(EVREye_Eye_Right, &mut right_eye);
+        self.fetch_eye_to_head_matrix(runtime, EVREye_Eye_Left, &mut left_eye);
+        self.fetch_eye_to_head_matrix(runtime, EVREye_Eye_Right, &mut right_eye);
 
         // View matrix must by multiplied by each eye_to_head transformation matrix
         utils::multiply_matrix(&view_matrix, &left_eye, &mut out.left_view_matrix);
@@ -312,16 +433,16 @@ impl OpenVRDisplay {
         out.timestamp = utils::timestamp();
     }
 
-    fn fetch_projection_matrix(&self, eye: openvr::EVREye, near: f32, far: f32, out: &mut [f32; 16]) {
+    fn fetch_projection_matrix(&self, runtime: &OpenVRRuntime, eye: openvr::EVREye, near: f32, far: f32, out: &mut [f32; 16]) {
         let matrix = unsafe {
-            (*self.system).GetProjectionMatrix.unwrap()(eye, near, far, EGraphicsAPIConvention_API_OpenGL)
+            (*runtime.system).GetProjectionMatrix.unwrap()(eye, near, far, EGraphicsAPIConvention_API_OpenGL)
         };
         *out = openvr_matrix44_to_array(&matrix);
     }
 
-    fn fetch_eye_to_head_matrix(&self, eye: openvr::EVREye, out: &mut [f32; 16]) {
+    fn fetch_eye_to_head_matrix(&self, runtime: &OpenVRRuntime, eye: openvr::EVREye, out: &mut [f32; 16]) {
         let matrix = unsafe {
-            (*self.system).GetEyeToHeadTransform.unwrap()(eye)
+            (*runtime.system).GetEyeToHeadTransform.unwrap()(eye)
         };
         *out = openvr_matrix34_to_array(&matrix);
     }
@@ -365,42 +486,122 @@ impl OpenVRDisplay {
 
     // Computing seconds to photons
     // More info: https://github.com/ValveSoftware/openvr/wiki/IVRSystem::GetDeviceToAbsoluteTrackingPose
-    fn get_seconds_to_photons(&self) -> f32 {
+    fn get_seconds_to_photons(&self, runtime: &OpenVRRuntime) -> f32 {
         let mut seconds_last_vsync = 0f32;
         let average_value = 0.04f32;
 
         unsafe {
-            if !(*self.system).GetTimeSinceLastVsync.unwrap()(&mut seconds_last_vsync, ptr::null_mut()) {
+            if !(*runtime.system).GetTimeSinceLastVsync.unwrap()(&mut seconds_last_vsync, ptr::null_mut()) {
                 // no vsync times are available, return a default average value
                 return average_value;
             }
         }
-        let display_freq = self.get_float_property(ETrackedDeviceProperty_Prop_DisplayFrequency_Float).unwrap_or(90.0);
+        let display_freq = self.get_float_property(runtime, ETrackedDeviceProperty_Prop_DisplayFrequency_Float).unwrap_or(90.0);
         let frame_duration = 1.0 / display_freq;
-        if let Some(vsync_to_photons) = self.get_float_property(ETrackedDeviceProperty_Prop_SecondsFromVsyncToPhotons_Float) {
+        if let Some(vsync_to_photons) = self.get_float_property(runtime, ETrackedDeviceProperty_Prop_SecondsFromVsyncToPhotons_Float) {
             frame_duration - seconds_last_vsync + vsync_to_photons
         } else {
             0.04f32
         }
     }
 
-    fn ensure_compositor_ready(&mut self)-> bool {
-        if self.compositor != ptr::null_mut() {
+    // Submits the texture of an eye.
+    // The render pose and the depth texture of the layer are submitted when available,
+    // so the compositor can reproject the frame.
+    unsafe fn submit_eye(&self,
+                         runtime: &mut OpenVRRuntime,
+                         eye: openvr::EVREye,
+                         layer: &VRLayer,
+                         texture_id: u32,
+                         bounds: &[f32; 4],
+                         depth_texture_id: Option<u32>) {
+        let submit_texture = submit::submit_fn(runtime.compositor);
+        let mut texture = gl_texture_to_openvr(texture_id);
+        let mut bounds = texture_bounds_to_openvr(bounds);
+
+        let pose = layer.render_pose.as_ref().and_then(|pose| {
+            pose.orientation.map(|orientation| {
+                pose_to_openvr_matrix(&pose.position.unwrap_or([0.0, 0.0, 0.0]), &orientation)
+            })
+        });
+        let depth = match (layer.depth.as_ref(), depth_texture_id) {
+            (Some(depth), Some(depth_texture_id)) => Some(submit::VRTextureDepthInfo_t {
+                handle: mem::transmute(depth_texture_id as u64),
+                mProjection: (*runtime.system).GetProjectionMatrix.unwrap()(eye, depth.near as f32, depth.far as f32,
+                                                                         EGraphicsAPIConvention_API_OpenGL),
+                vRange: openvr::HmdVector2_t {
+                    v: [0.0, 1.0]
+                }
+            }),
+            _ => None
+        };
+
+        let result = match (pose, depth) {
+            _ if !runtime.extended_submit => None,
+            (None, None) => None,
+            (Some(pose), None) => {
+                let mut texture = submit::VRTextureWithPose_t {
+                    texture: texture,
+                    mDeviceToAbsoluteTracking: pose
+                };
+                Some(submit_texture(eye, &mut texture as *mut _ as *mut openvr::Texture_t, &mut bounds,
+                                    submit::EVRSubmitFlags_Submit_TextureWithPose))
+            },
+            (None, Some(depth)) => {
+                let mut texture = submit::VRTextureWithDepth_t {
+                    texture: texture,
+                    depth: depth
+                };
+                Some(submit_texture(eye, &mut texture as *mut _ as *mut openvr::Texture_t, &mut bounds,
+                                    submit::EVRSubmitFlags_Submit_TextureWithDepth))
+            },
+            (Some(pose), Some(depth)) => {
+                let mut texture = submit::VRTextureWithPoseAndDepth_t {
+                    texture: texture,
+                    mDeviceToAbsoluteTracking: pose,
+                    depth: depth
+                };
+                Some(submit_texture(eye, &mut texture as *mut _ as *mut openvr::Texture_t, &mut bounds,
+                                    submit::EVRSubmitFlags_Submit_TextureWithPose |
+                                    submit::EVRSubmitFlags_Submit_TextureWithDepth))
+            }
+        };
+
+        match result {
+            Some(EVRCompositorError_VRCompositorError_None) => {},
+            Some(error) => {
+                // Runtimes without reprojection support reject the extra data.
+                // Submit the plain texture instead, and keep doing so if the runtime accepts it.
+                let plain = submit_texture(eye, &mut texture, &mut bounds, submit::EVRSubmitFlags_Submit_Default);
+                if plain == EVRCompositorError_VRCompositorError_None {
+                    warn!("OpenVR compositor rejected the render pose or depth of the layer: {:?}", error);
+                    runtime.extended_submit = false;
+                }
+            },
+            None => {
+                submit_texture(eye, &mut texture, &mut bounds, submit::EVRSubmitFlags_Submit_Default);
+            }
+        }
+    }
+
+    // Loads the compositor shared by the displays of the runtime
+    fn ensure_compositor_ready(&self, runtime: &mut OpenVRRuntime) -> bool {
+        if runtime.compositor != ptr::null_mut() {
             return true;
         }
 
         unsafe {
             let mut error = EVRInitError_VRInitError_None;
             let name = CString::new(format!("FnTable:{}", constants::IVRCompositor_Version)).unwrap();
-            self.compositor = (*(*self.lib).get_interface)(name.as_ptr(), &mut error)
-                          as *mut openvr::VR_IVRCompositor_FnTable;
-            if error as u32 == EVRInitError_VRInitError_None as u32 && self.compositor != ptr::null_mut() {
-                // Set seated tracking space (default in WebVR)
-                (*self.compositor).SetTrackingSpace.unwrap()(ETrackingUniverseOrigin_TrackingUniverseSeated);
+            let compositor = (*runtime.lib.get_interface)(name.as_ptr(), &mut error)
+                             as *mut openvr::VR_IVRCompositor_FnTable;
+            if error as u32 == EVRInitError_VRInitError_None as u32 && compositor != ptr::null_mut() {
+                // Seated tracking space is the default in WebVR
+                (*compositor).SetTrackingSpace.unwrap()(OpenVRDisplay::tracking_universe(self.reference_space));
+                runtime.compositor = compositor;
                 true
             } else {
                 error!("Error initializing OpenVR compositor: {:?}", error as u32);
-                self.compositor = ptr::null_mut();
                 false
             }
         }
@@ -446,6 +647,26 @@ fn openvr_matrix_to_quat(matrix: &openvr::HmdMatrix34_t) -> [f32; 4] {
     [x, y, z, w]
 }
 
+// Inverse of openvr_matrix_to_position and openvr_matrix_to_quat
+fn pose_to_openvr_matrix(position: &[f32; 3], orientation: &[f32; 4]) -> openvr::HmdMatrix34_t {
+    let matrix = utils::pose_to_matrix(position, orientation);
+    let mut result: openvr::HmdMatrix34_t = unsafe { mem::uninitialized() };
+    for row in 0..3 {
+        for column in 0..4 {
+            result.m[row][column] = matrix[column * 4 + row];
+        }
+    }
+    result
+}
+
+fn gl_texture_to_openvr(texture_id: u32) -> openvr::Texture_t {
+    let mut texture: openvr::Texture_t = unsafe { mem::uninitialized() };
+    texture.handle = unsafe { mem::transmute(texture_id as u64) };
+    texture.eColorSpace = openvr::EColorSpace::EColorSpace_ColorSpace_Auto;
+    texture.eType = EGraphicsAPIConvention_API_OpenGL;
+    texture
+}
+
 fn texture_bounds_to_openvr(bounds: &[f32; 4]) -> openvr::VRTextureBounds_t {
     let mut result: openvr::VRTextureBounds_t = unsafe { mem::uninitialized() };
     // WebVR uses uMin, vMin, uWidth and vHeight bounds
@@ -455,3 +676,16 @@ fn texture_bounds_to_openvr(bounds: &[f32; 4]) -> openvr::VRTextureBounds_t {
     result.vMax = result.vMin + bounds[3]; 
     result
 }
+
+// Width and depth of the box enclosing the bounds polygon
+fn bounds_extent(points: &[[f32; 3]]) -> (f32, f32) {
+    let mut min = [::std::f32::MAX; 3];
+    let mut max = [::std::f32::MIN; 3];
+    for point in points {
+        for i in 0..3 {
+            min[i] = min[i].min(point[i]);
+            max[i] = max[i].max(point[i]);
+        }
+    }
+    (max[0] - min[0], max[2] - min[2])
+}
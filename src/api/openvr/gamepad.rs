@@ -1,36 +1,37 @@
 use super::binding as openvr;
-use super::binding::ETrackingUniverseOrigin::*;
 use super::binding::EVRButtonId;
 use super::binding::EVRButtonId::*;
 use super::display::OpenVRDisplay;
+use super::runtime::OpenVRRuntimePtr;
 use super::super::utils;
-use std::cell::RefCell;
 use std::mem;
-use std::sync::Arc;
-use {VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton};
+use std::sync::{Arc, RwLock};
+use {VRGamepad, VRGamepadData, VRGamepadHand, VRGamepadState, VRGamepadButton, VRReferenceSpace};
 
-pub type OpenVRGamepadPtr = Arc<RefCell<OpenVRGamepad>>;
+pub type OpenVRGamepadPtr = Arc<RwLock<OpenVRGamepad>>;
+
+// Pose prediction used by VRGamepad::state
+const DEFAULT_PREDICTION_SECONDS: f32 = 0.04;
 
 pub struct OpenVRGamepad {
     gamepad_id: u32,
     display_id: u32,
     index: openvr::TrackedDeviceIndex_t,
-    system: *mut openvr::VR_IVRSystem_FnTable
+    runtime: OpenVRRuntimePtr,
+    reference_space: VRReferenceSpace
 }
 
-unsafe impl Send for OpenVRGamepad {}
-unsafe impl Sync for OpenVRGamepad {}
-
 impl OpenVRGamepad {
     pub fn new(index: openvr::TrackedDeviceIndex_t,
-               system: *mut openvr::VR_IVRSystem_FnTable,
+               runtime: OpenVRRuntimePtr,
                display_id: u32)
-               -> Arc<RefCell<OpenVRGamepad>> {
-        Arc::new(RefCell::new(OpenVRGamepad {
+               -> Arc<RwLock<OpenVRGamepad>> {
+        Arc::new(RwLock::new(OpenVRGamepad {
             gamepad_id: utils::new_id(),
             display_id: display_id,
             index: index,
-            system: system
+            runtime: runtime,
+            reference_space: VRReferenceSpace::Seated
         }))
     }
 
@@ -53,6 +54,28 @@ impl VRGamepad for OpenVRGamepad {
     }
     
     fn state(&self) -> VRGamepadState {
+        self.fetch_state(DEFAULT_PREDICTION_SECONDS)
+    }
+
+    fn predicted_state(&self, target_time: f64) -> VRGamepadState {
+        let seconds = (target_time - utils::timestamp()) / 1000.0;
+        let mut state = self.fetch_state(seconds as f32);
+        state.timestamp = target_time;
+        state
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+}
+
+impl OpenVRGamepad {
+    // State with the pose predicted by OpenVR for `seconds` from now
+    fn fetch_state(&self, seconds: f32) -> VRGamepadState {
         let mut state = VRGamepadState::default();
 
         state.gamepad_id = self.gamepad_id;
@@ -60,12 +83,13 @@ impl VRGamepad for OpenVRGamepad {
         let mut tracked_poses: [openvr::TrackedDevicePose_t; openvr::k_unMaxTrackedDeviceCount as usize]
                               = unsafe { mem::uninitialized() };
 
+        let runtime = self.runtime.lock().unwrap();
         unsafe {
-            (*self.system).GetControllerState.unwrap()(self.index,
+            (*runtime.system).GetControllerState.unwrap()(self.index,
                                                        &mut controller,
                                                        mem::size_of::<openvr::VRControllerState_t>() as u32);
-            (*self.system).GetDeviceToAbsoluteTrackingPose.unwrap()(ETrackingUniverseOrigin_TrackingUniverseSeated,
-                                                                    0.04f32,
+            (*runtime.system).GetDeviceToAbsoluteTrackingPose.unwrap()(OpenVRDisplay::tracking_universe(self.reference_space),
+                                                                    seconds,
                                                                     &mut tracked_poses[0],
                                                                     openvr::k_unMaxTrackedDeviceCount);
         }
@@ -11,13 +11,16 @@ type VRInitInternal = unsafe extern fn(*mut openvr::EVRInitError, openvr::EVRApp
 type VRShutdownInternal = unsafe extern fn();
 type VRIsHmdPresent = unsafe extern fn() -> bool;
 type VRGetGenericInterface = unsafe extern fn(*const ::std::os::raw::c_char, *mut openvr::EVRInitError) -> isize;
+type VRGetInitErrorDescription = unsafe extern fn(openvr::EVRInitError) -> *const ::std::os::raw::c_char;
 
 pub struct OpenVRLibrary {
     _lib: lib::Library,
     pub init_internal: Symbol<VRInitInternal>,
     pub shutdown_internal: Symbol<VRShutdownInternal>,
     pub is_hmd_present: Symbol<VRIsHmdPresent>,
-    pub get_interface: Symbol<VRGetGenericInterface>
+    pub get_interface: Symbol<VRGetGenericInterface>,
+    // Not available in old runtimes
+    pub get_init_error_description: Option<Symbol<VRGetInitErrorDescription>>
 }
 
 impl OpenVRLibrary {
@@ -27,13 +30,16 @@ impl OpenVRLibrary {
         let shutdown_internal = try!(lib.get::<VRShutdownInternal>(b"VR_ShutdownInternal\0")).into_raw();
         let is_hmd_present = try!(lib.get::<VRIsHmdPresent>(b"VR_IsHmdPresent\0")).into_raw();
         let get_interface = try!(lib.get::<VRGetGenericInterface>(b"VR_GetGenericInterface\0")).into_raw();
+        let get_init_error_description = lib.get::<VRGetInitErrorDescription>(b"VR_GetVRInitErrorAsEnglishDescription\0")
+                                            .ok().map(|symbol| symbol.into_raw());
         
         Ok(OpenVRLibrary {
             _lib: lib,
             init_internal: init_internal,
             shutdown_internal: shutdown_internal,
             is_hmd_present: is_hmd_present,
-            get_interface: get_interface
+            get_interface: get_interface,
+            get_init_error_description: get_init_error_description
         })
     }
 }
\ No newline at end of file
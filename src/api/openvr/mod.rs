@@ -3,7 +3,9 @@ mod constants;
 mod display;
 mod library;
 mod gamepad;
+mod runtime;
 mod service;
+mod submit;
 
 use {VRService, VRServiceCreator};
 
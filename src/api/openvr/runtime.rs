@@ -0,0 +1,45 @@
+use super::binding as openvr;
+use super::library::OpenVRLibrary;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+pub type OpenVRRuntimePtr = Arc<Mutex<OpenVRRuntime>>;
+
+// Interfaces of the initialized OpenVR runtime, shared by the service, its displays and gamepads.
+// OpenVR doesn't guarantee that its interfaces can be called concurrently, so every call
+// is made while holding the lock of the OpenVRRuntimePtr. The only exception is the
+// compositor WaitGetPoses call, which blocks until vsync and is only made by the render thread.
+// The lock must not be held while calling methods of other displays or gamepads.
+pub struct OpenVRRuntime {
+    pub lib: Arc<OpenVRLibrary>,
+    pub system: *mut openvr::VR_IVRSystem_FnTable,
+    pub chaperone: *mut openvr::VR_IVRChaperone_FnTable,
+    // Used to read the collision bounds when the play area is not set up. Null if unavailable
+    pub chaperone_setup: *mut openvr::VR_IVRChaperoneSetup_FnTable,
+    // Lazily loaded when a display starts presenting
+    pub compositor: *mut openvr::VR_IVRCompositor_FnTable,
+    // Cleared when the compositor rejects the render pose or the depth of a submitted texture
+    pub extended_submit: bool
+}
+
+// The function tables are owned by the runtime loaded in the process, not by the thread
+// that requested them, so they can be moved to other threads. Sync is provided by the Mutex
+// of OpenVRRuntimePtr, which serializes the calls.
+unsafe impl Send for OpenVRRuntime {}
+
+impl OpenVRRuntime {
+    pub fn new(lib: Arc<OpenVRLibrary>,
+               system: *mut openvr::VR_IVRSystem_FnTable,
+               chaperone: *mut openvr::VR_IVRChaperone_FnTable,
+               chaperone_setup: *mut openvr::VR_IVRChaperoneSetup_FnTable)
+               -> OpenVRRuntimePtr {
+        Arc::new(Mutex::new(OpenVRRuntime {
+            lib: lib,
+            system: system,
+            chaperone: chaperone,
+            chaperone_setup: chaperone_setup,
+            compositor: ptr::null_mut(),
+            extended_submit: true
+        }))
+    }
+}
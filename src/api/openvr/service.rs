@@ -7,40 +7,41 @@ use super::constants;
 use super::display::{OpenVRDisplay, OpenVRDisplayPtr};
 use super::gamepad::{OpenVRGamepad, OpenVRGamepadPtr};
 use super::library::OpenVRLibrary;
-use std::ffi::CString;
-use std::ptr;
+use super::runtime::{OpenVRRuntime, OpenVRRuntimePtr};
+use std::ffi::{CStr, CString};
 use std::mem;
-use {VRService, VRDisplay, VRDisplayPtr, VREvent, VRDisplayEvent, VRDisplayEventReason,
+use std::ptr;
+use std::sync::Arc;
+use {VRService, VRDisplay, VRDisplayPtr, VRError, VREvent, VRDisplayEvent, VRDisplayEventReason,
     VRGamepadEvent, VRGamepad, VRGamepadPtr};
 
 // OpenVR Service implementation
 pub struct OpenVRService {
     initialized: bool,
-    lib: Option<OpenVRLibrary>,
+    lib: Option<Arc<OpenVRLibrary>>,
     displays: Vec<OpenVRDisplayPtr>,
     gamepads: Vec<OpenVRGamepadPtr>,
-    system: *mut openvr::VR_IVRSystem_FnTable,
-    chaperone: *mut openvr::VR_IVRChaperone_FnTable,
+    // Available once initialized
+    runtime: Option<OpenVRRuntimePtr>
 }
 
-unsafe impl Send for OpenVRService {}
-
 impl VRService for OpenVRService {
-    fn initialize(&mut self) -> Result<(), String> { 
+    fn initialize(&mut self) -> Result<(), VRError> { 
         if self.initialized {
             return Ok(());
         }
 
         // Load OpenVR library
         match unsafe { OpenVRLibrary::new() } {
-            Ok(lib) => self.lib = Some(lib),
+            Ok(lib) => self.lib = Some(Arc::new(lib)),
             Err(msg) => {
-                return Err(format!("Error loading OpenVR dll: {:?}", msg));
+                warn!("Error loading OpenVR dll: {:?}", msg);
+                return Err(VRError::LibraryNotFound);
             }
         };
 
         if !self.is_available() {
-            return Err("Not available".into());
+            return Err(VRError::NoHmd);
         }
 
         // Initialize OpenVR
@@ -50,38 +51,51 @@ impl VRService for OpenVRService {
         }
 
         if error as u32 != EVRInitError_VRInitError_None as u32 {
-            return Err(format!("OpenVR Internal failed with error {}", error as u32));
+            return Err(self.init_error(error));
         }
 
         // Initialize System
         error = EVRInitError_VRInitError_None;
-        unsafe {
+        let system = unsafe {
             let name = CString::new(format!("FnTable:{}", constants::IVRSystem_Version)).unwrap();
-            self.system = (*self.lib.as_ref().unwrap().get_interface)(name.as_ptr(), &mut error)
-                          as *mut openvr::VR_IVRSystem_FnTable;
-        }
+            (*self.lib.as_ref().unwrap().get_interface)(name.as_ptr(), &mut error)
+            as *mut openvr::VR_IVRSystem_FnTable
+        };
 
         if error as u32 != EVRInitError_VRInitError_None as u32 {
-            return Err(format!("OpenVR GetGenericInterface failed with error {}", error as u32));
+            return Err(self.init_error(error));
         }
 
         // Initialize Chaperone
         error = EVRInitError_VRInitError_None;
-        unsafe {
+        let chaperone = unsafe {
             let name = CString::new(format!("FnTable:{}", constants::IVRChaperone_Version)).unwrap();
-            self.chaperone = (*self.lib.as_ref().unwrap().get_interface)(name.as_ptr(), &mut error)
-                             as *mut openvr::VR_IVRChaperone_FnTable;
-        }
+            (*self.lib.as_ref().unwrap().get_interface)(name.as_ptr(), &mut error)
+            as *mut openvr::VR_IVRChaperone_FnTable
+        };
           
         if error as u32 != EVRInitError_VRInitError_None as u32 {
-            return Err(format!("OpenVR GetGenericInterface failed with error {:?}", error));
+            return Err(self.init_error(error));
+        }
+
+        // Initialize ChaperoneSetup. It's optional, only used for the collision bounds
+        error = EVRInitError_VRInitError_None;
+        let mut chaperone_setup = unsafe {
+            let name = CString::new(format!("FnTable:{}", constants::IVRChaperoneSetup_Version)).unwrap();
+            (*self.lib.as_ref().unwrap().get_interface)(name.as_ptr(), &mut error)
+            as *mut openvr::VR_IVRChaperoneSetup_FnTable
+        };
+
+        if error as u32 != EVRInitError_VRInitError_None as u32 {
+            chaperone_setup = ptr::null_mut();
         }
 
+        self.runtime = Some(OpenVRRuntime::new(self.lib.as_ref().unwrap().clone(), system, chaperone, chaperone_setup));
         self.initialized = true;
         Ok(())
     }
 
-    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>,String> {
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>,VRError> {
         // Return cached displays if available
         if self.initialized && self.displays.len() > 0 {
             return Ok(self.clone_displays());
@@ -93,13 +107,10 @@ impl VRService for OpenVRService {
         self.displays.clear();
 
         for i in 0..max_device_count {
-            let device_class: openvr::ETrackedDeviceClass = unsafe {
-                (*self.system).GetTrackedDeviceClass.unwrap()(i as openvr::TrackedDeviceIndex_t)
-            };
-            
-            match device_class {
+            match self.device_class(i) {
                 ETrackedDeviceClass_TrackedDeviceClass_HMD => {
-                    self.displays.push(OpenVRDisplay::new(self.lib.as_ref().unwrap(), i, self.system, self.chaperone));
+                    let runtime = self.runtime.as_ref().unwrap().clone();
+                    self.displays.push(OpenVRDisplay::new(i, runtime));
                 },
                 _ => {}
             }
@@ -109,7 +120,7 @@ impl VRService for OpenVRService {
         Ok(self.clone_displays())
     }
 
-    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>,String> {
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>,VRError> {
         // Return cached gamepads if available
         if self.initialized && self.gamepads.len() > 0 {
             return Ok(self.clone_gamepads());
@@ -120,18 +131,15 @@ impl VRService for OpenVRService {
         self.gamepads.clear();
 
         let display_id = match self.displays.first() {
-            Some(display) => display.borrow().id(),
+            Some(display) => display.read().unwrap().id(),
             None => 0
         };
 
         for i in 0..max_device_count {
-            let device_class: openvr::ETrackedDeviceClass = unsafe {
-                (*self.system).GetTrackedDeviceClass.unwrap()(i as openvr::TrackedDeviceIndex_t)
-            };
-
-            match device_class {
+            match self.device_class(i) {
                 ETrackedDeviceClass_TrackedDeviceClass_Controller => {
-                    self.gamepads.push(OpenVRGamepad::new(i, self.system, display_id));
+                    let runtime = self.runtime.as_ref().unwrap().clone();
+                    self.gamepads.push(OpenVRGamepad::new(i, runtime, display_id));
                 },
                 _ => {}
             }
@@ -151,62 +159,64 @@ impl VRService for OpenVRService {
 
     fn poll_events(&self) -> Vec<VREvent> {
         let mut result = Vec::new();
-        if !self.initialized || self.system.is_null() {
-            return result;
-        }
+        let runtime = match self.runtime {
+            Some(ref runtime) => runtime,
+            None => return result
+        };
         let mut event: openvr::VREvent_t = unsafe { mem::uninitialized() };
         let size = mem::size_of::<openvr::VREvent_t>() as u32;
-        while unsafe { (*self.system).PollNextEvent.unwrap()(&mut event, size) } {
+        // The runtime is unlocked while the event is handled, displays and gamepads lock it too
+        while unsafe { (*runtime.lock().unwrap().system).PollNextEvent.unwrap()(&mut event, size) } {
 
             let event_type: openvr::EVREventType = unsafe { mem::transmute(event.eventType) };
 
             match event_type {
                 EVREventType_VREvent_TrackedDeviceUserInteractionStarted => {
                     if let Some(display) = self.get_display(event.trackedDeviceIndex) {
-                        result.push(VRDisplayEvent::Activate(display.borrow().data(), 
+                        result.push(VRDisplayEvent::Activate(display.read().unwrap().data(), 
                                                              VRDisplayEventReason::Mounted)
                                                              .into());
                     }
                 },
                 EVREventType_VREvent_TrackedDeviceUserInteractionEnded => {
                     if let Some(display) = self.get_display(event.trackedDeviceIndex) {
-                        result.push(VRDisplayEvent::Deactivate(display.borrow().data(), 
+                        result.push(VRDisplayEvent::Deactivate(display.read().unwrap().data(), 
                                                                VRDisplayEventReason::Unmounted)
                                                                .into());
                     }
                 },
                 EVREventType_VREvent_TrackedDeviceActivated => {
                     if let Some(display) = self.get_display(event.trackedDeviceIndex) {
-                        result.push(VRDisplayEvent::Connect(display.borrow().data()).into())
+                        result.push(VRDisplayEvent::Connect(display.read().unwrap().data()).into())
                     }
                     else if let Some(gamepad) = self.get_gamepad(event.trackedDeviceIndex) {
-                        let g = gamepad.borrow();
+                        let g = gamepad.read().unwrap();
                         result.push(VRGamepadEvent::Connect(g.data(), g.state()).into());
                     }
                 },
                 EVREventType_VREvent_TrackedDeviceDeactivated => {
                     if let Some(display) = self.get_display(event.trackedDeviceIndex) {
-                        result.push(VRDisplayEvent::Disconnect(display.borrow().id()).into())
+                        result.push(VRDisplayEvent::Disconnect(display.read().unwrap().id()).into())
                     }
                     else if let Some(gamepad) = self.get_gamepad(event.trackedDeviceIndex) {
-                        result.push(VRGamepadEvent::Disconnect(gamepad.borrow().id()).into());
+                        result.push(VRGamepadEvent::Disconnect(gamepad.read().unwrap().id()).into());
                     }
                 },
                 EVREventType_VREvent_DashboardActivated => {
                     if let Some(display) = self.get_display(event.trackedDeviceIndex) {
-                        result.push(VRDisplayEvent::Blur(display.borrow().data()).into())
+                        result.push(VRDisplayEvent::Blur(display.read().unwrap().data()).into())
                     }
                 },
                 EVREventType_VREvent_DashboardDeactivated => {
                     if let Some(display) = self.get_display(event.trackedDeviceIndex) {
-                        result.push(VRDisplayEvent::Focus(display.borrow().data()).into())
+                        result.push(VRDisplayEvent::Focus(display.read().unwrap().data()).into())
                     }
                 },
                 EVREventType_VREvent_ChaperoneDataHasChanged |
                 EVREventType_VREvent_IpdChanged |
                 EVREventType_VREvent_TrackedDeviceUpdated => {
                     if let Some(display) = self.get_display(event.trackedDeviceIndex) {
-                        result.push(VRDisplayEvent::Change(display.borrow().data()).into())
+                        result.push(VRDisplayEvent::Change(display.read().unwrap().data()).into())
                     }
                 },
                 _ => {}
@@ -237,8 +247,14 @@ impl OpenVRService {
             lib: None,
             displays: Vec::new(),
             gamepads: Vec::new(),
-            system: ptr::null_mut(),
-            chaperone: ptr::null_mut()
+            runtime: None
+        }
+    }
+
+    fn device_class(&self, index: openvr::TrackedDeviceIndex_t) -> openvr::ETrackedDeviceClass {
+        let runtime = self.runtime.as_ref().unwrap().lock().unwrap();
+        unsafe {
+            (*runtime.system).GetTrackedDeviceClass.unwrap()(index)
         }
     }
 
@@ -251,10 +267,39 @@ impl OpenVRService {
     }
 
     pub fn get_display(&self, index: openvr::TrackedDeviceIndex_t) -> Option<&OpenVRDisplayPtr> {
-        self.displays.iter().find(|&d| d.borrow().index() == index)
+        self.displays.iter().find(|&d| d.read().unwrap().index() == index)
     }
 
     pub fn get_gamepad(&self, index: openvr::TrackedDeviceIndex_t) -> Option<&OpenVRGamepadPtr> {
-        self.gamepads.iter().find(|&d| d.borrow().index() == index)
+        self.gamepads.iter().find(|&d| d.read().unwrap().index() == index)
+    }
+
+    // Converts an EVRInitError to the equivalent VRError
+    fn init_error(&self, error: openvr::EVRInitError) -> VRError {
+        match error {
+            EVRInitError_VRInitError_Init_InstallationNotFound |
+            EVRInitError_VRInitError_Init_InstallationCorrupt |
+            EVRInitError_VRInitError_Init_VRClientDLLNotFound => VRError::RuntimeNotInstalled,
+            EVRInitError_VRInitError_Init_HmdNotFound |
+            EVRInitError_VRInitError_Init_HmdNotFoundPresenceFailed => VRError::NoHmd,
+            EVRInitError_VRInitError_Init_InterfaceNotFound |
+            EVRInitError_VRInitError_Init_InvalidInterface => VRError::InterfaceVersionMismatch,
+            EVRInitError_VRInitError_Init_NotInitialized => VRError::NotInitialized,
+            _ => {
+                let get_description = self.lib.as_ref().and_then(|lib| lib.get_init_error_description.as_ref());
+                let description = match get_description {
+                    Some(get_description) => unsafe {
+                        let description = (**get_description)(error);
+                        if description.is_null() {
+                            None
+                        } else {
+                            Some(CStr::from_ptr(description).to_string_lossy().into_owned())
+                        }
+                    },
+                    None => None
+                };
+                VRError::InitFailed(error as i32, description.unwrap_or_else(|| format!("{:?}", error)))
+            }
+        }
     }
 }
\ No newline at end of file
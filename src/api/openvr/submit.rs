@@ -0,0 +1,57 @@
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+#![allow(non_upper_case_globals)]
+// Submit flags and texture types added to openvr_capi.h after the IVRCompositor_018 interface
+// generated in binding.rs. Runtimes that don't support them reject the submit, so they are only
+// used while the compositor accepts them (see OpenVRDisplay::submit_eye).
+
+use super::binding::{EVRCompositorError, EVREye, HmdMatrix34_t, HmdMatrix44_t, HmdVector2_t, Texture_t,
+                     VRTextureBounds_t, VR_IVRCompositor_FnTable};
+use std::mem;
+
+pub const EVRSubmitFlags_Submit_Default: i32 = 0;
+pub const EVRSubmitFlags_Submit_TextureWithPose: i32 = 8;
+pub const EVRSubmitFlags_Submit_TextureWithDepth: i32 = 16;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct VRTextureWithPose_t {
+    pub texture: Texture_t,
+    pub mDeviceToAbsoluteTracking: HmdMatrix34_t,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct VRTextureDepthInfo_t {
+    pub handle: *mut ::std::os::raw::c_void,
+    pub mProjection: HmdMatrix44_t,
+    pub vRange: HmdVector2_t,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct VRTextureWithDepth_t {
+    pub texture: Texture_t,
+    pub depth: VRTextureDepthInfo_t,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct VRTextureWithPoseAndDepth_t {
+    pub texture: Texture_t,
+    pub mDeviceToAbsoluteTracking: HmdMatrix34_t,
+    pub depth: VRTextureDepthInfo_t,
+}
+
+// IVRCompositor::Submit taking the flags as a plain integer.
+// The EVRSubmitFlags enum of binding.rs can't hold the flags above.
+pub type SubmitFn = unsafe extern "C" fn(eEye: EVREye,
+                                         pTexture: *mut Texture_t,
+                                         pBounds: *mut VRTextureBounds_t,
+                                         nSubmitFlags: i32)
+                                         -> EVRCompositorError;
+
+pub unsafe fn submit_fn(compositor: *mut VR_IVRCompositor_FnTable) -> SubmitFn {
+    // Same signature: EVRSubmitFlags is a repr(i32) enum
+    mem::transmute((*compositor).Submit.unwrap())
+}
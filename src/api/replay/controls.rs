@@ -0,0 +1,191 @@
+use VRError;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Playback controls shared by the displays of a replay service.
+// The handle can be cloned and used from any thread.
+#[derive(Clone)]
+pub struct ReplayControls {
+    state: Arc<Mutex<PlaybackState>>
+}
+
+struct PlaybackState {
+    // Recording duration in nanoseconds
+    duration: f64,
+    speed: f64,
+    looping: bool,
+    as_fast_as_possible: bool,
+    // Playback time in nanoseconds at base_instant. It keeps growing when looping.
+    base_elapsed: f64,
+    base_instant: Instant,
+    // Incremented on every seek
+    seek_count: u64,
+    // Playback time set by the last seek
+    seek_elapsed: f64
+}
+
+impl PlaybackState {
+    fn elapsed(&self) -> f64 {
+        if self.as_fast_as_possible {
+            return self.base_elapsed;
+        }
+        let wall = self.base_instant.elapsed();
+        let wall = wall.as_secs() as f64 * 1e9 + wall.subsec_nanos() as f64;
+        self.base_elapsed + wall * self.speed
+    }
+
+    // Restarts the wall clock reference, keeping the current playback time
+    fn rebase(&mut self) {
+        self.base_elapsed = self.elapsed();
+        self.base_instant = Instant::now();
+    }
+}
+
+impl ReplayControls {
+    pub fn new() -> ReplayControls {
+        ReplayControls {
+            state: Arc::new(Mutex::new(PlaybackState {
+                duration: 0.0,
+                speed: 1.0,
+                looping: false,
+                as_fast_as_possible: false,
+                base_elapsed: 0.0,
+                base_instant: Instant::now(),
+                seek_count: 0,
+                seek_elapsed: 0.0
+            }))
+        }
+    }
+
+    // Duration of the recording in seconds
+    pub fn duration(&self) -> f64 {
+        self.state.lock().unwrap().duration * 1e-9
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.state.lock().unwrap().speed
+    }
+
+    // Sets the playback speed. 1.0 reproduces the original timing.
+    // Fails if the speed is not a finite positive number.
+    pub fn set_speed(&self, speed: f64) -> Result<(), VRError> {
+        if !(speed > 0.0 && speed.is_finite()) {
+            return Err(VRError::Backend("Replay".into(), format!("Invalid playback speed {}", speed)));
+        }
+        let mut state = self.state.lock().unwrap();
+        state.rebase();
+        state.speed = speed;
+        Ok(())
+    }
+
+    pub fn looping(&self) -> bool {
+        self.state.lock().unwrap().looping
+    }
+
+    // Restart from the beginning when the end of the recording is reached
+    pub fn set_looping(&self, looping: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.rebase();
+        if !looping && state.duration > 0.0 {
+            let position = state.base_elapsed % state.duration;
+            state.base_elapsed = position;
+            state.seek_count += 1;
+            state.seek_elapsed = position;
+        }
+        state.looping = looping;
+    }
+
+    pub fn as_fast_as_possible(&self) -> bool {
+        self.state.lock().unwrap().as_fast_as_possible
+    }
+
+    // When enabled the playback time doesn't follow the wall clock.
+    // Instead, each sync_poses call jumps directly to the next recorded sync_poses call,
+    // so tests run deterministically and without waiting.
+    pub fn set_as_fast_as_possible(&self, enabled: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.rebase();
+        state.as_fast_as_possible = enabled;
+    }
+
+    // Jumps to a position of the recording in seconds
+    pub fn seek(&self, seconds: f64) {
+        let mut state = self.state.lock().unwrap();
+        state.base_elapsed = (seconds * 1e9).max(0.0).min(state.duration);
+        state.base_instant = Instant::now();
+        state.seek_count += 1;
+        state.seek_elapsed = state.base_elapsed;
+    }
+
+    // Current position in the recording in seconds
+    pub fn position(&self) -> f64 {
+        let state = self.state.lock().unwrap();
+        recording_time(&state, state.elapsed()) * 1e-9
+    }
+
+    // True when the end of a non looping recording has been reached
+    pub fn is_finished(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        !state.looping && state.elapsed() >= state.duration
+    }
+
+    // Sets the recording duration in nanoseconds. Called by the service when the recording is loaded.
+    pub fn set_duration(&self, duration: u64) {
+        self.state.lock().unwrap().duration = duration as f64;
+    }
+
+    // Playback time in nanoseconds. It keeps growing after the end of the recording.
+    pub fn elapsed(&self) -> f64 {
+        self.state.lock().unwrap().elapsed()
+    }
+
+    // Returns the number of seeks and the playback time set by the last one
+    pub fn last_seek(&self) -> (u64, f64) {
+        let state = self.state.lock().unwrap();
+        (state.seek_count, state.seek_elapsed)
+    }
+
+    // Converts a playback time to a time in the recording
+    pub fn to_recording_time(&self, elapsed: f64) -> u64 {
+        let state = self.state.lock().unwrap();
+        recording_time(&state, elapsed) as u64
+    }
+
+    // Returns the loop duration in nanoseconds and the index of the loop reproduced at `elapsed`.
+    // The duration is 0 when the recording is not looping.
+    pub fn loop_count(&self, elapsed: f64) -> (f64, u64) {
+        let state = self.state.lock().unwrap();
+        if state.looping && state.duration > 0.0 {
+            (state.duration, (elapsed / state.duration).floor() as u64)
+        } else {
+            (0.0, 0)
+        }
+    }
+
+    // Advances the playback to `elapsed` nanoseconds.
+    // Blocks the calling thread unless the playback runs as fast as possible.
+    pub fn wait_until(&self, elapsed: f64) {
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            if state.as_fast_as_possible {
+                if elapsed > state.base_elapsed {
+                    state.base_elapsed = elapsed;
+                }
+                return;
+            }
+            (elapsed - state.elapsed()) / state.speed
+        };
+        if wait > 0.0 {
+            thread::sleep(Duration::new((wait / 1e9) as u64, (wait % 1e9) as u32));
+        }
+    }
+}
+
+fn recording_time(state: &PlaybackState, elapsed: f64) -> f64 {
+    if state.looping && state.duration > 0.0 {
+        elapsed % state.duration
+    } else {
+        elapsed.min(state.duration)
+    }
+}
@@ -0,0 +1,206 @@
+use {VRDisplay, VRDisplayData, VRFrameData, VRFrameStats, VRLayer, VRReferenceSpace};
+use recording::{Recording, RecordedEvent};
+use super::controls::ReplayControls;
+use super::timeline::DeviceTimeline;
+use super::super::utils;
+use std::sync::{Arc, Mutex, RwLock};
+
+pub type ReplayDisplayPtr = Arc<RwLock<ReplayDisplay>>;
+
+// Display that reproduces the data recorded by a RecordingDisplay
+pub struct ReplayDisplay {
+    display_id: u32,
+    timeline: DeviceTimeline,
+    controls: ReplayControls,
+    // Timeline index of the frame data recorded after the sync_poses call
+    // reproduced in the last sync_poses call
+    synced_frame: Mutex<Option<usize>>,
+    reference_space: VRReferenceSpace
+}
+
+impl ReplayDisplay {
+    pub fn new(recording: Arc<Recording>, recorded_id: u32, controls: ReplayControls) -> ReplayDisplayPtr {
+        let timeline = DeviceTimeline::new(recording, |entry| {
+            entry.event.is_display_event() && entry.device_id == recorded_id
+        });
+
+        Arc::new(RwLock::new(ReplayDisplay {
+            display_id: utils::new_id(),
+            timeline: timeline,
+            controls: controls,
+            synced_frame: Mutex::new(None),
+            reference_space: VRReferenceSpace::Seated
+        }))
+    }
+
+    pub fn controls(&self) -> ReplayControls {
+        self.controls.clone()
+    }
+
+    fn frame_data_at(&self, time: u64) -> Option<VRFrameData> {
+        self.timeline.latest_index(time, |event| match *event {
+            RecordedEvent::InmediateFrameData { .. } | RecordedEvent::SyncedFrameData { .. } => true,
+            _ => false
+        }).map(|index| self.recorded_frame_data(index))
+    }
+
+    // Frame data of the timeline entry `index` converted to the selected reference space.
+    // Poses recorded in the raw space can't be converted.
+    fn recorded_frame_data(&self, index: usize) -> VRFrameData {
+        let mut data = match self.timeline.entry(index).event {
+            RecordedEvent::InmediateFrameData { ref data, .. } |
+            RecordedEvent::SyncedFrameData { ref data, .. } => data.clone(),
+            _ => unreachable!()
+        };
+        let recorded = self.timeline.reference_space_before(index);
+        if let Some(transform) = utils::reference_space_transform(recorded,
+                                                                   self.reference_space,
+                                                                   self.data().stage_parameters.as_ref()) {
+            utils::transform_frame_data(&transform, &mut data);
+        }
+        data
+    }
+
+    // Finds the next recorded sync_poses call reproduced after the playback time `elapsed`.
+    // Returns its playback time and the index of the entry.
+    fn next_sync(&self, elapsed: f64) -> Option<(f64, usize)> {
+        let (loop_duration, loop_index) = self.controls.loop_count(elapsed);
+        // Looping recordings may find it in the next loop
+        let loops = if loop_duration > 0.0 { 2 } else { 1 };
+        for l in loop_index..loop_index + loops {
+            let offset = l as f64 * loop_duration;
+            for i in self.timeline.first_after(offset, elapsed)..self.timeline.len() {
+                let entry = self.timeline.entry(i);
+                if let RecordedEvent::SyncPoses = entry.event {
+                    return Some((offset + entry.time as f64, i));
+                }
+            }
+        }
+        None
+    }
+
+    // Frame data recorded by the synced_frame_data call that followed a sync_poses call
+    fn synced_frame_after(&self, sync_index: usize) -> Option<usize> {
+        for i in sync_index + 1..self.timeline.len() {
+            match self.timeline.entry(i).event {
+                RecordedEvent::SyncPoses => break,
+                RecordedEvent::SyncedFrameData { .. } => return Some(i),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    // Recorded frame data are reused with the requested depth values
+    fn update_projection(&self, data: &mut VRFrameData, near: f64, far: f64) {
+        let display = self.data();
+        data.left_projection_matrix = utils::fov_to_projection_matrix(&display.left_eye_parameters.field_of_view,
+                                                                      near as f32, far as f32);
+        data.right_projection_matrix = utils::fov_to_projection_matrix(&display.right_eye_parameters.field_of_view,
+                                                                       near as f32, far as f32);
+    }
+}
+
+impl VRDisplay for ReplayDisplay {
+    fn id(&self) -> u32 {
+        self.display_id
+    }
+
+    fn data(&self) -> VRDisplayData {
+        let time = self.controls.to_recording_time(self.controls.elapsed());
+        let mut data = self.timeline.latest(time, |event| match *event {
+            RecordedEvent::DisplayData(_) => true,
+            _ => false
+        }).map(|entry| match entry.event {
+            RecordedEvent::DisplayData(ref data) => data.clone(),
+            _ => unreachable!()
+        }).unwrap_or_default();
+        data.display_id = self.display_id;
+        data
+    }
+
+    fn inmediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let time = self.controls.to_recording_time(self.controls.elapsed());
+        let mut data = self.frame_data_at(time).unwrap_or_default();
+        self.update_projection(&mut data, near_z, far_z);
+        data
+    }
+
+    fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let synced_frame = *self.synced_frame.lock().unwrap();
+        let mut data = match synced_frame {
+            Some(index) => self.recorded_frame_data(index),
+            None => {
+                let time = self.controls.to_recording_time(self.controls.elapsed());
+                self.frame_data_at(time).unwrap_or_default()
+            }
+        };
+        self.update_projection(&mut data, near_z, far_z);
+        data
+    }
+
+    fn predicted_frame_data(&self, near_z: f64, far_z: f64, target_time: f64) -> VRFrameData {
+        // The latest recorded frame data is extrapolated to the target time
+        let time = self.controls.to_recording_time(self.controls.elapsed());
+        let latest = self.frame_data_at(time).unwrap_or_default();
+        let dt = (target_time - latest.timestamp) / 1000.0;
+        let mut data = utils::extrapolate_frame_data(&latest, &self.data(), dt as f32);
+        data.timestamp = target_time;
+        self.update_projection(&mut data, near_z, far_z);
+        data
+    }
+
+    fn reset_pose(&mut self) {
+        // The recorded poses already include the effects of the original reset_pose calls
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+
+    fn sync_poses(&self) {
+        // Reproduce the timing of the recorded sync_poses calls
+        match self.next_sync(self.controls.elapsed()) {
+            Some((time, index)) => {
+                if self.controls.as_fast_as_possible() {
+                    // Reproduce everything recorded until the next frame starts,
+                    // so events recorded during the frame are polled in the same frame.
+                    let end = match self.next_sync(time) {
+                        Some((next, _)) => next - 1.0,
+                        None => self.controls.duration() * 1e9
+                    };
+                    self.controls.wait_until(end.max(time));
+                } else {
+                    self.controls.wait_until(time);
+                }
+                *self.synced_frame.lock().unwrap() = self.synced_frame_after(index);
+            },
+            None => {
+                // End of the recording: keep the last frame
+            }
+        }
+    }
+
+    fn frame_stats(&self) -> VRFrameStats {
+        // Statistics measured by the recorded display
+        let time = self.controls.to_recording_time(self.controls.elapsed());
+        self.timeline.latest(time, |event| match *event {
+            RecordedEvent::FrameStats(_) => true,
+            _ => false
+        }).map(|entry| match entry.event {
+            RecordedEvent::FrameStats(ref stats) => stats.clone(),
+            _ => unreachable!()
+        }).unwrap_or_default()
+    }
+
+    fn submit_frame(&mut self, _layer: &VRLayer) {
+    }
+
+    fn submit_layers(&mut self, _layers: &[VRLayer]) {
+    }
+}
+
@@ -0,0 +1,126 @@
+use {VRGamepad, VRGamepadData, VRGamepadState, VRReferenceSpace, VRStageParameters};
+use recording::{Recording, RecordedEvent};
+use super::controls::ReplayControls;
+use super::timeline::DeviceTimeline;
+use super::super::utils;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+pub type ReplayGamepadPtr = Arc<RwLock<ReplayGamepad>>;
+
+// Gamepad that reproduces the states recorded by a RecordingGamepad
+pub struct ReplayGamepad {
+    gamepad_id: u32,
+    timeline: DeviceTimeline,
+    controls: ReplayControls,
+    // Maps recorded display ids to the ids of the replay displays
+    display_ids: HashMap<u32, u32>,
+    // Display data of the recorded display linked to the gamepad
+    display_timeline: DeviceTimeline,
+    reference_space: VRReferenceSpace
+}
+
+impl ReplayGamepad {
+    pub fn new(recording: Arc<Recording>,
+               recorded_id: u32,
+               controls: ReplayControls,
+               display_ids: HashMap<u32, u32>)
+               -> ReplayGamepadPtr {
+        let recorded_display_id = recording.entries.iter().filter(|entry| entry.device_id == recorded_id)
+                                                          .filter_map(|entry| match entry.event {
+            RecordedEvent::GamepadData(ref data) => Some(data.display_id),
+            _ => None
+        }).next();
+        let display_timeline = DeviceTimeline::new(recording.clone(), |entry| match entry.event {
+            RecordedEvent::DisplayData(_) => Some(entry.device_id) == recorded_display_id,
+            _ => false
+        });
+        let timeline = DeviceTimeline::new(recording, |entry| {
+            entry.event.is_gamepad_event() && entry.device_id == recorded_id
+        });
+
+        Arc::new(RwLock::new(ReplayGamepad {
+            gamepad_id: utils::new_id(),
+            timeline: timeline,
+            controls: controls,
+            display_ids: display_ids,
+            display_timeline: display_timeline,
+            reference_space: VRReferenceSpace::Seated
+        }))
+    }
+
+    pub fn controls(&self) -> ReplayControls {
+        self.controls.clone()
+    }
+
+    // State of the timeline entry `index` converted to the selected reference space.
+    // Poses recorded in the raw space can't be converted.
+    fn recorded_state(&self, index: usize, time: u64) -> VRGamepadState {
+        let mut state = match self.timeline.entry(index).event {
+            RecordedEvent::GamepadState(ref state) => state.clone(),
+            _ => unreachable!()
+        };
+        let recorded = self.timeline.reference_space_before(index);
+        if let Some(transform) = utils::reference_space_transform(recorded,
+                                                                   self.reference_space,
+                                                                   self.stage_parameters(time).as_ref()) {
+            state.pose = utils::transform_pose(&transform, &state.pose);
+        }
+        state
+    }
+
+    fn stage_parameters(&self, time: u64) -> Option<VRStageParameters> {
+        self.display_timeline.latest(time, |_| true).and_then(|entry| match entry.event {
+            RecordedEvent::DisplayData(ref data) => data.stage_parameters.clone(),
+            _ => None
+        })
+    }
+}
+
+impl VRGamepad for ReplayGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad_id
+    }
+
+    fn data(&self) -> VRGamepadData {
+        let time = self.controls.to_recording_time(self.controls.elapsed());
+        let mut data = self.timeline.latest(time, |event| match *event {
+            RecordedEvent::GamepadData(_) => true,
+            _ => false
+        }).map(|entry| match entry.event {
+            RecordedEvent::GamepadData(ref data) => data.clone(),
+            _ => unreachable!()
+        }).unwrap_or_default();
+        if let Some(id) = self.display_ids.get(&data.display_id) {
+            data.display_id = *id;
+        }
+        data
+    }
+
+    fn state(&self) -> VRGamepadState {
+        let time = self.controls.to_recording_time(self.controls.elapsed());
+        let mut state = self.timeline.latest_index(time, |event| match *event {
+            RecordedEvent::GamepadState(_) => true,
+            _ => false
+        }).map(|index| self.recorded_state(index, time)).unwrap_or_default();
+        state.gamepad_id = self.gamepad_id;
+        state
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = space;
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.reference_space
+    }
+
+    fn predicted_state(&self, target_time: f64) -> VRGamepadState {
+        // The latest recorded state is extrapolated to the target time
+        let mut state = self.state();
+        let dt = (target_time - state.timestamp) / 1000.0;
+        state.pose = utils::extrapolate_pose(&state.pose, dt as f32);
+        state.timestamp = target_time;
+        state
+    }
+}
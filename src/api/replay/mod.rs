@@ -0,0 +1,32 @@
+mod controls;
+mod display;
+mod gamepad;
+mod service;
+mod timeline;
+
+use {VRService, VRServiceCreator};
+use std::path::{Path, PathBuf};
+
+pub use self::controls::ReplayControls;
+pub use self::display::{ReplayDisplay, ReplayDisplayPtr};
+pub use self::gamepad::{ReplayGamepad, ReplayGamepadPtr};
+pub use self::service::ReplayVRService;
+
+pub struct ReplayServiceCreator {
+    path: PathBuf
+}
+
+impl ReplayServiceCreator {
+    // Creates services that play back a file recorded with RecordingService
+    pub fn new<P: AsRef<Path>>(path: P) -> Box<VRServiceCreator> {
+        Box::new(ReplayServiceCreator {
+            path: path.as_ref().to_path_buf()
+        })
+    }
+}
+
+impl VRServiceCreator for ReplayServiceCreator {
+     fn new_service(&self) -> Box<VRService> {
+         Box::new(ReplayVRService::new(self.path.clone()))
+     }
+}
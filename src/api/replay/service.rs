@@ -0,0 +1,188 @@
+use {VRService, VRDisplay, VRDisplayEvent, VRError, VRGamepad, VRDisplayPtr, VREvent, VRGamepadEvent, VRGamepadPtr};
+use recording::{Recording, RecordedEvent};
+use super::controls::ReplayControls;
+use super::display::{ReplayDisplay, ReplayDisplayPtr};
+use super::gamepad::{ReplayGamepad, ReplayGamepadPtr};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// Service that plays back a session recorded with RecordingService
+pub struct ReplayVRService {
+    path: Option<PathBuf>,
+    recording: Option<Arc<Recording>>,
+    controls: ReplayControls,
+    displays: Vec<ReplayDisplayPtr>,
+    gamepads: Vec<ReplayGamepadPtr>,
+    // Maps recorded display ids to the ids of the replay displays
+    display_ids: HashMap<u32, u32>,
+    // Maps recorded gamepad ids to the ids of the replay gamepads
+    gamepad_ids: HashMap<u32, u32>,
+    // Playback time of the last poll_events call
+    last_poll: Cell<f64>,
+    last_seek_count: Cell<u64>
+}
+
+impl VRService for ReplayVRService {
+    fn initialize(&mut self) -> Result<(), VRError> {
+        if self.recording.is_some() {
+            return Ok(());
+        }
+        let recording = match self.path {
+            Some(ref path) => try!(Recording::from_file(path).map_err(|e| VRError::Backend("Replay".into(), e))),
+            None => return Err(VRError::Backend("Replay".into(), "No recording to replay".into()))
+        };
+        self.load(recording);
+        Ok(())
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, VRError> {
+        try!(self.initialize());
+        Ok(self.displays.iter().map(|d| d.clone() as VRDisplayPtr).collect())
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, VRError> {
+        try!(self.initialize());
+        Ok(self.gamepads.iter().map(|g| g.clone() as VRGamepadPtr).collect())
+    }
+
+    fn is_available(&self) -> bool {
+        self.recording.is_some() || self.path.as_ref().map_or(false, |path| path.exists())
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        let mut events = Vec::new();
+        let recording = match self.recording {
+            Some(ref recording) => recording,
+            None => return events
+        };
+
+        let elapsed = self.controls.elapsed();
+        let (seek_count, seek_elapsed) = self.controls.last_seek();
+        if seek_count != self.last_seek_count.get() {
+            // Restart from the seek position, including the events recorded at that time
+            self.last_seek_count.set(seek_count);
+            self.last_poll.set(seek_elapsed - 1.0);
+        }
+        let last_poll = self.last_poll.get();
+        if elapsed <= last_poll {
+            return events;
+        }
+        self.last_poll.set(elapsed);
+
+        // Events recorded between the last poll and now, in all the reproduced loops
+        let (loop_duration, first_loop) = self.controls.loop_count(last_poll.max(0.0));
+        let (_, last_loop) = self.controls.loop_count(elapsed);
+        for l in first_loop..last_loop + 1 {
+            let offset = l as f64 * loop_duration;
+            for entry in &recording.entries {
+                let time = offset + entry.time as f64;
+                if time <= last_poll || time > elapsed {
+                    continue;
+                }
+                if let RecordedEvent::Event(ref event) = entry.event {
+                    events.push(self.remap_event(event));
+                }
+            }
+        }
+
+        events
+    }
+}
+
+impl ReplayVRService {
+    // Creates a service that loads the recording file when it's initialized
+    pub fn new(path: PathBuf) -> ReplayVRService {
+        let mut service = ReplayVRService::empty();
+        service.path = Some(path);
+        service
+    }
+
+    pub fn from_recording(recording: Recording) -> ReplayVRService {
+        let mut service = ReplayVRService::empty();
+        service.load(recording);
+        service
+    }
+
+    fn empty() -> ReplayVRService {
+        ReplayVRService {
+            path: None,
+            recording: None,
+            controls: ReplayControls::new(),
+            displays: Vec::new(),
+            gamepads: Vec::new(),
+            display_ids: HashMap::new(),
+            gamepad_ids: HashMap::new(),
+            // Events recorded at time 0 must be included in the first poll
+            last_poll: Cell::new(-1.0),
+            last_seek_count: Cell::new(0)
+        }
+    }
+
+    // Playback controls shared by all the replayed devices
+    pub fn controls(&self) -> ReplayControls {
+        self.controls.clone()
+    }
+
+    fn load(&mut self, recording: Recording) {
+        let duration = recording.entries.last().map_or(0, |entry| entry.time);
+        self.controls.set_duration(duration);
+
+        let recording = Arc::new(recording);
+        for recorded_id in recording.display_ids() {
+            let display = ReplayDisplay::new(recording.clone(), recorded_id, self.controls.clone());
+            self.display_ids.insert(recorded_id, display.read().unwrap().id());
+            self.displays.push(display);
+        }
+        for recorded_id in recording.gamepad_ids() {
+            let gamepad = ReplayGamepad::new(recording.clone(), recorded_id, self.controls.clone(),
+                                             self.display_ids.clone());
+            self.gamepad_ids.insert(recorded_id, gamepad.read().unwrap().id());
+            self.gamepads.push(gamepad);
+        }
+        self.recording = Some(recording);
+    }
+
+    fn display_id(&self, recorded_id: u32) -> u32 {
+        *self.display_ids.get(&recorded_id).unwrap_or(&recorded_id)
+    }
+
+    fn gamepad_id(&self, recorded_id: u32) -> u32 {
+        *self.gamepad_ids.get(&recorded_id).unwrap_or(&recorded_id)
+    }
+
+    // Replaces the recorded device ids with the ids of the replayed devices
+    fn remap_event(&self, event: &VREvent) -> VREvent {
+        let mut event = event.clone();
+        match event {
+            VREvent::Display(ref mut event) => match *event {
+                VRDisplayEvent::Connect(ref mut data) |
+                VRDisplayEvent::Activate(ref mut data, _) |
+                VRDisplayEvent::Deactivate(ref mut data, _) |
+                VRDisplayEvent::Change(ref mut data) |
+                VRDisplayEvent::Blur(ref mut data) |
+                VRDisplayEvent::Focus(ref mut data) |
+                VRDisplayEvent::PresentChange(ref mut data, _) => {
+                    data.display_id = self.display_id(data.display_id);
+                },
+                VRDisplayEvent::Disconnect(ref mut id) |
+                VRDisplayEvent::Pause(ref mut id) |
+                VRDisplayEvent::Resume(ref mut id) |
+                VRDisplayEvent::Exit(ref mut id) => {
+                    *id = self.display_id(*id);
+                }
+            },
+            VREvent::Gamepad(ref mut event) => match *event {
+                VRGamepadEvent::Connect(ref mut data, ref mut state) => {
+                    data.display_id = self.display_id(data.display_id);
+                    state.gamepad_id = self.gamepad_id(state.gamepad_id);
+                },
+                VRGamepadEvent::Disconnect(ref mut id) => {
+                    *id = self.gamepad_id(*id);
+                }
+            }
+        }
+        event
+    }
+}
@@ -0,0 +1,73 @@
+use VRReferenceSpace;
+use recording::{Recording, RecordedEntry, RecordedEvent};
+use std::sync::Arc;
+
+// Entries of a single recorded device, sorted by time
+pub struct DeviceTimeline {
+    recording: Arc<Recording>,
+    // Indices of the recording entries of the device
+    entries: Vec<usize>,
+    // Reference space selected in the device before each entry
+    reference_spaces: Vec<VRReferenceSpace>
+}
+
+impl DeviceTimeline {
+    pub fn new<F>(recording: Arc<Recording>, filter: F) -> DeviceTimeline where F: Fn(&RecordedEntry) -> bool {
+        let entries: Vec<usize> = recording.entries.iter().enumerate().filter(|&(_, entry)| filter(entry))
+                                                   .map(|(index, _)| index).collect();
+        let mut space = VRReferenceSpace::default();
+        let reference_spaces = entries.iter().map(|&index| {
+            let before = space;
+            if let RecordedEvent::SetReferenceSpace(selected) = recording.entries[index].event {
+                space = selected;
+            }
+            before
+        }).collect();
+        DeviceTimeline {
+            recording: recording,
+            entries: entries,
+            reference_spaces: reference_spaces
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn entry(&self, index: usize) -> &RecordedEntry {
+        &self.recording.entries[self.entries[index]]
+    }
+
+    // Index of the first entry whose playback time, shifted by `offset`, is after `elapsed`
+    pub fn first_after(&self, offset: f64, elapsed: f64) -> usize {
+        let (mut low, mut high) = (0, self.entries.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if offset + self.entry(mid).time as f64 <= elapsed {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    // Reference space selected in the recorded device when the entry `index` was recorded
+    pub fn reference_space_before(&self, index: usize) -> VRReferenceSpace {
+        self.reference_spaces[index]
+    }
+
+    // Returns the latest entry recorded at or before `time` matching the filter.
+    // Falls back to the first matching entry when the recording has not reached one yet.
+    pub fn latest<F>(&self, time: u64, filter: F) -> Option<&RecordedEntry> where F: Fn(&RecordedEvent) -> bool {
+        self.latest_index(time, filter).map(|i| self.entry(i))
+    }
+
+    // Same as `latest` but returns the index of the entry
+    pub fn latest_index<F>(&self, time: u64, filter: F) -> Option<usize> where F: Fn(&RecordedEvent) -> bool {
+        let end = self.first_after(0.0, time as f64);
+        (0..end).rev().find(|&i| filter(&self.entry(i).event)).or_else(|| {
+            (end..self.len()).find(|&i| filter(&self.entry(i).event))
+        })
+    }
+}
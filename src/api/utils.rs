@@ -1,10 +1,18 @@
 use std::mem;
+use {VRDisplayData, VRFieldOfView, VRFrameData, VRPose, VRReferenceSpace, VRStageBounds, VRStageParameters};
 use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT};
 use std::sync::atomic::Ordering::SeqCst;
 use time;
 
 static DEVICE_ID_COUNTER: AtomicUsize  = ATOMIC_USIZE_INIT;
 
+// Average seated to standing transform used when a display doesn't report stage parameters
+#[allow(dead_code)]
+pub const DEFAULT_SITTING_TO_STANDING_TRANSFORM: [f32; 16] = [1.0, 0.0, 0.0, 0.0,
+                                                              0.0, 1.0, 0.0, 0.0,
+                                                              0.0, 0.0, 1.0, 0.0,
+                                                              0.0, 0.75, 0.0, 1.0];
+
 // Generates a unique identifier for any VRDisplay
 #[allow(dead_code)]
 pub fn new_id() -> u32 {
@@ -135,3 +143,195 @@ pub fn copysign(a: f32, b: f32) -> f32 {
         a.abs() * b.signum()
     }
 }
+
+// Builds a column major transformation matrix from a position and an orientation quaternion
+#[allow(dead_code)]
+pub fn pose_to_matrix(position: &[f32; 3], orientation: &[f32; 4]) -> [f32; 16] {
+    let (x, y, z, w) = (orientation[0], orientation[1], orientation[2], orientation[3]);
+    let (x2, y2, z2) = (x + x, y + y, z + z);
+    let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+    let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+    let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+
+    [1.0 - (yy + zz), xy + wz, xz - wy, 0.0,
+     xy - wz, 1.0 - (xx + zz), yz + wx, 0.0,
+     xz + wy, yz - wx, 1.0 - (xx + yy), 0.0,
+     position[0], position[1], position[2], 1.0]
+}
+
+// View matrix must be the inverse of the head transform multiplied by the eye_to_head transform
+#[allow(dead_code)]
+pub fn eye_view_matrix(head: &[f32; 16], eye_offset: &[f32; 3]) -> [f32; 16] {
+    let eye_to_head = pose_to_matrix(eye_offset, &[0.0, 0.0, 0.0, 1.0]);
+    let mut eye = identity_matrix!();
+    multiply_matrix(head, &eye_to_head, &mut eye);
+    let mut view = identity_matrix!();
+    inverse_matrix(&eye, &mut view);
+    view
+}
+
+// Hamilton product of two [x, y, z, w] quaternions
+#[allow(dead_code)]
+pub fn multiply_quat(a: &[f32; 4], b: &[f32; 4]) -> [f32; 4] {
+    [a[3] * b[0] + a[0] * b[3] + a[1] * b[2] - a[2] * b[1],
+     a[3] * b[1] - a[0] * b[2] + a[1] * b[3] + a[2] * b[0],
+     a[3] * b[2] + a[0] * b[1] - a[1] * b[0] + a[2] * b[3],
+     a[3] * b[3] - a[0] * b[0] - a[1] * b[1] - a[2] * b[2]]
+}
+
+// Converts a rotation vector (axis scaled by the angle in radians) to a quaternion
+#[allow(dead_code)]
+pub fn rotation_vector_to_quat(v: &[f32; 3]) -> [f32; 4] {
+    let angle = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if angle == 0.0 {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+    let s = (angle * 0.5).sin() / angle;
+    [v[0] * s, v[1] * s, v[2] * s, (angle * 0.5).cos()]
+}
+
+// Rotates a vector by a quaternion
+#[allow(dead_code)]
+pub fn rotate_vector(q: &[f32; 4], v: &[f32; 3]) -> [f32; 3] {
+    let p = multiply_quat(&multiply_quat(q, &[v[0], v[1], v[2], 0.0]), &[-q[0], -q[1], -q[2], q[3]]);
+    [p[0], p[1], p[2]]
+}
+
+// Predicts the pose `dt` seconds after the given one, assuming constant accelerations.
+// Used by backends without native pose prediction.
+// Angular velocities and accelerations must be given in the tracking space.
+#[allow(dead_code)]
+pub fn extrapolate_pose(pose: &VRPose, dt: f32) -> VRPose {
+    let mut out = *pose;
+    if let (Some(position), Some(velocity)) = (pose.position, pose.linear_velocity) {
+        let acceleration = pose.linear_acceleration.unwrap_or([0.0, 0.0, 0.0]);
+        let mut p = position;
+        let mut v = velocity;
+        for i in 0..3 {
+            p[i] += (velocity[i] + 0.5 * acceleration[i] * dt) * dt;
+            v[i] += acceleration[i] * dt;
+        }
+        out.position = Some(p);
+        out.linear_velocity = Some(v);
+    }
+    if let (Some(orientation), Some(velocity)) = (pose.orientation, pose.angular_velocity) {
+        let acceleration = pose.angular_acceleration.unwrap_or([0.0, 0.0, 0.0]);
+        let mut rotation = [0.0; 3];
+        let mut v = velocity;
+        for i in 0..3 {
+            rotation[i] = (velocity[i] + 0.5 * acceleration[i] * dt) * dt;
+            v[i] += acceleration[i] * dt;
+        }
+        let q = multiply_quat(&rotation_vector_to_quat(&rotation), &orientation);
+        let length = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+        out.orientation = Some([q[0] / length, q[1] / length, q[2] / length, q[3] / length]);
+        out.angular_velocity = Some(v);
+    }
+    out
+}
+
+// Predicts the frame data `dt` seconds after the given one.
+// The view matrices are computed from the extrapolated pose and the eye offsets of the display.
+#[allow(dead_code)]
+pub fn extrapolate_frame_data(data: &VRFrameData, display: &VRDisplayData, dt: f32) -> VRFrameData {
+    let mut out = data.clone();
+    out.pose = extrapolate_pose(&data.pose, dt);
+    out.timestamp = data.timestamp + dt as f64 * 1000.0;
+    if let Some(orientation) = out.pose.orientation {
+        let position = out.pose.position.unwrap_or([0.0, 0.0, 0.0]);
+        let head = pose_to_matrix(&position, &orientation);
+        out.left_view_matrix = eye_view_matrix(&head, &display.left_eye_parameters.offset);
+        out.right_view_matrix = eye_view_matrix(&head, &display.right_eye_parameters.offset);
+    }
+    out
+}
+
+// Transform from the `from` reference space to the `to` reference space.
+// None if no transform is needed or if the spaces can't be related, as happens with the raw space.
+#[allow(dead_code)]
+pub fn reference_space_transform(from: VRReferenceSpace,
+                                 to: VRReferenceSpace,
+                                 stage: Option<&VRStageParameters>)
+                                 -> Option<[f32; 16]> {
+    let sitting_to_standing = stage.map_or(DEFAULT_SITTING_TO_STANDING_TRANSFORM,
+                                           |stage| stage.sitting_to_standing_transform);
+    match (from, to) {
+        (VRReferenceSpace::Seated, VRReferenceSpace::Standing) => Some(sitting_to_standing),
+        (VRReferenceSpace::Standing, VRReferenceSpace::Seated) => {
+            let mut inverse = identity_matrix!();
+            inverse_matrix(&sitting_to_standing, &mut inverse);
+            Some(inverse)
+        },
+        _ => None
+    }
+}
+
+// Applies a rigid transform to a pose, including its velocities and accelerations
+#[allow(dead_code)]
+pub fn transform_pose(transform: &[f32; 16], pose: &VRPose) -> VRPose {
+    let m = transform;
+    let rotate = |v: [f32; 3]| -> [f32; 3] {
+        [m[0] * v[0] + m[4] * v[1] + m[8] * v[2],
+         m[1] * v[0] + m[5] * v[1] + m[9] * v[2],
+         m[2] * v[0] + m[6] * v[1] + m[10] * v[2]]
+    };
+
+    let mut out = *pose;
+    out.position = pose.position.map(|p| {
+        let r = rotate(p);
+        [r[0] + m[12], r[1] + m[13], r[2] + m[14]]
+    });
+    out.orientation = pose.orientation.map(|q| multiply_quat(&matrix_to_quat(transform), &q));
+    out.linear_velocity = pose.linear_velocity.map(&rotate);
+    out.linear_acceleration = pose.linear_acceleration.map(&rotate);
+    out.angular_velocity = pose.angular_velocity.map(&rotate);
+    out.angular_acceleration = pose.angular_acceleration.map(&rotate);
+    out
+}
+
+// Applies a rigid transform to the pose and the view matrices of a frame
+#[allow(dead_code)]
+pub fn transform_frame_data(transform: &[f32; 16], data: &mut VRFrameData) {
+    data.pose = transform_pose(transform, &data.pose);
+    let mut inverse = identity_matrix!();
+    inverse_matrix(transform, &mut inverse);
+    let left_view = data.left_view_matrix;
+    multiply_matrix(&left_view, &inverse, &mut data.left_view_matrix);
+    let right_view = data.right_view_matrix;
+    multiply_matrix(&right_view, &inverse, &mut data.right_view_matrix);
+}
+
+// Rectangular play area of the given size centered at the standing space origin
+#[allow(dead_code)]
+pub fn rect_stage_bounds(size_x: f32, size_z: f32) -> VRStageBounds {
+    let (x, z) = (size_x * 0.5, size_z * 0.5);
+    VRStageBounds::Polygon(vec![[-x, 0.0, -z], [x, 0.0, -z], [x, 0.0, z], [-x, 0.0, z]])
+}
+
+// Computes a column major projection matrix from a field of view given in degrees
+#[allow(dead_code)]
+pub fn fov_to_projection_matrix(fov: &VRFieldOfView, near: f32, far: f32) -> [f32; 16] {
+    let left = -(fov.left_degrees as f32).to_radians().tan() * near;
+    let right = (fov.right_degrees as f32).to_radians().tan() * near;
+    let top = (fov.up_degrees as f32).to_radians().tan() * near;
+    let bottom = -(fov.down_degrees as f32).to_radians().tan() * near;
+    frustum_matrix(left, right, bottom, top, near, far)
+}
+
+#[allow(dead_code)]
+pub fn frustum_matrix(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> [f32; 16] {
+    let r_width  = 1.0 / (right - left);
+    let r_height = 1.0 / (top - bottom);
+    let r_depth  = 1.0 / (near - far);
+    let x = 2.0 * (near * r_width);
+    let y = 2.0 * (near * r_height);
+    let a = (right + left) * r_width;
+    let b = (top + bottom) * r_height;
+    let c = (far + near) * r_depth;
+    let d = 2.0 * (far * near * r_depth);
+
+    [x, 0.0, 0.0, 0.0,
+     0.0, y, 0.0, 0.0,
+     a, b, c, -1.0,
+     0.0, 0.0, d, 0.0]
+}
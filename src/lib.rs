@@ -18,7 +18,11 @@ extern crate log;
 #[cfg(feature = "serde-serialization")]
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "mock-file")]
+extern crate serde_json;
 extern crate time;
+#[cfg(feature = "mock-file")]
+extern crate toml;
 
 #[cfg(feature = "googlevr")]
 mod gl {
@@ -33,12 +37,16 @@ pub mod vr_display_capabilities;
 pub mod vr_eye;
 pub mod vr_eye_parameters;
 pub mod vr_frame_data;
+pub mod vr_frame_stats;
 pub mod vr_layer;
 pub mod vr_pose;
+pub mod vr_reference_space;
 pub mod vr_stage_parameters;
 pub mod vr_event;
 pub mod vr_field_view;
 pub mod vr_gamepad;
+pub mod vr_error;
+pub mod vr_wire;
 
 pub use vr_display::{VRDisplay,VRDisplayPtr};
 pub use vr_service::{VRService,VRServiceCreator};
@@ -48,12 +56,17 @@ pub use vr_display_capabilities::VRDisplayCapabilities;
 pub use vr_eye::VREye;
 pub use vr_eye_parameters::VREyeParameters;
 pub use vr_frame_data::VRFrameData;
-pub use vr_layer::VRLayer;
+pub use vr_frame_stats::VRFrameStats;
+pub use vr_layer::{VRLayer, VRLayerDepth};
 pub use vr_pose::VRPose;
-pub use vr_stage_parameters::VRStageParameters;
+pub use vr_reference_space::VRReferenceSpace;
+pub use vr_stage_parameters::{VRStageParameters, VRStageBounds};
 pub use vr_event::{VREvent, VRDisplayEvent, VRDisplayEventReason, VRGamepadEvent};
 pub use vr_field_view::VRFieldOfView;
 pub use vr_gamepad::{VRGamepad, VRGamepadPtr, VRGamepadHand,
                      VRGamepadData, VRGamepadState, VRGamepadButton};
+pub use vr_error::VRError;
+pub use vr_wire::VRWireError;
 
 pub mod api;
+pub mod recording;
@@ -0,0 +1,136 @@
+use {VRDisplay, VRDisplayData, VRDisplayPtr, VRFrameData, VRFrameStats, VRLayer, VRReferenceSpace};
+use super::format::RecordedEvent;
+use super::writer::RecordingWriter;
+use vr_wire;
+use std::sync::{Arc, Mutex, RwLock};
+
+pub type RecordingDisplayPtr = Arc<RwLock<RecordingDisplay>>;
+
+// VRDisplay decorator that records all the frame data returned by the wrapped display,
+// the sync_poses and submit_frame calls and the display data snapshots.
+// Entries are recorded with the id of the wrapped display.
+pub struct RecordingDisplay {
+    display: VRDisplayPtr,
+    writer: RecordingWriter,
+    // Encoded display data of the last recorded snapshot
+    last_data: Mutex<Vec<u8>>
+}
+
+impl RecordingDisplay {
+    pub fn new(display: VRDisplayPtr, writer: RecordingWriter) -> RecordingDisplayPtr {
+        let recording = RecordingDisplay {
+            display: display,
+            writer: writer,
+            last_data: Mutex::new(Vec::new())
+        };
+        // Initial snapshot
+        recording.data();
+
+        Arc::new(RwLock::new(recording))
+    }
+
+    // Returns the wrapped display
+    pub fn display(&self) -> VRDisplayPtr {
+        self.display.clone()
+    }
+
+    pub fn writer(&self) -> RecordingWriter {
+        self.writer.clone()
+    }
+
+    fn record(&self, event: RecordedEvent) {
+        let id = self.display.read().unwrap().id();
+        self.writer.record(id, event);
+    }
+}
+
+impl VRDisplay for RecordingDisplay {
+    fn id(&self) -> u32 {
+        self.display.read().unwrap().id()
+    }
+
+    fn data(&self) -> VRDisplayData {
+        let data = self.display.read().unwrap().data();
+
+        // Only record a new snapshot when the data changes
+        let mut encoded = Vec::new();
+        vr_wire::write_display_data(&mut encoded, &data);
+        let mut last_data = self.last_data.lock().unwrap();
+        if *last_data != encoded {
+            *last_data = encoded;
+            self.writer.record(data.display_id, RecordedEvent::DisplayData(data.clone()));
+        }
+
+        data
+    }
+
+    fn inmediate_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let data = self.display.read().unwrap().inmediate_frame_data(near_z, far_z);
+        self.record(RecordedEvent::InmediateFrameData {
+            near: near_z,
+            far: far_z,
+            data: data.clone()
+        });
+        data
+    }
+
+    fn synced_frame_data(&self, near_z: f64, far_z: f64) -> VRFrameData {
+        let data = self.display.read().unwrap().synced_frame_data(near_z, far_z);
+        self.record(RecordedEvent::SyncedFrameData {
+            near: near_z,
+            far: far_z,
+            data: data.clone()
+        });
+        data
+    }
+
+    fn predicted_frame_data(&self, near_z: f64, far_z: f64, target_time: f64) -> VRFrameData {
+        // Predictions aren't recorded. Replays extrapolate them from the recorded frame data.
+        self.display.read().unwrap().predicted_frame_data(near_z, far_z, target_time)
+    }
+
+    fn reset_pose(&mut self) {
+        self.display.write().unwrap().reset_pose();
+        self.record(RecordedEvent::ResetPose);
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.display.write().unwrap().set_reference_space(space);
+        self.record(RecordedEvent::SetReferenceSpace(space));
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.display.read().unwrap().reference_space()
+    }
+
+    fn sync_poses(&self) {
+        self.display.read().unwrap().sync_poses();
+        self.record(RecordedEvent::SyncPoses);
+    }
+
+    fn submit_frame(&mut self, layer: &VRLayer) {
+        self.display.write().unwrap().submit_frame(layer);
+        self.record(RecordedEvent::SubmitFrame(layer.clone()));
+    }
+
+    fn submit_layers(&mut self, layers: &[VRLayer]) {
+        self.display.write().unwrap().submit_layers(layers);
+        self.record(RecordedEvent::SubmitLayers(layers.to_vec()));
+    }
+
+    fn frame_stats(&self) -> VRFrameStats {
+        let stats = self.display.read().unwrap().frame_stats();
+        self.record(RecordedEvent::FrameStats(stats.clone()));
+        stats
+    }
+
+    fn start_present(&mut self) {
+        self.display.write().unwrap().start_present();
+        self.record(RecordedEvent::StartPresent);
+    }
+
+    fn stop_present(&mut self) {
+        self.display.write().unwrap().stop_present();
+        self.record(RecordedEvent::StopPresent);
+    }
+}
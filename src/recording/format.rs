@@ -0,0 +1,379 @@
+use {VRDisplayData, VREvent, VRFrameData, VRGamepadData, VRGamepadState, VRFrameStats, VRLayer, VRReferenceSpace,
+     VRStageBounds, VRStageParameters};
+use std::fs::File;
+use std::io::Read;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use vr_wire::{self, VRWireError, WireReader, read_capabilities, read_event_with, read_eye_parameters, read_frame_data,
+              read_frame_stats, read_gamepad_data, read_gamepad_state, read_reference_space, read_stage_parameters,
+              write_display_data, write_event, write_f64, write_frame_data, write_frame_stats, write_gamepad_data,
+              write_gamepad_state, write_layer, write_reference_space, write_u16, write_u32, write_u64, write_u8};
+
+// Recording file layout. All values are little endian.
+//
+// header: "WVRREC" magic + u16 version
+// entries until the end of the file:
+//   u8 kind + u64 nanoseconds since the recording started + u32 device_id + payload
+//
+// Payloads:
+//   DisplayData: display data
+//   InmediateFrameData, SyncedFrameData: f64 near, f64 far, frame data
+//   SubmitFrame: layer
+//   SubmitLayers: u32 count + layers
+//   SyncPoses, ResetPose, StartPresent, StopPresent: empty
+//   SetReferenceSpace: reference space
+//   FrameStats: frame stats
+//   GamepadData: gamepad data
+//   GamepadState: gamepad state
+//   Event: event. device_id is not used by service events.
+//
+// Values use the encoding described in vr_wire, without the wire version header.
+// Changes to that encoding require a new recording version.
+//
+// Version history:
+//   1: initial version
+//   2: i32 order and f32 opacity appended to layers, SubmitLayers entries
+//   3: optional right eye texture appended to layers
+//   4: optional depth (u32 texture, optional u32 right texture, f64 near, f64 far),
+//      optional render pose and optional f64 render timestamp appended to layers
+//   5: FrameStats entries
+//   6: SetReferenceSpace entries
+//   7: stage bounds appended to stage parameters, stored as an optional polygon
+//      (u32 count + f32 x, y, z points). Missing polygons mean the bounds are not available.
+//   8: u32 display_id prepended to display data, also in events
+pub const MAGIC: &'static [u8] = b"WVRREC";
+pub const VERSION: u16 = 8;
+
+const KIND_DISPLAY_DATA: u8 = 1;
+const KIND_INMEDIATE_FRAME_DATA: u8 = 2;
+const KIND_SYNCED_FRAME_DATA: u8 = 3;
+const KIND_SYNC_POSES: u8 = 4;
+const KIND_SUBMIT_FRAME: u8 = 5;
+const KIND_RESET_POSE: u8 = 6;
+const KIND_START_PRESENT: u8 = 7;
+const KIND_STOP_PRESENT: u8 = 8;
+const KIND_EVENT: u8 = 9;
+const KIND_GAMEPAD_DATA: u8 = 10;
+const KIND_GAMEPAD_STATE: u8 = 11;
+const KIND_SUBMIT_LAYERS: u8 = 12;
+const KIND_FRAME_STATS: u8 = 13;
+const KIND_SET_REFERENCE_SPACE: u8 = 14;
+
+// A call recorded from a VRDisplay, a VRGamepad or a VRService
+#[derive(Debug, Clone)]
+pub enum RecordedEvent {
+    // Snapshot of the display data. Recorded when the recording starts and every time the data changes.
+    DisplayData(VRDisplayData),
+    InmediateFrameData {
+        near: f64,
+        far: f64,
+        data: VRFrameData
+    },
+    SyncedFrameData {
+        near: f64,
+        far: f64,
+        data: VRFrameData
+    },
+    SyncPoses,
+    SubmitFrame(VRLayer),
+    // Layers passed to VRDisplay::submit_layers, in submission order
+    SubmitLayers(Vec<VRLayer>),
+    ResetPose,
+    StartPresent,
+    StopPresent,
+    // Reference space selected in a display or a gamepad
+    SetReferenceSpace(VRReferenceSpace),
+    // Statistics returned by VRDisplay::frame_stats
+    FrameStats(VRFrameStats),
+    // Snapshot of the gamepad data. Recorded when the recording starts and every time the data changes.
+    GamepadData(VRGamepadData),
+    // State returned by VRGamepad::state
+    GamepadState(VRGamepadState),
+    // Event returned by VRService::poll_events
+    Event(VREvent)
+}
+
+impl RecordedEvent {
+    pub fn is_display_event(&self) -> bool {
+        match *self {
+            RecordedEvent::GamepadData(_) | RecordedEvent::GamepadState(_) | RecordedEvent::Event(_) => false,
+            _ => true
+        }
+    }
+
+    pub fn is_gamepad_event(&self) -> bool {
+        match *self {
+            RecordedEvent::GamepadData(_) | RecordedEvent::GamepadState(_) |
+            RecordedEvent::SetReferenceSpace(_) => true,
+            _ => false
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedEntry {
+    // Nanoseconds elapsed since the recording started
+    pub time: u64,
+    // Id of the display or gamepad
+    pub device_id: u32,
+    pub event: RecordedEvent
+}
+
+// A recording loaded in memory
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub entries: Vec<RecordedEntry>
+}
+
+impl Recording {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Recording, String> {
+        let path = path.as_ref();
+        let mut bytes = Vec::new();
+        let mut file = try!(File::open(path).map_err(|e| format!("Error opening {:?}: {}", path, e)));
+        try!(file.read_to_end(&mut bytes).map_err(|e| format!("Error reading {:?}: {}", path, e)));
+        Recording::from_bytes(&bytes).map_err(|e| format!("Error parsing {:?}: {}", path, e))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Recording, String> {
+        let mut reader = Reader::new(bytes);
+        if try!(reader.bytes(MAGIC.len())) != MAGIC {
+            return Err("Not a WebVR recording".into());
+        }
+        let version = try!(reader.u16());
+        if version == 0 || version > VERSION {
+            return Err(format!("Unsupported recording version {}", version));
+        }
+        reader.version = version;
+
+        let mut entries = Vec::new();
+        while !reader.is_empty() {
+            entries.push(try!(read_entry(&mut reader)));
+        }
+
+        Ok(Recording {
+            entries: entries
+        })
+    }
+
+    // Ids of the recorded displays, in order of appearance
+    pub fn display_ids(&self) -> Vec<u32> {
+        self.device_ids(|event| event.is_display_event())
+    }
+
+    // Ids of the recorded gamepads, in order of appearance
+    pub fn gamepad_ids(&self) -> Vec<u32> {
+        self.device_ids(|event| event.is_gamepad_event())
+    }
+
+    fn device_ids<F>(&self, filter: F) -> Vec<u32> where F: Fn(&RecordedEvent) -> bool {
+        let mut ids = Vec::new();
+        for entry in &self.entries {
+            // Recorded by both displays and gamepads, so it doesn't tell the kind of the device
+            if let RecordedEvent::SetReferenceSpace(_) = entry.event {
+                continue;
+            }
+            if filter(&entry.event) && !ids.contains(&entry.device_id) {
+                ids.push(entry.device_id);
+            }
+        }
+        ids
+    }
+}
+
+pub fn write_header(out: &mut Vec<u8>) {
+    out.extend_from_slice(MAGIC);
+    write_u16(out, VERSION);
+}
+
+pub fn write_entry(out: &mut Vec<u8>, entry: &RecordedEntry) {
+    let kind = match entry.event {
+        RecordedEvent::DisplayData(_) => KIND_DISPLAY_DATA,
+        RecordedEvent::InmediateFrameData { .. } => KIND_INMEDIATE_FRAME_DATA,
+        RecordedEvent::SyncedFrameData { .. } => KIND_SYNCED_FRAME_DATA,
+        RecordedEvent::SyncPoses => KIND_SYNC_POSES,
+        RecordedEvent::SubmitFrame(_) => KIND_SUBMIT_FRAME,
+        RecordedEvent::SubmitLayers(_) => KIND_SUBMIT_LAYERS,
+        RecordedEvent::ResetPose => KIND_RESET_POSE,
+        RecordedEvent::StartPresent => KIND_START_PRESENT,
+        RecordedEvent::StopPresent => KIND_STOP_PRESENT,
+        RecordedEvent::FrameStats(_) => KIND_FRAME_STATS,
+        RecordedEvent::SetReferenceSpace(_) => KIND_SET_REFERENCE_SPACE,
+        RecordedEvent::GamepadData(_) => KIND_GAMEPAD_DATA,
+        RecordedEvent::GamepadState(_) => KIND_GAMEPAD_STATE,
+        RecordedEvent::Event(_) => KIND_EVENT
+    };
+    write_u8(out, kind);
+    write_u64(out, entry.time);
+    write_u32(out, entry.device_id);
+
+    match entry.event {
+        RecordedEvent::DisplayData(ref data) => write_display_data(out, data),
+        RecordedEvent::InmediateFrameData { near, far, ref data } |
+        RecordedEvent::SyncedFrameData { near, far, ref data } => {
+            write_f64(out, near);
+            write_f64(out, far);
+            write_frame_data(out, data);
+        },
+        RecordedEvent::SubmitFrame(ref layer) => write_layer(out, layer),
+        RecordedEvent::SubmitLayers(ref layers) => {
+            write_u32(out, layers.len() as u32);
+            for layer in layers {
+                write_layer(out, layer);
+            }
+        },
+        RecordedEvent::FrameStats(ref stats) => write_frame_stats(out, stats),
+        RecordedEvent::SetReferenceSpace(space) => write_reference_space(out, space),
+        RecordedEvent::GamepadData(ref data) => write_gamepad_data(out, data),
+        RecordedEvent::GamepadState(ref state) => write_gamepad_state(out, state),
+        RecordedEvent::Event(ref event) => write_event(out, event),
+        RecordedEvent::SyncPoses |
+        RecordedEvent::ResetPose |
+        RecordedEvent::StartPresent |
+        RecordedEvent::StopPresent => {}
+    }
+}
+
+fn read_entry(reader: &mut Reader) -> Result<RecordedEntry, String> {
+    let kind = try!(reader.u8());
+    let time = try!(reader.u64());
+    let device_id = try!(reader.u32());
+
+    let event = match kind {
+        KIND_DISPLAY_DATA => {
+            let version = reader.version;
+            RecordedEvent::DisplayData(try!(read_display_data(&mut reader.wire, version)))
+        },
+        KIND_INMEDIATE_FRAME_DATA => RecordedEvent::InmediateFrameData {
+            near: try!(reader.f64()),
+            far: try!(reader.f64()),
+            data: try!(read_frame_data(reader))
+        },
+        KIND_SYNCED_FRAME_DATA => RecordedEvent::SyncedFrameData {
+            near: try!(reader.f64()),
+            far: try!(reader.f64()),
+            data: try!(read_frame_data(reader))
+        },
+        KIND_SYNC_POSES => RecordedEvent::SyncPoses,
+        KIND_SUBMIT_FRAME => RecordedEvent::SubmitFrame(try!(read_layer(reader))),
+        KIND_SUBMIT_LAYERS => {
+            let count = try!(reader.u32());
+            let mut layers = Vec::new();
+            for _ in 0..count {
+                layers.push(try!(read_layer(reader)));
+            }
+            RecordedEvent::SubmitLayers(layers)
+        },
+        KIND_RESET_POSE => RecordedEvent::ResetPose,
+        KIND_START_PRESENT => RecordedEvent::StartPresent,
+        KIND_STOP_PRESENT => RecordedEvent::StopPresent,
+        KIND_FRAME_STATS => RecordedEvent::FrameStats(try!(read_frame_stats(reader))),
+        KIND_SET_REFERENCE_SPACE => RecordedEvent::SetReferenceSpace(try!(read_reference_space(reader))),
+        KIND_GAMEPAD_DATA => RecordedEvent::GamepadData(try!(read_gamepad_data(reader))),
+        KIND_GAMEPAD_STATE => RecordedEvent::GamepadState(try!(read_gamepad_state(reader))),
+        KIND_EVENT => {
+            let version = reader.version;
+            RecordedEvent::Event(try!(read_event_with(&mut reader.wire, |wire| read_display_data(wire, version))))
+        },
+        _ => return Err(format!("Unknown entry kind {}", kind))
+    };
+
+    Ok(RecordedEntry {
+        time: time,
+        device_id: device_id,
+        event: event
+    })
+}
+
+// Display data stored with the layout of the recording `version`
+fn read_display_data(reader: &mut WireReader, version: u16) -> Result<VRDisplayData, VRWireError> {
+    if version >= 8 {
+        return vr_wire::read_display_data(reader);
+    }
+
+    // Display data without display_id
+    let mut data = VRDisplayData::default();
+    data.display_name = try!(reader.string());
+    data.connected = try!(reader.bool());
+    data.capabilities = try!(read_capabilities(reader));
+    if try!(reader.bool()) {
+        if version >= 7 {
+            data.stage_parameters = Some(try!(read_stage_parameters(reader)));
+        } else {
+            // Stage parameters without bounds
+            let mut stage = VRStageParameters {
+                sitting_to_standing_transform: [0.0; 16],
+                size_x: 0.0,
+                size_z: 0.0,
+                bounds: VRStageBounds::NotAvailable
+            };
+            try!(reader.f32_slice(&mut stage.sitting_to_standing_transform));
+            stage.size_x = try!(reader.f32());
+            stage.size_z = try!(reader.f32());
+            data.stage_parameters = Some(stage);
+        }
+    }
+    data.left_eye_parameters = try!(read_eye_parameters(reader));
+    data.right_eye_parameters = try!(read_eye_parameters(reader));
+    Ok(data)
+}
+
+// Layer stored with the layout of the recording version
+fn read_layer(reader: &mut Reader) -> Result<VRLayer, String> {
+    if reader.version >= 4 {
+        return Ok(try!(vr_wire::read_layer(reader)));
+    }
+
+    // Fields appended in later versions are missing
+    let mut layer = VRLayer::default();
+    layer.texture_id = try!(reader.u32());
+    try!(reader.f32_slice(&mut layer.left_bounds));
+    try!(reader.f32_slice(&mut layer.right_bounds));
+    if try!(reader.bool()) {
+        layer.texture_size = Some((try!(reader.u32()), try!(reader.u32())));
+    }
+    if reader.version >= 2 {
+        layer.order = try!(reader.u32()) as i32;
+        layer.opacity = try!(reader.f32());
+    }
+    if reader.version >= 3 && try!(reader.bool()) {
+        layer.right_texture_id = Some(try!(reader.u32()));
+    }
+    Ok(layer)
+}
+
+// Reader of the recording payloads, which use the wire encoding of the values
+struct Reader<'a> {
+    wire: WireReader<'a>,
+    // Format version of the recording
+    version: u16
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader {
+            wire: WireReader::new(bytes),
+            version: VERSION
+        }
+    }
+}
+
+impl<'a> Deref for Reader<'a> {
+    type Target = WireReader<'a>;
+
+    fn deref(&self) -> &WireReader<'a> {
+        &self.wire
+    }
+}
+
+impl<'a> DerefMut for Reader<'a> {
+    fn deref_mut(&mut self) -> &mut WireReader<'a> {
+        &mut self.wire
+    }
+}
+
+// Errors of the wire reader are reported with the other recording errors
+impl From<VRWireError> for String {
+    fn from(error: VRWireError) -> String {
+        error.to_string()
+    }
+}
@@ -0,0 +1,81 @@
+use {VRGamepad, VRGamepadData, VRGamepadPtr, VRGamepadState, VRReferenceSpace};
+use super::format::RecordedEvent;
+use super::writer::RecordingWriter;
+use vr_wire;
+use std::sync::{Arc, Mutex, RwLock};
+
+pub type RecordingGamepadPtr = Arc<RwLock<RecordingGamepad>>;
+
+// VRGamepad decorator that records all the states returned by the wrapped gamepad
+// and the gamepad data snapshots.
+// Entries are recorded with the id of the wrapped gamepad.
+pub struct RecordingGamepad {
+    gamepad: VRGamepadPtr,
+    writer: RecordingWriter,
+    // Encoded gamepad data of the last recorded snapshot
+    last_data: Mutex<Vec<u8>>
+}
+
+impl RecordingGamepad {
+    pub fn new(gamepad: VRGamepadPtr, writer: RecordingWriter) -> RecordingGamepadPtr {
+        let recording = RecordingGamepad {
+            gamepad: gamepad,
+            writer: writer,
+            last_data: Mutex::new(Vec::new())
+        };
+        // Initial snapshot
+        recording.data();
+
+        Arc::new(RwLock::new(recording))
+    }
+
+    // Returns the wrapped gamepad
+    pub fn gamepad(&self) -> VRGamepadPtr {
+        self.gamepad.clone()
+    }
+
+    pub fn writer(&self) -> RecordingWriter {
+        self.writer.clone()
+    }
+}
+
+impl VRGamepad for RecordingGamepad {
+    fn id(&self) -> u32 {
+        self.gamepad.read().unwrap().id()
+    }
+
+    fn data(&self) -> VRGamepadData {
+        let data = self.gamepad.read().unwrap().data();
+
+        // Only record a new snapshot when the data changes
+        let mut encoded = Vec::new();
+        vr_wire::write_gamepad_data(&mut encoded, &data);
+        let mut last_data = self.last_data.lock().unwrap();
+        if *last_data != encoded {
+            *last_data = encoded;
+            self.writer.record(self.id(), RecordedEvent::GamepadData(data.clone()));
+        }
+
+        data
+    }
+
+    fn state(&self) -> VRGamepadState {
+        let state = self.gamepad.read().unwrap().state();
+        self.writer.record(self.id(), RecordedEvent::GamepadState(state.clone()));
+        state
+    }
+
+    fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.gamepad.write().unwrap().set_reference_space(space);
+        self.writer.record(self.id(), RecordedEvent::SetReferenceSpace(space));
+    }
+
+    fn reference_space(&self) -> VRReferenceSpace {
+        self.gamepad.read().unwrap().reference_space()
+    }
+
+    fn predicted_state(&self, target_time: f64) -> VRGamepadState {
+        // Only the states of the gamepad are recorded. Replays extrapolate the predictions from them.
+        self.gamepad.read().unwrap().predicted_state(target_time)
+    }
+}
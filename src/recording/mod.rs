@@ -0,0 +1,15 @@
+// Session recording.
+// Wrap a service with RecordingService, or single devices with RecordingDisplay and RecordingGamepad,
+// to save all the data returned by the devices to a compact binary file.
+// Recordings can be loaded later to reproduce issues without the hardware.
+mod display;
+mod format;
+mod gamepad;
+mod service;
+mod writer;
+
+pub use self::display::{RecordingDisplay, RecordingDisplayPtr};
+pub use self::format::{Recording, RecordedEntry, RecordedEvent};
+pub use self::gamepad::{RecordingGamepad, RecordingGamepadPtr};
+pub use self::service::RecordingService;
+pub use self::writer::RecordingWriter;
@@ -0,0 +1,78 @@
+use {VRDisplayPtr, VRError, VREvent, VRGamepadPtr, VRService};
+use super::display::{RecordingDisplay, RecordingDisplayPtr};
+use super::format::RecordedEvent;
+use super::gamepad::{RecordingGamepad, RecordingGamepadPtr};
+use super::writer::RecordingWriter;
+use std::collections::HashMap;
+
+// VRService decorator that records the events returned by poll_events
+// and wraps all the fetched displays and gamepads with a RecordingDisplay or a RecordingGamepad.
+pub struct RecordingService {
+    service: Box<VRService>,
+    writer: RecordingWriter,
+    // Recording displays by the id of the wrapped display
+    displays: HashMap<u32, RecordingDisplayPtr>,
+    // Recording gamepads by the id of the wrapped gamepad
+    gamepads: HashMap<u32, RecordingGamepadPtr>
+}
+
+impl RecordingService {
+    pub fn new(service: Box<VRService>, writer: RecordingWriter) -> RecordingService {
+        RecordingService {
+            service: service,
+            writer: writer,
+            displays: HashMap::new(),
+            gamepads: HashMap::new()
+        }
+    }
+
+    pub fn writer(&self) -> RecordingWriter {
+        self.writer.clone()
+    }
+}
+
+impl VRService for RecordingService {
+    fn initialize(&mut self) -> Result<(), VRError> {
+        self.service.initialize()
+    }
+
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, VRError> {
+        let displays = try!(self.service.fetch_displays());
+        let mut result = Vec::new();
+        for display in displays {
+            let id = display.read().unwrap().id();
+            let writer = &self.writer;
+            let recording = self.displays.entry(id).or_insert_with(|| {
+                RecordingDisplay::new(display.clone(), writer.clone())
+            });
+            result.push(recording.clone() as VRDisplayPtr);
+        }
+        Ok(result)
+    }
+
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, VRError> {
+        let gamepads = try!(self.service.fetch_gamepads());
+        let mut result = Vec::new();
+        for gamepad in gamepads {
+            let id = gamepad.read().unwrap().id();
+            let writer = &self.writer;
+            let recording = self.gamepads.entry(id).or_insert_with(|| {
+                RecordingGamepad::new(gamepad.clone(), writer.clone())
+            });
+            result.push(recording.clone() as VRGamepadPtr);
+        }
+        Ok(result)
+    }
+
+    fn is_available(&self) -> bool {
+        self.service.is_available()
+    }
+
+    fn poll_events(&self) -> Vec<VREvent> {
+        let events = self.service.poll_events();
+        for event in &events {
+            self.writer.record(0, RecordedEvent::Event(event.clone()));
+        }
+        events
+    }
+}
@@ -0,0 +1,82 @@
+use super::format::{self, RecordedEntry, RecordedEvent};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use time;
+
+// Handle to a recording file.
+// The handle can be cloned to record several devices to the same file.
+#[derive(Clone)]
+pub struct RecordingWriter {
+    inner: Arc<Mutex<WriterState>>
+}
+
+struct WriterState {
+    out: Box<Write + Send>,
+    // precise_time_ns value when the recording started
+    start_ns: u64,
+    // Reused buffer to encode entries
+    buffer: Vec<u8>,
+    // Recording stops after the first IO error
+    failed: bool
+}
+
+impl RecordingWriter {
+    // Creates a new recording file, replacing any existing file
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<RecordingWriter, String> {
+        let path = path.as_ref();
+        let file = try!(File::create(path).map_err(|e| format!("Error creating {:?}: {}", path, e)));
+        RecordingWriter::from_writer(BufWriter::new(file))
+    }
+
+    // Records to any output stream
+    pub fn from_writer<W: Write + Send + 'static>(out: W) -> Result<RecordingWriter, String> {
+        let mut out = out;
+        let mut header = Vec::new();
+        format::write_header(&mut header);
+        try!(out.write_all(&header).map_err(|e| format!("Error writing recording header: {}", e)));
+
+        Ok(RecordingWriter {
+            inner: Arc::new(Mutex::new(WriterState {
+                out: Box::new(out),
+                start_ns: time::precise_time_ns(),
+                buffer: Vec::new(),
+                failed: false
+            }))
+        })
+    }
+
+    // Appends an event of a display or gamepad to the recording, timestamped with the current time
+    pub fn record(&self, device_id: u32, event: RecordedEvent) {
+        let mut state = self.inner.lock().unwrap();
+        if state.failed {
+            return;
+        }
+
+        let entry = RecordedEntry {
+            time: time::precise_time_ns().saturating_sub(state.start_ns),
+            device_id: device_id,
+            event: event
+        };
+        let mut buffer = ::std::mem::replace(&mut state.buffer, Vec::new());
+        buffer.clear();
+        format::write_entry(&mut buffer, &entry);
+        if let Err(e) = state.out.write_all(&buffer) {
+            error!("Error writing VR recording, recording stopped: {}", e);
+            state.failed = true;
+        }
+        state.buffer = buffer;
+    }
+
+    // Writes buffered entries to the output
+    pub fn flush(&self) -> Result<(), String> {
+        let mut state = self.inner.lock().unwrap();
+        state.out.flush().map_err(|e| format!("Error flushing VR recording: {}", e))
+    }
+
+    // Returns false if the recording stopped due to an IO error
+    pub fn is_recording(&self) -> bool {
+        !self.inner.lock().unwrap().failed
+    }
+}
@@ -1,7 +1,10 @@
-use {VRDisplayData, VRFrameData, VRLayer};
-use std::sync::Arc;
-use std::cell::RefCell;
-pub type VRDisplayPtr = Arc<RefCell<VRDisplay>>;
+use {VRDisplayData, VRFrameData, VRFrameStats, VRLayer, VRReferenceSpace};
+use std::sync::{Arc, RwLock};
+
+// Shared handle to a display. It can be sent to other threads:
+// pose and data reads only need a read lock, so they can run while the render thread is
+// blocked in sync_poses. Methods that take &mut self need a write lock.
+pub type VRDisplayPtr = Arc<RwLock<VRDisplay>>;
 
 // The VRDisplay traits forms the base of all VR device implementations
 pub trait VRDisplay: Send + Sync {
@@ -21,18 +24,47 @@ pub trait VRDisplay: Send + Sync {
     // sync_poses must have been called before this call.
     fn synced_frame_data(&self, next: f64, far_z: f64) -> VRFrameData;
 
+    // Returns the VRFrameData predicted for target_time, a timestamp in milliseconds
+    // in the same time base as VRFrameData::timestamp. The returned timestamp is target_time.
+    // Backends without native prediction extrapolate the latest pose using its velocities.
+    fn predicted_frame_data(&self, near_z: f64, far_z: f64, target_time: f64) -> VRFrameData;
+
     // Resets the pose for this display
     fn reset_pose(&mut self);
 
+    // Selects the reference space of the poses and view matrices returned by this display
+    fn set_reference_space(&mut self, space: VRReferenceSpace);
+
+    fn reference_space(&self) -> VRReferenceSpace;
+
     // Synchronization point to keep in step with the HMD
     // Returns VRFrameData to be used in the next render frame
-    // Must be called in the render thread, before doing any work
-    fn sync_poses(&mut self);
+    // Must be called in the render thread, before doing any work.
+    // Only needs a read lock, so other threads can keep reading poses while it waits for vsync.
+    fn sync_poses(&self);
 
     // Submits frame to the display
     // Must be called in the render thread
     fn submit_frame(&mut self, layer: &VRLayer);
 
+    // Submits a stack of layers composited by the display, sorted by VRLayer::order.
+    // The number of layers must not exceed VRDisplayCapabilities::max_layers.
+    // Must be called in the render thread
+    fn submit_layers(&mut self, layers: &[VRLayer]) {
+        // Displays that can't composite layers only present the bottom layer
+        let mut sorted: Vec<&VRLayer> = layers.iter().collect();
+        sorted.sort_by_key(|layer| layer.order);
+        if sorted.len() > 1 {
+            warn!("VRDisplay can't composite layers, {} layers ignored", sorted.len() - 1);
+        }
+        if let Some(layer) = sorted.first() {
+            self.submit_frame(layer);
+        }
+    }
+
+    // Returns the timing statistics of the last frames
+    fn frame_stats(&self) -> VRFrameStats;
+
     // Hint to indicate that we are going to start sending frames to the device
     fn start_present(&mut self) {}
 
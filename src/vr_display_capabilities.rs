@@ -15,7 +15,8 @@ pub struct VRDisplayCapabilities {
     pub can_present: bool,
 
     // Indicates the maximum length of the array that requestPresent() will accept,
-    // Must be 1 if canPresent is true, 0 otherwise.
+    // and the maximum number of layers accepted by VRDisplay::submit_layers.
+    // 0 if canPresent is false.
     pub max_layers: u64
 }
 
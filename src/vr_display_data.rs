@@ -9,6 +9,7 @@ pub struct VRDisplayData {
     pub display_name: String,
     pub connected: bool,
     pub capabilities: VRDisplayCapabilities,
+    // None when the display doesn't support room-scale or the stage is not available yet
     pub stage_parameters: Option<VRStageParameters>,
     pub left_eye_parameters: VREyeParameters,
     pub right_eye_parameters: VREyeParameters,
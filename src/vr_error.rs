@@ -0,0 +1,56 @@
+use std::error::Error;
+use std::fmt;
+
+// Errors returned by the VRServices
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+pub enum VRError {
+    // The VR runtime library could not be loaded
+    LibraryNotFound,
+
+    // The VR runtime is not installed or its installation is corrupt
+    RuntimeNotInstalled,
+
+    // No headset is connected
+    NoHmd,
+
+    // The installed VR runtime doesn't support the interface versions used by the backend
+    InterfaceVersionMismatch,
+
+    // The VR runtime failed to initialize.
+    // params: runtime specific error code, description
+    InitFailed(i32, String),
+
+    // The service is used before being initialized
+    NotInitialized,
+
+    // Any other error reported by a backend.
+    // params: backend name, message
+    Backend(String, String)
+}
+
+impl fmt::Display for VRError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VRError::InitFailed(code, ref description) => {
+                write!(f, "VR runtime initialization failed with error {}: {}", code, description)
+            },
+            VRError::Backend(ref name, ref message) => write!(f, "{}: {}", name, message),
+            _ => f.write_str(self.description())
+        }
+    }
+}
+
+impl Error for VRError {
+    fn description(&self) -> &str {
+        match *self {
+            VRError::LibraryNotFound => "VR runtime library not found",
+            VRError::RuntimeNotInstalled => "VR runtime is not installed",
+            VRError::NoHmd => "No VR headset connected",
+            VRError::InterfaceVersionMismatch => "The installed VR runtime version is not supported",
+            VRError::InitFailed(_, ref description) => description,
+            VRError::NotInitialized => "VR service is not initialized",
+            VRError::Backend(_, ref message) => message
+        }
+    }
+}
@@ -1,6 +1,5 @@
 use VRPose;
-use std::mem;
-use std::ptr;
+use vr_wire::{self, VRWireError};
 
 // Represents all the information needed to render a single frame of a VR scene
 #[derive(Debug, Clone)]
@@ -41,24 +40,12 @@ impl Default for VRFrameData {
 }
 
 impl VRFrameData {
+    // Encodes the frame data with the versioned format described in vr_wire
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut vec = vec![0u8; mem::size_of::<VRFrameData>()];
-        unsafe {
-            ptr::copy_nonoverlapping(mem::transmute(self),
-                                    vec.as_mut_ptr(),
-                                    mem::size_of::<VRFrameData>());
-        }
-        vec
+        vr_wire::encode(|out| vr_wire::write_frame_data(out, self))
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> VRFrameData {
-        unsafe {
-            let mut result: VRFrameData = mem::uninitialized();
-            ptr::copy_nonoverlapping(bytes.as_ptr(),
-                                     mem::transmute(&mut result),
-                                     mem::size_of::<VRFrameData>());
-
-            result        
-        }
+    pub fn from_bytes(bytes: &[u8]) -> Result<VRFrameData, VRWireError> {
+        vr_wire::decode(bytes, |reader| vr_wire::read_frame_data(reader))
     }
-}
\ No newline at end of file
+}
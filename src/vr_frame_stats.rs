@@ -0,0 +1,24 @@
+// Frame timing statistics of a VRDisplay.
+// Used to monitor the rendering performance and to adapt the rendering quality.
+// Times are in milliseconds. Frame counters are cumulative.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+pub struct VRFrameStats {
+    // Index of the frame started by the last sync_poses call
+    pub frame_index: u64,
+
+    // Time between the last two sync_poses calls
+    pub frame_interval: f64,
+
+    // CPU time between the sync_poses call and the submit_frame call of the last submitted frame
+    pub cpu_frame_time: f64,
+
+    // Display refreshes where no new frame was ready, so a previous frame was presented again
+    pub missed_frames: u64,
+
+    // Frames reprojected by the compositor to match the latest head pose
+    pub reprojected_frames: u64,
+
+    // Refresh rate of the display in Hz
+    pub refresh_rate: f64
+}
@@ -1,13 +1,24 @@
-use std::sync::Arc;
-use std::cell::RefCell;
-use VRPose;
+use std::sync::{Arc, RwLock};
+use {VRPose, VRReferenceSpace};
+use vr_wire::{self, VRWireError};
 
-pub type VRGamepadPtr = Arc<RefCell<VRGamepad>>;
+// Shared handle to a gamepad. It can be sent to other threads.
+pub type VRGamepadPtr = Arc<RwLock<VRGamepad>>;
 
-pub trait VRGamepad {
+pub trait VRGamepad: Send + Sync {
     fn id(&self) -> u32;
     fn data(&self) -> VRGamepadData;
     fn state(&self) -> VRGamepadState;
+
+    // Returns the state with the pose predicted for target_time, a timestamp in milliseconds
+    // in the same time base as VRGamepadState::timestamp. The returned timestamp is target_time.
+    // Backends without native prediction extrapolate the latest pose using its velocities.
+    fn predicted_state(&self, target_time: f64) -> VRGamepadState;
+
+    // Selects the reference space of the poses returned by this gamepad
+    fn set_reference_space(&mut self, space: VRReferenceSpace);
+
+    fn reference_space(&self) -> VRReferenceSpace;
 }
 
 #[derive(Debug, Clone)]
@@ -34,6 +45,17 @@ impl Default for VRGamepadState {
      }
 }
 
+impl VRGamepadState {
+    // Encodes the state with the versioned format described in vr_wire
+    pub fn to_bytes(&self) -> Vec<u8> {
+        vr_wire::encode(|out| vr_wire::write_gamepad_state(out, self))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<VRGamepadState, VRWireError> {
+        vr_wire::decode(bytes, |reader| vr_wire::read_gamepad_state(reader))
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
 pub enum VRGamepadHand {
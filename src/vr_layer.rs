@@ -1,3 +1,5 @@
+use VRPose;
+
 // Data provided to a VRDisplay and presented in the HMD.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
@@ -12,10 +14,50 @@ pub struct VRLayer {
 
     // UVs defining the texture bounds to present to the eye in UV space: [x,y,w,h]
     // Defaults to [0.5, 0.0, 0.5, 1.0]
+    // Relative to the right eye texture when right_texture_id is set.
     pub right_bounds: [f32; 4],
 
-    // Hint with texture size
+    // Optional texture rendered only for the right eye.
+    // When set, texture_id is only presented to the left eye.
+    // Defaults to None, both eyes are presented from texture_id.
+    pub right_texture_id: Option<u32>,
+
+    // Hint with texture size. Both eye textures must have the same size.
     pub texture_size: Option<(u32, u32)>,
+
+    // Stacking order when several layers are submitted with VRDisplay::submit_layers.
+    // Layers with a higher order are composited over layers with a lower order.
+    // Layers with the same order keep the order in which they were submitted.
+    pub order: i32,
+
+    // Opacity used to blend the layer over the layers below it, from 0.0 to 1.0.
+    // Defaults to 1.0
+    pub opacity: f32,
+
+    // Optional depth buffer of the layer, used by backends that support positional reprojection.
+    pub depth: Option<VRLayerDepth>,
+
+    // Pose used to render the layer, usually the pose returned by synced_frame_data.
+    // Backends that reproject frames use it instead of the pose synced in sync_poses.
+    pub render_pose: Option<VRPose>,
+
+    // Timestamp of the VRFrameData used to render the layer, in milliseconds.
+    pub render_timestamp: Option<f64>,
+}
+
+// Depth texture rendered along with the color texture of a VRLayer
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+pub struct VRLayerDepth {
+    // Depth texture. Uses the texture bounds of the layer.
+    pub texture_id: u32,
+
+    // Optional depth texture for the right eye, used along with VRLayer::right_texture_id.
+    pub right_texture_id: Option<u32>,
+
+    // Depth range used in the projection matrices when the layer was rendered
+    pub near: f64,
+    pub far: f64,
 }
 
 impl Default for VRLayer {
@@ -24,7 +66,32 @@ impl Default for VRLayer {
             texture_id: 0,
             left_bounds: [0.0, 0.0, 0.5, 1.0],
             right_bounds: [0.5, 0.0, 0.5, 1.0],
-            texture_size : None
+            right_texture_id: None,
+            texture_size : None,
+            order: 0,
+            opacity: 1.0,
+            depth: None,
+            render_pose: None,
+            render_timestamp: None
         }
     }
-}
\ No newline at end of file
+}
+
+impl VRLayer {
+    // Texture presented to the left eye
+    pub fn left_texture(&self) -> u32 {
+        self.texture_id
+    }
+
+    // Texture presented to the right eye
+    pub fn right_texture(&self) -> u32 {
+        self.right_texture_id.unwrap_or(self.texture_id)
+    }
+}
+
+impl VRLayerDepth {
+    // Depth texture of the right eye
+    pub fn right_texture(&self) -> u32 {
+        self.right_texture_id.unwrap_or(self.texture_id)
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use VRDisplayPtr;
 use VREvent;
 use VRGamepadPtr;
+use VRReferenceSpace;
 use VRService;
 use VRServiceCreator;
 
@@ -16,12 +17,33 @@ use api::OpenVRServiceCreator;
 #[cfg(feature = "mock")]
 use api::MockServiceCreator;
 
+#[cfg(feature = "mock-file")]
+use api::MockDisplayDescription;
+#[cfg(any(feature = "mock-file", feature = "replay", all(unix, feature = "ipc")))]
+use std::path::Path;
+#[cfg(any(feature = "mock-file", feature = "replay", all(unix, feature = "ipc"), feature = "network"))]
+use VRError;
+
+#[cfg(feature = "replay")]
+use api::{ReplayControls, ReplayVRService};
+
+#[cfg(unix)]
+#[cfg(feature = "ipc")]
+use api::RemoteVRService;
+
+#[cfg(feature = "network")]
+use api::NetworkVRService;
+#[cfg(feature = "network")]
+use std::net::{SocketAddr, ToSocketAddrs};
+
 // Single entry point all the VRServices and displays
 pub struct VRServiceManager {
     initialized: bool,
     services: Vec<Box<VRService>>,
     displays: HashMap<u32, VRDisplayPtr>,
-    gamepads: HashMap<u32, VRGamepadPtr>
+    gamepads: HashMap<u32, VRGamepadPtr>,
+    // Reference space applied to all the displays and gamepads, including the ones found later
+    reference_space: Option<VRReferenceSpace>
 }
 
 impl Drop for VRServiceManager {
@@ -38,7 +60,8 @@ impl VRServiceManager {
             initialized: false,
             services: Vec::new(),
             displays: HashMap::new(),
-            gamepads: HashMap::new()
+            gamepads: HashMap::new(),
+            reference_space: None
         }
     }
 
@@ -66,6 +89,50 @@ impl VRServiceManager {
         self.register(creator.new_service());
     }
 
+    // Register a mock VR Service built from a JSON or TOML device description file
+    // Allows testing new headset profiles without recompiling
+    #[cfg(feature = "mock-file")]
+    pub fn register_mock_from_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), VRError> {
+        let description = try!(MockDisplayDescription::from_file(path).map_err(|e| VRError::Backend("Mock".into(), e)));
+        let service = try!(description.to_service().map_err(|e| VRError::Backend("Mock".into(), e)));
+        self.register(Box::new(service));
+        Ok(())
+    }
+
+    // Register a VR Service that plays back a file recorded with RecordingService
+    // Returns the playback controls of the replayed devices
+    #[cfg(feature = "replay")]
+    pub fn register_replay<P: AsRef<Path>>(&mut self, path: P) -> Result<ReplayControls, VRError> {
+        let mut service = ReplayVRService::new(path.as_ref().to_path_buf());
+        try!(service.initialize());
+        let controls = service.controls();
+        self.register(Box::new(service));
+        Ok(controls)
+    }
+
+    // Register a VR Service that exposes the devices of a RemoteVRServer listening at `path`
+    #[cfg(unix)]
+    #[cfg(feature = "ipc")]
+    pub fn register_remote<P: AsRef<Path>>(&mut self, path: P) -> Result<(), VRError> {
+        let mut service = RemoteVRService::new(path.as_ref().to_path_buf());
+        try!(service.initialize());
+        self.register(Box::new(service));
+        Ok(())
+    }
+
+    // Register a VR Service that listens on `address` for a device streaming its poses over TCP
+    // Returns the address devices must connect to
+    #[cfg(feature = "network")]
+    pub fn register_network<A: ToSocketAddrs>(&mut self, address: A) -> Result<SocketAddr, VRError> {
+        let address = try!(address.to_socket_addrs().ok().and_then(|mut addresses| addresses.next()).ok_or_else(|| {
+            VRError::Backend("Network".into(), "Invalid address".into())
+        }));
+        let mut service = NetworkVRService::new(address);
+        try!(service.initialize());
+        let local_address = service.local_address().unwrap();
+        self.register(Box::new(service));
+        Ok(local_address)
+    }
 
     // Register a new VR service
     pub fn register(&mut self, service: Box<VRService>) {
@@ -80,7 +147,7 @@ impl VRServiceManager {
 
         for service in &mut self.services {
             if let Err(msg) = service.initialize() {
-                error!("Error initializing VRService: {:?}", msg);
+                error!("Error initializing VRService: {}", msg);
             }
         }
         self.initialized = true;
@@ -93,7 +160,7 @@ impl VRServiceManager {
             result.push(display.clone());
         }
         // Sort by display_id to match service initialization order
-        result.sort_by(|a, b| a.borrow().id().cmp(&b.borrow().id()));
+        result.sort_by(|a, b| a.read().unwrap().id().cmp(&b.read().unwrap().id()));
         result
     }
 
@@ -104,7 +171,7 @@ impl VRServiceManager {
             result.push(gamepad.clone());
         }
         // Sort by gamepad_id to match service initialization order
-        result.sort_by(|a, b| a.borrow().id().cmp(&b.borrow().id()));
+        result.sort_by(|a, b| a.read().unwrap().id().cmp(&b.read().unwrap().id()));
         result
     }
 
@@ -112,6 +179,10 @@ impl VRServiceManager {
         self.displays.get(&display_id)
     }
 
+    pub fn get_gamepad(&self, gamepad_id: u32) -> Option<&VRGamepadPtr> {
+        self.gamepads.get(&gamepad_id)
+    }
+
     pub fn poll_events(&mut self) -> Vec<VREvent> {
         let mut events = Vec::new();
         for service in &mut self.services {
@@ -120,6 +191,17 @@ impl VRServiceManager {
         events
     }
 
+    // Selects the reference space of all the displays and gamepads
+    pub fn set_reference_space(&mut self, space: VRReferenceSpace) {
+        self.reference_space = Some(space);
+        for display in self.displays.values() {
+            display.write().unwrap().set_reference_space(space);
+        }
+        for gamepad in self.gamepads.values() {
+            gamepad.write().unwrap().set_reference_space(space);
+        }
+    }
+
     pub fn is_initialized(&self) -> bool {
         self.initialized
     }
@@ -133,8 +215,11 @@ impl VRServiceManager {
             let displays = service.fetch_displays();
             if let Ok(displays) = displays {
                 for display in displays {
-                    let key = display.borrow().id();
+                    let key = display.read().unwrap().id();
                     if !self.displays.contains_key(&key) {
+                        if let Some(space) = self.reference_space {
+                            display.write().unwrap().set_reference_space(space);
+                        }
                         self.displays.insert(key, display.clone());
                     }
                 }
@@ -149,8 +234,11 @@ impl VRServiceManager {
             let gamepads = service.fetch_gamepads();
             if let Ok(gamepads) = gamepads {
                 for gamepad in gamepads {
-                    let key = gamepad.borrow().id();
+                    let key = gamepad.read().unwrap().id();
                     if !self.gamepads.contains_key(&key) {
+                        if let Some(space) = self.reference_space {
+                            gamepad.write().unwrap().set_reference_space(space);
+                        }
                         self.gamepads.insert(key, gamepad.clone());
                     }
                 }
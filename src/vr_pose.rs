@@ -1,3 +1,5 @@
+use vr_wire::{self, VRWireError};
+
 // The VRPose struct represents a sensor’s state at a given timestamp.
 #[derive(Debug, Clone, Copy, Default)]
 #[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
@@ -25,4 +27,15 @@ pub struct VRPose {
     // Linear acceleration of the sensor given in radians per second squared.
     // May be None if the sensor is incapable of providing angular acceleration data.
     pub angular_acceleration: Option<[f32; 3]>,
-}
\ No newline at end of file
+}
+
+impl VRPose {
+    // Encodes the pose with the versioned format described in vr_wire
+    pub fn to_bytes(&self) -> Vec<u8> {
+        vr_wire::encode(|out| vr_wire::write_pose(out, self))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<VRPose, VRWireError> {
+        vr_wire::decode(bytes, |reader| vr_wire::read_pose(reader))
+    }
+}
@@ -0,0 +1,18 @@
+// Coordinate system of the poses returned by displays and gamepads
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+pub enum VRReferenceSpace {
+    // Origin at the seated zero pose, reset by VRDisplay::reset_pose. Default in WebVR.
+    Seated,
+    // Origin on the floor at the center of the play area.
+    // sitting_to_standing_transform is already applied to the poses.
+    Standing,
+    // Uncalibrated space of the tracking system. Backends without calibration use the seated space.
+    Raw
+}
+
+impl Default for VRReferenceSpace {
+    fn default() -> VRReferenceSpace {
+        VRReferenceSpace::Seated
+    }
+}
@@ -1,13 +1,14 @@
 use VRDisplayPtr;
 use VREvent;
+use VRError;
 use VRGamepadPtr;
 
 pub trait VRService: Send {
-    fn initialize(&mut self) -> Result<(), String>;
+    fn initialize(&mut self) -> Result<(), VRError>;
 
-    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, String>;
+    fn fetch_displays(&mut self) -> Result<Vec<VRDisplayPtr>, VRError>;
 
-    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, String>;
+    fn fetch_gamepads(&mut self) -> Result<Vec<VRGamepadPtr>, VRError>;
 
     fn is_available(&self) -> bool;
 
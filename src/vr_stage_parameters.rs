@@ -5,8 +5,28 @@
 pub struct VRStageParameters {
     // matrix that transforms the sitting-space view matrices of VRFrameData to standing-space.
     pub sitting_to_standing_transform: [f32; 16],
-    // Width of the play-area bounds in meters.
+    // Width of the play-area bounds in meters. 0.0 when the bounds are not available.
     pub size_x: f32,
-    // Depth of the play-area bounds in meters
-    pub size_z: f32
-}
\ No newline at end of file
+    // Depth of the play-area bounds in meters. 0.0 when the bounds are not available.
+    pub size_z: f32,
+    // Shape of the play-area bounds.
+    #[cfg_attr(feature = "serde-serialization", serde(default))]
+    pub bounds: VRStageBounds
+}
+
+// Boundary of the play area
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde-serialization", derive(Deserialize, Serialize))]
+pub enum VRStageBounds {
+    // The device has not reported the bounds yet. E.g. the room setup is not calibrated.
+    NotAvailable,
+    // Points of the boundary polygon in standing space, in meters.
+    // Points lie on the floor (y = 0.0) and are ordered around the play area.
+    Polygon(Vec<[f32; 3]>)
+}
+
+impl Default for VRStageBounds {
+    fn default() -> VRStageBounds {
+        VRStageBounds::NotAvailable
+    }
+}
@@ -0,0 +1,915 @@
+use {VRDisplayCapabilities, VRDisplayData, VRDisplayEvent, VRDisplayEventReason, VREvent, VREyeParameters,
+     VRFieldOfView, VRFrameData, VRFrameStats, VRGamepadButton, VRGamepadData, VRGamepadEvent, VRGamepadHand,
+     VRGamepadState, VRLayer, VRLayerDepth, VRPose, VRReferenceSpace, VRStageBounds, VRStageParameters};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+// Binary encoding of the values shared between processes. Also used by the session recordings.
+//
+// All values are little endian. A message starts with the u16 wire version followed by the payload:
+//   VRFrameData: f64 timestamp, left projection, left view, right projection and right view matrices
+//                (16 f32 each, column major), pose
+//   VRPose: position, linear velocity, linear acceleration, orientation (4 f32), angular velocity
+//           and angular acceleration (3 f32 each). Each value is stored as an optional value.
+//   VRGamepadState: u32 gamepad_id, connected, f64 timestamp, axes (u32 count + f64 values),
+//                   buttons (u32 count + pressed and touched flags), pose
+//   VRGamepadData: u32 display_id, name, u8 hand (0: unknown, 1: left, 2: right)
+//   VRDisplayData: u32 display_id, display name, connected, capabilities (has_position, has_orientation,
+//                  has_external_display, can_present, u64 max_layers), optional stage parameters,
+//                  left and right eye parameters
+//   VRStageParameters: sitting_to_standing_transform (16 f32), f32 size_x, f32 size_z,
+//                      optional bounds polygon (u32 count + f32 x, y, z points)
+//   VREyeParameters: offset (3 f32), u32 render_width, u32 render_height,
+//                    field of view (f64 up, right, down and left degrees)
+//   VRLayer: u32 texture_id, left and right bounds (4 f32 each), optional texture size (2 u32),
+//            i32 order, f32 opacity, optional u32 right_texture_id, optional depth (u32 texture_id,
+//            optional u32 right_texture_id, f64 near, f64 far), optional render pose,
+//            optional f64 render timestamp
+//   VRFrameStats: u64 frame index, f64 frame interval, f64 cpu frame time, u64 missed frames,
+//                 u64 reprojected frames, f64 refresh rate
+//   VRReferenceSpace: u8 space (0: seated, 1: standing, 2: raw)
+//   VREvent: u8 event kind + event payload (display data, display ids, gamepad data and state)
+//
+// Booleans are stored as an u8 with value 0 or 1.
+// Optional values are stored as a bool flag followed by the value when the flag is 1.
+// Strings are stored as a u32 length followed by the utf8 bytes.
+//
+// Messages are rejected when they are truncated, have trailing bytes or use an unknown version.
+//
+// Stream transports frame each message as a u32 payload length followed by the payload.
+//
+// Version history:
+//   1: initial version
+//   2: display_id prepended to VRDisplayData
+pub const WIRE_VERSION: u16 = 2;
+
+// Larger framed messages are rejected to avoid allocating corrupted lengths
+pub const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+const EVENT_DISPLAY_CONNECT: u8 = 1;
+const EVENT_DISPLAY_DISCONNECT: u8 = 2;
+const EVENT_DISPLAY_ACTIVATE: u8 = 3;
+const EVENT_DISPLAY_DEACTIVATE: u8 = 4;
+const EVENT_DISPLAY_CHANGE: u8 = 5;
+const EVENT_DISPLAY_BLUR: u8 = 6;
+const EVENT_DISPLAY_FOCUS: u8 = 7;
+const EVENT_DISPLAY_PRESENT_CHANGE: u8 = 8;
+const EVENT_DISPLAY_PAUSE: u8 = 9;
+const EVENT_DISPLAY_RESUME: u8 = 10;
+const EVENT_DISPLAY_EXIT: u8 = 11;
+const EVENT_GAMEPAD_CONNECT: u8 = 12;
+const EVENT_GAMEPAD_DISCONNECT: u8 = 13;
+
+// Errors returned when decoding a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum VRWireError {
+    // The message ends in the middle of a value
+    UnexpectedEnd,
+
+    // The message was encoded with a wire version not supported by this version of the crate
+    UnsupportedVersion(u16),
+
+    // A boolean or optional value flag other than 0 or 1
+    InvalidBoolean(u8),
+
+    // A string with invalid utf8 bytes
+    InvalidString,
+
+    // A value out of the range of an enumeration.
+    // params: name of the value, value
+    InvalidValue(&'static str, u32),
+
+    // Bytes left after the end of the message.
+    // params: number of bytes
+    TrailingBytes(usize)
+}
+
+impl fmt::Display for VRWireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VRWireError::UnsupportedVersion(version) => write!(f, "Unsupported wire version {}", version),
+            VRWireError::InvalidBoolean(value) => write!(f, "Invalid boolean value {}", value),
+            VRWireError::InvalidValue(name, value) => write!(f, "Invalid {} {}", name, value),
+            VRWireError::TrailingBytes(count) => write!(f, "{} unexpected bytes after the end of the message", count),
+            _ => f.write_str(self.description())
+        }
+    }
+}
+
+impl Error for VRWireError {
+    fn description(&self) -> &str {
+        match *self {
+            VRWireError::UnexpectedEnd => "Unexpected end of data",
+            VRWireError::UnsupportedVersion(_) => "Unsupported wire version",
+            VRWireError::InvalidBoolean(_) => "Invalid boolean value",
+            VRWireError::InvalidString => "Invalid UTF-8 string",
+            VRWireError::InvalidValue(..) => "Invalid enumeration value",
+            VRWireError::TrailingBytes(_) => "Unexpected bytes after the end of the message"
+        }
+    }
+}
+
+// Encodes a message with the current wire version
+pub fn encode<F>(write: F) -> Vec<u8> where F: FnOnce(&mut Vec<u8>) {
+    let mut out = Vec::new();
+    write_u16(&mut out, WIRE_VERSION);
+    write(&mut out);
+    out
+}
+
+// Decodes a message encoded with `encode`. The whole buffer must be used by the message.
+pub fn decode<T, F>(bytes: &[u8], read: F) -> Result<T, VRWireError>
+    where F: FnOnce(&mut WireReader) -> Result<T, VRWireError> {
+    let mut reader = WireReader::new(bytes);
+    let version = try!(reader.u16());
+    if version == 0 || version > WIRE_VERSION {
+        return Err(VRWireError::UnsupportedVersion(version));
+    }
+    let value = try!(read(&mut reader));
+    try!(reader.finish());
+    Ok(value)
+}
+
+// Writes a length prefixed message to a stream
+pub fn write_message<W: Write>(stream: &mut W, payload: &[u8]) -> io::Result<()> {
+    let mut message = Vec::with_capacity(payload.len() + 4);
+    write_u32(&mut message, payload.len() as u32);
+    message.extend_from_slice(payload);
+    try!(stream.write_all(&message));
+    stream.flush()
+}
+
+// Reads the payload of a length prefixed message from a stream
+pub fn read_message<R: Read>(stream: &mut R) -> io::Result<Vec<u8>> {
+    let mut header = [0u8; 4];
+    try!(stream.read_exact(&mut header));
+    let len = try!(WireReader::new(&header).u32().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Message too large: {} bytes", len)));
+    }
+    let mut payload = vec![0u8; len];
+    try!(stream.read_exact(&mut payload));
+    Ok(payload)
+}
+
+pub fn write_frame_data(out: &mut Vec<u8>, data: &VRFrameData) {
+    write_f64(out, data.timestamp);
+    write_f32_slice(out, &data.left_projection_matrix);
+    write_f32_slice(out, &data.left_view_matrix);
+    write_f32_slice(out, &data.right_projection_matrix);
+    write_f32_slice(out, &data.right_view_matrix);
+    write_pose(out, &data.pose);
+}
+
+pub fn read_frame_data(reader: &mut WireReader) -> Result<VRFrameData, VRWireError> {
+    let mut data = VRFrameData::default();
+    data.timestamp = try!(reader.f64());
+    try!(reader.f32_slice(&mut data.left_projection_matrix));
+    try!(reader.f32_slice(&mut data.left_view_matrix));
+    try!(reader.f32_slice(&mut data.right_projection_matrix));
+    try!(reader.f32_slice(&mut data.right_view_matrix));
+    data.pose = try!(read_pose(reader));
+    Ok(data)
+}
+
+pub fn write_pose(out: &mut Vec<u8>, pose: &VRPose) {
+    write_optional_vec3(out, &pose.position);
+    write_optional_vec3(out, &pose.linear_velocity);
+    write_optional_vec3(out, &pose.linear_acceleration);
+    match pose.orientation {
+        Some(ref orientation) => {
+            write_u8(out, 1);
+            write_f32_slice(out, orientation);
+        },
+        None => write_u8(out, 0)
+    }
+    write_optional_vec3(out, &pose.angular_velocity);
+    write_optional_vec3(out, &pose.angular_acceleration);
+}
+
+pub fn read_pose(reader: &mut WireReader) -> Result<VRPose, VRWireError> {
+    let mut pose = VRPose::default();
+    pose.position = try!(read_optional_vec3(reader));
+    pose.linear_velocity = try!(read_optional_vec3(reader));
+    pose.linear_acceleration = try!(read_optional_vec3(reader));
+    if try!(reader.bool()) {
+        let mut orientation = [0.0; 4];
+        try!(reader.f32_slice(&mut orientation));
+        pose.orientation = Some(orientation);
+    }
+    pose.angular_velocity = try!(read_optional_vec3(reader));
+    pose.angular_acceleration = try!(read_optional_vec3(reader));
+    Ok(pose)
+}
+
+pub fn write_gamepad_state(out: &mut Vec<u8>, state: &VRGamepadState) {
+    write_u32(out, state.gamepad_id);
+    write_bool(out, state.connected);
+    write_f64(out, state.timestamp);
+    write_u32(out, state.axes.len() as u32);
+    for axis in &state.axes {
+        write_f64(out, *axis);
+    }
+    write_u32(out, state.buttons.len() as u32);
+    for button in &state.buttons {
+        write_bool(out, button.pressed);
+        write_bool(out, button.touched);
+    }
+    write_pose(out, &state.pose);
+}
+
+pub fn read_gamepad_state(reader: &mut WireReader) -> Result<VRGamepadState, VRWireError> {
+    let mut state = VRGamepadState::default();
+    state.gamepad_id = try!(reader.u32());
+    state.connected = try!(reader.bool());
+    state.timestamp = try!(reader.f64());
+    let axis_count = try!(reader.u32());
+    for _ in 0..axis_count {
+        state.axes.push(try!(reader.f64()));
+    }
+    let button_count = try!(reader.u32());
+    for _ in 0..button_count {
+        state.buttons.push(VRGamepadButton {
+            pressed: try!(reader.bool()),
+            touched: try!(reader.bool())
+        });
+    }
+    state.pose = try!(read_pose(reader));
+    Ok(state)
+}
+
+pub fn write_display_data(out: &mut Vec<u8>, data: &VRDisplayData) {
+    write_u32(out, data.display_id);
+    write_string(out, &data.display_name);
+    write_bool(out, data.connected);
+    write_capabilities(out, &data.capabilities);
+    match data.stage_parameters {
+        Some(ref stage) => {
+            write_u8(out, 1);
+            write_stage_parameters(out, stage);
+        },
+        None => write_u8(out, 0)
+    }
+    write_eye_parameters(out, &data.left_eye_parameters);
+    write_eye_parameters(out, &data.right_eye_parameters);
+}
+
+pub fn read_display_data(reader: &mut WireReader) -> Result<VRDisplayData, VRWireError> {
+    let mut data = VRDisplayData::default();
+    data.display_id = try!(reader.u32());
+    data.display_name = try!(reader.string());
+    data.connected = try!(reader.bool());
+    data.capabilities = try!(read_capabilities(reader));
+    if try!(reader.bool()) {
+        data.stage_parameters = Some(try!(read_stage_parameters(reader)));
+    }
+    data.left_eye_parameters = try!(read_eye_parameters(reader));
+    data.right_eye_parameters = try!(read_eye_parameters(reader));
+    Ok(data)
+}
+
+pub fn write_capabilities(out: &mut Vec<u8>, capabilities: &VRDisplayCapabilities) {
+    write_bool(out, capabilities.has_position);
+    write_bool(out, capabilities.has_orientation);
+    write_bool(out, capabilities.has_external_display);
+    write_bool(out, capabilities.can_present);
+    write_u64(out, capabilities.max_layers);
+}
+
+pub fn read_capabilities(reader: &mut WireReader) -> Result<VRDisplayCapabilities, VRWireError> {
+    Ok(VRDisplayCapabilities {
+        has_position: try!(reader.bool()),
+        has_orientation: try!(reader.bool()),
+        has_external_display: try!(reader.bool()),
+        can_present: try!(reader.bool()),
+        max_layers: try!(reader.u64())
+    })
+}
+
+pub fn write_stage_parameters(out: &mut Vec<u8>, stage: &VRStageParameters) {
+    write_f32_slice(out, &stage.sitting_to_standing_transform);
+    write_f32(out, stage.size_x);
+    write_f32(out, stage.size_z);
+    match stage.bounds {
+        VRStageBounds::Polygon(ref points) => {
+            write_u8(out, 1);
+            write_u32(out, points.len() as u32);
+            for point in points {
+                write_f32_slice(out, point);
+            }
+        },
+        VRStageBounds::NotAvailable => write_u8(out, 0)
+    }
+}
+
+pub fn read_stage_parameters(reader: &mut WireReader) -> Result<VRStageParameters, VRWireError> {
+    let mut stage = VRStageParameters {
+        sitting_to_standing_transform: [0.0; 16],
+        size_x: 0.0,
+        size_z: 0.0,
+        bounds: VRStageBounds::NotAvailable
+    };
+    try!(reader.f32_slice(&mut stage.sitting_to_standing_transform));
+    stage.size_x = try!(reader.f32());
+    stage.size_z = try!(reader.f32());
+    if try!(reader.bool()) {
+        let count = try!(reader.u32());
+        let mut points = Vec::new();
+        for _ in 0..count {
+            let mut point = [0.0; 3];
+            try!(reader.f32_slice(&mut point));
+            points.push(point);
+        }
+        stage.bounds = VRStageBounds::Polygon(points);
+    }
+    Ok(stage)
+}
+
+pub fn write_event(out: &mut Vec<u8>, event: &VREvent) {
+    match *event {
+        VREvent::Display(ref event) => match *event {
+            VRDisplayEvent::Connect(ref data) => {
+                write_u8(out, EVENT_DISPLAY_CONNECT);
+                write_display_data(out, data);
+            },
+            VRDisplayEvent::Disconnect(id) => {
+                write_u8(out, EVENT_DISPLAY_DISCONNECT);
+                write_u32(out, id);
+            },
+            VRDisplayEvent::Activate(ref data, reason) => {
+                write_u8(out, EVENT_DISPLAY_ACTIVATE);
+                write_display_data(out, data);
+                write_reason(out, reason);
+            },
+            VRDisplayEvent::Deactivate(ref data, reason) => {
+                write_u8(out, EVENT_DISPLAY_DEACTIVATE);
+                write_display_data(out, data);
+                write_reason(out, reason);
+            },
+            VRDisplayEvent::Change(ref data) => {
+                write_u8(out, EVENT_DISPLAY_CHANGE);
+                write_display_data(out, data);
+            },
+            VRDisplayEvent::Blur(ref data) => {
+                write_u8(out, EVENT_DISPLAY_BLUR);
+                write_display_data(out, data);
+            },
+            VRDisplayEvent::Focus(ref data) => {
+                write_u8(out, EVENT_DISPLAY_FOCUS);
+                write_display_data(out, data);
+            },
+            VRDisplayEvent::PresentChange(ref data, presenting) => {
+                write_u8(out, EVENT_DISPLAY_PRESENT_CHANGE);
+                write_display_data(out, data);
+                write_bool(out, presenting);
+            },
+            VRDisplayEvent::Pause(id) => {
+                write_u8(out, EVENT_DISPLAY_PAUSE);
+                write_u32(out, id);
+            },
+            VRDisplayEvent::Resume(id) => {
+                write_u8(out, EVENT_DISPLAY_RESUME);
+                write_u32(out, id);
+            },
+            VRDisplayEvent::Exit(id) => {
+                write_u8(out, EVENT_DISPLAY_EXIT);
+                write_u32(out, id);
+            }
+        },
+        VREvent::Gamepad(ref event) => match *event {
+            VRGamepadEvent::Connect(ref data, ref state) => {
+                write_u8(out, EVENT_GAMEPAD_CONNECT);
+                write_gamepad_data(out, data);
+                write_gamepad_state(out, state);
+            },
+            VRGamepadEvent::Disconnect(id) => {
+                write_u8(out, EVENT_GAMEPAD_DISCONNECT);
+                write_u32(out, id);
+            }
+        }
+    }
+}
+
+pub fn read_event(reader: &mut WireReader) -> Result<VREvent, VRWireError> {
+    read_event_with(reader, read_display_data)
+}
+
+// Reads an event using `read_display` to read its display data.
+// Used to read events stored with older display data layouts.
+pub fn read_event_with<F>(reader: &mut WireReader, mut read_display: F) -> Result<VREvent, VRWireError>
+    where F: FnMut(&mut WireReader) -> Result<VRDisplayData, VRWireError> {
+    let kind = try!(reader.u8());
+    let event = match kind {
+        EVENT_DISPLAY_CONNECT => VRDisplayEvent::Connect(try!(read_display(reader))).into(),
+        EVENT_DISPLAY_DISCONNECT => VRDisplayEvent::Disconnect(try!(reader.u32())).into(),
+        EVENT_DISPLAY_ACTIVATE => {
+            VRDisplayEvent::Activate(try!(read_display(reader)), try!(read_reason(reader))).into()
+        },
+        EVENT_DISPLAY_DEACTIVATE => {
+            VRDisplayEvent::Deactivate(try!(read_display(reader)), try!(read_reason(reader))).into()
+        },
+        EVENT_DISPLAY_CHANGE => VRDisplayEvent::Change(try!(read_display(reader))).into(),
+        EVENT_DISPLAY_BLUR => VRDisplayEvent::Blur(try!(read_display(reader))).into(),
+        EVENT_DISPLAY_FOCUS => VRDisplayEvent::Focus(try!(read_display(reader))).into(),
+        EVENT_DISPLAY_PRESENT_CHANGE => {
+            VRDisplayEvent::PresentChange(try!(read_display(reader)), try!(reader.bool())).into()
+        },
+        EVENT_DISPLAY_PAUSE => VRDisplayEvent::Pause(try!(reader.u32())).into(),
+        EVENT_DISPLAY_RESUME => VRDisplayEvent::Resume(try!(reader.u32())).into(),
+        EVENT_DISPLAY_EXIT => VRDisplayEvent::Exit(try!(reader.u32())).into(),
+        EVENT_GAMEPAD_CONNECT => {
+            VRGamepadEvent::Connect(try!(read_gamepad_data(reader)), try!(read_gamepad_state(reader))).into()
+        },
+        EVENT_GAMEPAD_DISCONNECT => VRGamepadEvent::Disconnect(try!(reader.u32())).into(),
+        _ => return Err(VRWireError::InvalidValue("event kind", kind as u32))
+    };
+    Ok(event)
+}
+
+pub fn write_reason(out: &mut Vec<u8>, reason: VRDisplayEventReason) {
+    write_u8(out, match reason {
+        VRDisplayEventReason::Navigation => 0,
+        VRDisplayEventReason::Mounted => 1,
+        VRDisplayEventReason::Unmounted => 2
+    });
+}
+
+pub fn read_reason(reader: &mut WireReader) -> Result<VRDisplayEventReason, VRWireError> {
+    match try!(reader.u8()) {
+        0 => Ok(VRDisplayEventReason::Navigation),
+        1 => Ok(VRDisplayEventReason::Mounted),
+        2 => Ok(VRDisplayEventReason::Unmounted),
+        value => Err(VRWireError::InvalidValue("display event reason", value as u32))
+    }
+}
+
+pub fn write_gamepad_data(out: &mut Vec<u8>, data: &VRGamepadData) {
+    write_u32(out, data.display_id);
+    write_string(out, &data.name);
+    write_u8(out, match data.hand {
+        VRGamepadHand::Unknown => 0,
+        VRGamepadHand::Left => 1,
+        VRGamepadHand::Right => 2
+    });
+}
+
+pub fn read_gamepad_data(reader: &mut WireReader) -> Result<VRGamepadData, VRWireError> {
+    let display_id = try!(reader.u32());
+    let name = try!(reader.string());
+    let hand = match try!(reader.u8()) {
+        0 => VRGamepadHand::Unknown,
+        1 => VRGamepadHand::Left,
+        2 => VRGamepadHand::Right,
+        value => return Err(VRWireError::InvalidValue("gamepad hand", value as u32))
+    };
+    Ok(VRGamepadData {
+        display_id: display_id,
+        name: name,
+        hand: hand
+    })
+}
+
+pub fn write_eye_parameters(out: &mut Vec<u8>, eye: &VREyeParameters) {
+    write_f32_slice(out, &eye.offset);
+    write_u32(out, eye.render_width);
+    write_u32(out, eye.render_height);
+    write_f64(out, eye.field_of_view.up_degrees);
+    write_f64(out, eye.field_of_view.right_degrees);
+    write_f64(out, eye.field_of_view.down_degrees);
+    write_f64(out, eye.field_of_view.left_degrees);
+}
+
+pub fn read_eye_parameters(reader: &mut WireReader) -> Result<VREyeParameters, VRWireError> {
+    let mut eye = VREyeParameters::default();
+    try!(reader.f32_slice(&mut eye.offset));
+    eye.render_width = try!(reader.u32());
+    eye.render_height = try!(reader.u32());
+    eye.field_of_view = VRFieldOfView {
+        up_degrees: try!(reader.f64()),
+        right_degrees: try!(reader.f64()),
+        down_degrees: try!(reader.f64()),
+        left_degrees: try!(reader.f64())
+    };
+    Ok(eye)
+}
+
+pub fn write_frame_stats(out: &mut Vec<u8>, stats: &VRFrameStats) {
+    write_u64(out, stats.frame_index);
+    write_f64(out, stats.frame_interval);
+    write_f64(out, stats.cpu_frame_time);
+    write_u64(out, stats.missed_frames);
+    write_u64(out, stats.reprojected_frames);
+    write_f64(out, stats.refresh_rate);
+}
+
+pub fn read_frame_stats(reader: &mut WireReader) -> Result<VRFrameStats, VRWireError> {
+    Ok(VRFrameStats {
+        frame_index: try!(reader.u64()),
+        frame_interval: try!(reader.f64()),
+        cpu_frame_time: try!(reader.f64()),
+        missed_frames: try!(reader.u64()),
+        reprojected_frames: try!(reader.u64()),
+        refresh_rate: try!(reader.f64())
+    })
+}
+
+pub fn write_layer(out: &mut Vec<u8>, layer: &VRLayer) {
+    write_u32(out, layer.texture_id);
+    write_f32_slice(out, &layer.left_bounds);
+    write_f32_slice(out, &layer.right_bounds);
+    match layer.texture_size {
+        Some((width, height)) => {
+            write_u8(out, 1);
+            write_u32(out, width);
+            write_u32(out, height);
+        },
+        None => write_u8(out, 0)
+    }
+    write_u32(out, layer.order as u32);
+    write_f32(out, layer.opacity);
+    match layer.right_texture_id {
+        Some(texture_id) => {
+            write_u8(out, 1);
+            write_u32(out, texture_id);
+        },
+        None => write_u8(out, 0)
+    }
+    match layer.depth {
+        Some(ref depth) => {
+            write_u8(out, 1);
+            write_u32(out, depth.texture_id);
+            match depth.right_texture_id {
+                Some(texture_id) => {
+                    write_u8(out, 1);
+                    write_u32(out, texture_id);
+                },
+                None => write_u8(out, 0)
+            }
+            write_f64(out, depth.near);
+            write_f64(out, depth.far);
+        },
+        None => write_u8(out, 0)
+    }
+    match layer.render_pose {
+        Some(ref pose) => {
+            write_u8(out, 1);
+            write_pose(out, pose);
+        },
+        None => write_u8(out, 0)
+    }
+    match layer.render_timestamp {
+        Some(timestamp) => {
+            write_u8(out, 1);
+            write_f64(out, timestamp);
+        },
+        None => write_u8(out, 0)
+    }
+}
+
+pub fn read_layer(reader: &mut WireReader) -> Result<VRLayer, VRWireError> {
+    let mut layer = VRLayer::default();
+    layer.texture_id = try!(reader.u32());
+    try!(reader.f32_slice(&mut layer.left_bounds));
+    try!(reader.f32_slice(&mut layer.right_bounds));
+    if try!(reader.bool()) {
+        layer.texture_size = Some((try!(reader.u32()), try!(reader.u32())));
+    }
+    layer.order = try!(reader.u32()) as i32;
+    layer.opacity = try!(reader.f32());
+    if try!(reader.bool()) {
+        layer.right_texture_id = Some(try!(reader.u32()));
+    }
+    if try!(reader.bool()) {
+        let texture_id = try!(reader.u32());
+        let right_texture_id = if try!(reader.bool()) { Some(try!(reader.u32())) } else { None };
+        layer.depth = Some(VRLayerDepth {
+            texture_id: texture_id,
+            right_texture_id: right_texture_id,
+            near: try!(reader.f64()),
+            far: try!(reader.f64())
+        });
+    }
+    if try!(reader.bool()) {
+        layer.render_pose = Some(try!(read_pose(reader)));
+    }
+    if try!(reader.bool()) {
+        layer.render_timestamp = Some(try!(reader.f64()));
+    }
+    Ok(layer)
+}
+
+pub fn write_reference_space(out: &mut Vec<u8>, space: VRReferenceSpace) {
+    write_u8(out, match space {
+        VRReferenceSpace::Seated => 0,
+        VRReferenceSpace::Standing => 1,
+        VRReferenceSpace::Raw => 2
+    });
+}
+
+pub fn read_reference_space(reader: &mut WireReader) -> Result<VRReferenceSpace, VRWireError> {
+    match try!(reader.u8()) {
+        0 => Ok(VRReferenceSpace::Seated),
+        1 => Ok(VRReferenceSpace::Standing),
+        2 => Ok(VRReferenceSpace::Raw),
+        value => Err(VRWireError::InvalidValue("reference space", value as u32))
+    }
+}
+
+pub fn write_optional_vec3(out: &mut Vec<u8>, value: &Option<[f32; 3]>) {
+    match *value {
+        Some(ref v) => {
+            write_u8(out, 1);
+            write_f32_slice(out, v);
+        },
+        None => write_u8(out, 0)
+    }
+}
+
+pub fn read_optional_vec3(reader: &mut WireReader) -> Result<Option<[f32; 3]>, VRWireError> {
+    if try!(reader.bool()) {
+        let mut v = [0.0; 3];
+        try!(reader.f32_slice(&mut v));
+        Ok(Some(v))
+    } else {
+        Ok(None)
+    }
+}
+
+#[inline]
+pub fn write_u8(out: &mut Vec<u8>, value: u8) {
+    out.push(value);
+}
+
+#[inline]
+pub fn write_bool(out: &mut Vec<u8>, value: bool) {
+    out.push(value as u8);
+}
+
+#[inline]
+pub fn write_u16(out: &mut Vec<u8>, value: u16) {
+    out.push(value as u8);
+    out.push((value >> 8) as u8);
+}
+
+#[inline]
+pub fn write_u32(out: &mut Vec<u8>, value: u32) {
+    for i in 0..4 {
+        out.push((value >> (i * 8)) as u8);
+    }
+}
+
+#[inline]
+pub fn write_u64(out: &mut Vec<u8>, value: u64) {
+    for i in 0..8 {
+        out.push((value >> (i * 8)) as u8);
+    }
+}
+
+#[inline]
+pub fn write_f32(out: &mut Vec<u8>, value: f32) {
+    write_u32(out, value.to_bits());
+}
+
+#[inline]
+pub fn write_f64(out: &mut Vec<u8>, value: f64) {
+    write_u64(out, value.to_bits());
+}
+
+pub fn write_string(out: &mut Vec<u8>, value: &str) {
+    write_u32(out, value.len() as u32);
+    out.extend_from_slice(value.as_bytes());
+}
+
+pub fn write_f32_slice(out: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        write_f32(out, *value);
+    }
+}
+
+// Little endian reader with bounds checking
+pub struct WireReader<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl<'a> WireReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> WireReader<'a> {
+        WireReader {
+            bytes: bytes,
+            position: 0
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    // Number of bytes not read yet
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    // Fails when there are bytes not read yet
+    pub fn finish(&self) -> Result<(), VRWireError> {
+        match self.remaining() {
+            0 => Ok(()),
+            count => Err(VRWireError::TrailingBytes(count))
+        }
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8], VRWireError> {
+        if self.remaining() < count {
+            return Err(VRWireError::UnexpectedEnd);
+        }
+        let result = &self.bytes[self.position..self.position + count];
+        self.position += count;
+        Ok(result)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, VRWireError> {
+        Ok(try!(self.bytes(1))[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, VRWireError> {
+        match try!(self.u8()) {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(VRWireError::InvalidBoolean(value))
+        }
+    }
+
+    pub fn u16(&mut self) -> Result<u16, VRWireError> {
+        let bytes = try!(self.bytes(2));
+        Ok(bytes[0] as u16 | (bytes[1] as u16) << 8)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, VRWireError> {
+        let bytes = try!(self.bytes(4));
+        let mut value = 0u32;
+        for i in 0..4 {
+            value |= (bytes[i] as u32) << (i * 8);
+        }
+        Ok(value)
+    }
+
+    pub fn u64(&mut self) -> Result<u64, VRWireError> {
+        let bytes = try!(self.bytes(8));
+        let mut value = 0u64;
+        for i in 0..8 {
+            value |= (bytes[i] as u64) << (i * 8);
+        }
+        Ok(value)
+    }
+
+    pub fn f32(&mut self) -> Result<f32, VRWireError> {
+        Ok(f32::from_bits(try!(self.u32())))
+    }
+
+    pub fn f64(&mut self) -> Result<f64, VRWireError> {
+        Ok(f64::from_bits(try!(self.u64())))
+    }
+
+    pub fn string(&mut self) -> Result<String, VRWireError> {
+        let len = try!(self.u32()) as usize;
+        let bytes = try!(self.bytes(len));
+        String::from_utf8(bytes.to_vec()).map_err(|_| VRWireError::InvalidString)
+    }
+
+    pub fn f32_slice(&mut self, out: &mut [f32]) -> Result<(), VRWireError> {
+        for value in out.iter_mut() {
+            *value = try!(self.f32());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {VRFrameData, VRGamepadButton, VRGamepadState, VRPose};
+    use std::io::{Cursor, ErrorKind};
+    use super::*;
+
+    fn pose() -> VRPose {
+        let mut pose = VRPose::default();
+        pose.position = Some([0.1, 1.6, -0.3]);
+        pose.orientation = Some([0.0, 0.7071, 0.0, 0.7071]);
+        pose.angular_velocity = Some([0.5, 0.0, -0.25]);
+        pose
+    }
+
+    fn assert_pose_eq(a: &VRPose, b: &VRPose) {
+        assert_eq!(a.position, b.position);
+        assert_eq!(a.linear_velocity, b.linear_velocity);
+        assert_eq!(a.linear_acceleration, b.linear_acceleration);
+        assert_eq!(a.orientation, b.orientation);
+        assert_eq!(a.angular_velocity, b.angular_velocity);
+        assert_eq!(a.angular_acceleration, b.angular_acceleration);
+    }
+
+    fn frame_data() -> VRFrameData {
+        let mut data = VRFrameData::default();
+        data.timestamp = 1234.5;
+        for i in 0..16 {
+            data.left_projection_matrix[i] = i as f32;
+            data.left_view_matrix[i] = i as f32 * 0.5;
+            data.right_projection_matrix[i] = -(i as f32);
+            data.right_view_matrix[i] = i as f32 * 0.25;
+        }
+        data.pose = pose();
+        data
+    }
+
+    fn gamepad_state() -> VRGamepadState {
+        let mut state = VRGamepadState::default();
+        state.gamepad_id = 7;
+        state.connected = true;
+        state.timestamp = 99.25;
+        state.axes = vec![0.5, -1.0];
+        state.buttons = vec![VRGamepadButton { pressed: true, touched: true },
+                             VRGamepadButton { pressed: false, touched: true }];
+        state.pose = pose();
+        state
+    }
+
+    #[test]
+    fn frame_data_round_trip() {
+        let data = frame_data();
+        let decoded = VRFrameData::from_bytes(&data.to_bytes()).unwrap();
+        assert_eq!(decoded.timestamp, data.timestamp);
+        assert_eq!(decoded.left_projection_matrix, data.left_projection_matrix);
+        assert_eq!(decoded.left_view_matrix, data.left_view_matrix);
+        assert_eq!(decoded.right_projection_matrix, data.right_projection_matrix);
+        assert_eq!(decoded.right_view_matrix, data.right_view_matrix);
+        assert_pose_eq(&decoded.pose, &data.pose);
+    }
+
+    #[test]
+    fn pose_round_trip() {
+        let pose = pose();
+        assert!(pose.linear_velocity.is_none());
+        assert_pose_eq(&VRPose::from_bytes(&pose.to_bytes()).unwrap(), &pose);
+
+        let empty = VRPose::default();
+        assert_pose_eq(&VRPose::from_bytes(&empty.to_bytes()).unwrap(), &empty);
+    }
+
+    #[test]
+    fn gamepad_state_round_trip() {
+        let state = gamepad_state();
+        let decoded = VRGamepadState::from_bytes(&state.to_bytes()).unwrap();
+        assert_eq!(decoded.gamepad_id, state.gamepad_id);
+        assert_eq!(decoded.connected, state.connected);
+        assert_eq!(decoded.timestamp, state.timestamp);
+        assert_eq!(decoded.axes, state.axes);
+        assert_eq!(decoded.buttons.len(), state.buttons.len());
+        for (a, b) in decoded.buttons.iter().zip(&state.buttons) {
+            assert_eq!(a.pressed, b.pressed);
+            assert_eq!(a.touched, b.touched);
+        }
+        assert_pose_eq(&decoded.pose, &state.pose);
+    }
+
+    #[test]
+    fn truncated_messages() {
+        let bytes = frame_data().to_bytes();
+        for len in 0..bytes.len() {
+            assert_eq!(VRFrameData::from_bytes(&bytes[..len]).unwrap_err(), VRWireError::UnexpectedEnd);
+        }
+        let bytes = gamepad_state().to_bytes();
+        for len in 0..bytes.len() {
+            assert_eq!(VRGamepadState::from_bytes(&bytes[..len]).unwrap_err(), VRWireError::UnexpectedEnd);
+        }
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let mut bytes = pose().to_bytes();
+        bytes.extend_from_slice(&[0, 0, 0]);
+        assert_eq!(VRPose::from_bytes(&bytes).unwrap_err(), VRWireError::TrailingBytes(3));
+    }
+
+    #[test]
+    fn unsupported_versions() {
+        let bytes = pose().to_bytes();
+        for &version in &[0, WIRE_VERSION + 1] {
+            let mut message = Vec::new();
+            write_u16(&mut message, version);
+            message.extend_from_slice(&bytes[2..]);
+            assert_eq!(VRPose::from_bytes(&message).unwrap_err(), VRWireError::UnsupportedVersion(version));
+        }
+    }
+
+    #[test]
+    fn framed_messages() {
+        let payload = pose().to_bytes();
+        let mut stream = Vec::new();
+        write_message(&mut stream, &payload).unwrap();
+        assert_eq!(read_message(&mut Cursor::new(stream)).unwrap(), payload);
+
+        let mut stream = Vec::new();
+        write_u32(&mut stream, MAX_MESSAGE_SIZE as u32 + 1);
+        let error = read_message(&mut Cursor::new(stream)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,222 @@
+#![cfg(all(unix, feature = "ipc", feature = "mock"))]
+
+extern crate rust_webvr;
+
+use rust_webvr::*;
+use rust_webvr::api::*;
+use std::env;
+use std::fs;
+use std::io;
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[test]
+fn remote_service_end_to_end() {
+    let path = env::temp_dir().join(format!("rust-webvr-ipc-{}.sock", process::id()));
+
+    let mut mock = MockVRService::new();
+    let mock_display = mock.display();
+    let events = mock.event_queue();
+    let mut manager = VRServiceManager::new();
+    manager.register(Box::new(mock));
+
+    let server = Arc::new(RemoteVRServer::bind(&path, manager).unwrap());
+    let runner = server.clone();
+    let handle = thread::spawn(move || runner.run());
+
+    let mut service = RemoteVRService::new(path.clone());
+    service.initialize().unwrap();
+    let displays = service.fetch_displays().unwrap();
+    assert_eq!(displays.len(), 1);
+    let display = displays[0].clone();
+
+    // Display data is forwarded with the id of the local display
+    let local_data = mock_display.read().unwrap().data();
+    let data = display.read().unwrap().data();
+    let display_id = display.read().unwrap().id();
+    assert_eq!(data.display_id, display_id);
+    assert_eq!(data.display_name, local_data.display_name);
+    assert_eq!(data.left_eye_parameters.render_width, local_data.left_eye_parameters.render_width);
+
+    // Frame data
+    let local_frame = mock_display.read().unwrap().inmediate_frame_data(0.1, 100.0);
+    let frame = display.read().unwrap().inmediate_frame_data(0.1, 100.0);
+    assert_eq!(frame.pose.position, local_frame.pose.position);
+    assert_eq!(frame.left_projection_matrix, local_frame.left_projection_matrix);
+
+    // Submitted layers reach the server display
+    let mut layer = VRLayer::default();
+    layer.texture_id = 7;
+    layer.left_bounds = [0.0, 0.0, 0.25, 1.0];
+    display.write().unwrap().start_present();
+    display.read().unwrap().sync_poses();
+    display.write().unwrap().submit_frame(&layer);
+    let submitted = mock_display.read().unwrap().last_submitted_frame().unwrap();
+    assert_eq!(submitted.layer.texture_id, 7);
+    assert_eq!(submitted.layer.left_bounds, [0.0, 0.0, 0.25, 1.0]);
+
+    // Events use the ids of the local devices
+    events.push(VRDisplayEvent::Change(local_data.clone()));
+    events.push(VRDisplayEvent::Pause(local_data.display_id));
+    let polled = service.poll_events();
+    assert_eq!(polled.len(), 2);
+    match polled[0] {
+        VREvent::Display(VRDisplayEvent::Change(ref data)) => assert_eq!(data.display_id, display_id),
+        ref event => panic!("Unexpected event {:?}", event)
+    }
+    match polled[1] {
+        VREvent::Display(VRDisplayEvent::Pause(id)) => assert_eq!(id, display_id),
+        ref event => panic!("Unexpected event {:?}", event)
+    }
+
+    server.stop();
+    handle.join().unwrap();
+}
+
+#[test]
+fn remote_service_reconnects() {
+    let server_path = env::temp_dir().join(format!("rust-webvr-ipc-server-{}.sock", process::id()));
+    let proxy_path = env::temp_dir().join(format!("rust-webvr-ipc-proxy-{}.sock", process::id()));
+
+    let mut manager = VRServiceManager::new();
+    manager.register_mock();
+    let server = Arc::new(RemoteVRServer::bind(&server_path, manager).unwrap());
+    let runner = server.clone();
+    let handle = thread::spawn(move || runner.run());
+    let clients = start_proxy(&proxy_path, &server_path);
+
+    let mut service = RemoteVRService::new(proxy_path.clone());
+    service.initialize().unwrap();
+    assert_eq!(service.fetch_displays().unwrap().len(), 1);
+
+    // Close the idle connection from the server side
+    for client in clients.lock().unwrap().drain(..) {
+        client.shutdown(Shutdown::Both).unwrap();
+    }
+    assert_eq!(service.fetch_displays().unwrap().len(), 1);
+    assert_eq!(clients.lock().unwrap().len(), 1);
+
+    server.stop();
+    handle.join().unwrap();
+    let _ = fs::remove_file(&proxy_path);
+}
+
+// Forwards the connections accepted at `path` to the server listening at `server_path`.
+// Returns the accepted client connections, so they can be closed.
+fn start_proxy(path: &Path, server_path: &Path) -> Arc<Mutex<Vec<UnixStream>>> {
+    let listener = UnixListener::bind(path).unwrap();
+    let server_path = server_path.to_path_buf();
+    let clients = Arc::new(Mutex::new(Vec::new()));
+    let accepted = clients.clone();
+    thread::spawn(move || {
+        for client in listener.incoming() {
+            let client = client.unwrap();
+            let server = UnixStream::connect(&server_path).unwrap();
+            accepted.lock().unwrap().push(client.try_clone().unwrap());
+            forward(client.try_clone().unwrap(), server.try_clone().unwrap());
+            forward(server, client);
+        }
+    });
+    clients
+}
+
+fn forward(mut from: UnixStream, mut to: UnixStream) {
+    thread::spawn(move || {
+        let _ = io::copy(&mut from, &mut to);
+        let _ = to.shutdown(Shutdown::Both);
+    });
+}
+
+#[test]
+fn remote_server_socket_ownership() {
+    let path = env::temp_dir().join(format!("rust-webvr-ipc-owner-{}.sock", process::id()));
+
+    // Sockets left by a server that is no longer running are replaced
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+    let server = RemoteVRServer::bind(&path, VRServiceManager::new()).unwrap();
+
+    // Live sockets are not
+    assert!(RemoteVRServer::bind(&path, VRServiceManager::new()).is_err());
+
+    // A server only removes its own socket
+    fs::remove_file(&path).unwrap();
+    let listener = UnixListener::bind(&path).unwrap();
+    drop(server);
+    assert!(path.exists());
+    drop(listener);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn remote_events_reach_every_client() {
+    let path = env::temp_dir().join(format!("rust-webvr-ipc-events-{}.sock", process::id()));
+
+    let mut mock = MockVRService::new();
+    let mock_display = mock.display();
+    let events = mock.event_queue();
+    let mut manager = VRServiceManager::new();
+    manager.register(Box::new(mock));
+
+    let server = Arc::new(RemoteVRServer::bind(&path, manager).unwrap());
+    let runner = server.clone();
+    let handle = thread::spawn(move || runner.run());
+
+    let mut first = RemoteVRService::new(path.clone());
+    let mut second = RemoteVRService::new(path.clone());
+    first.initialize().unwrap();
+    second.initialize().unwrap();
+    let first_id = first.fetch_displays().unwrap()[0].read().unwrap().id();
+    let second_id = second.fetch_displays().unwrap()[0].read().unwrap().id();
+
+    let remote_id = mock_display.read().unwrap().data().display_id;
+    events.push(VRDisplayEvent::Pause(remote_id));
+    for &(ref service, display_id) in &[(&first, first_id), (&second, second_id)] {
+        let polled = service.poll_events();
+        assert_eq!(polled.len(), 1);
+        match polled[0] {
+            VREvent::Display(VRDisplayEvent::Pause(id)) => assert_eq!(id, display_id),
+            ref event => panic!("Unexpected event {:?}", event)
+        }
+        assert_eq!(service.poll_events().len(), 0);
+    }
+
+    server.stop();
+    handle.join().unwrap();
+}
+
+#[test]
+fn remote_reference_space_is_shared() {
+    let path = env::temp_dir().join(format!("rust-webvr-ipc-space-{}.sock", process::id()));
+
+    let mut mock = MockVRService::new();
+    mock.add_gamepad(VRGamepadHand::Right, 2, 2);
+    let mut manager = VRServiceManager::new();
+    manager.register(Box::new(mock));
+
+    let server = Arc::new(RemoteVRServer::bind(&path, manager).unwrap());
+    let runner = server.clone();
+    let handle = thread::spawn(move || runner.run());
+
+    let mut first = RemoteVRService::new(path.clone());
+    let mut second = RemoteVRService::new(path.clone());
+    first.initialize().unwrap();
+    second.initialize().unwrap();
+
+    let first_display = first.fetch_displays().unwrap()[0].clone();
+    let second_display = second.fetch_displays().unwrap()[0].clone();
+    first_display.write().unwrap().set_reference_space(VRReferenceSpace::Standing);
+    assert_eq!(second_display.read().unwrap().reference_space(), VRReferenceSpace::Standing);
+
+    let first_gamepad = first.fetch_gamepads().unwrap()[0].clone();
+    let second_gamepad = second.fetch_gamepads().unwrap()[0].clone();
+    first_gamepad.write().unwrap().set_reference_space(VRReferenceSpace::Standing);
+    assert_eq!(second_gamepad.read().unwrap().reference_space(), VRReferenceSpace::Standing);
+
+    server.stop();
+    handle.join().unwrap();
+}
@@ -0,0 +1,56 @@
+#![cfg(all(feature = "network", feature = "mock"))]
+
+extern crate rust_webvr;
+
+use rust_webvr::*;
+use rust_webvr::api::*;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn network_service_loopback() {
+    let mut service = NetworkVRService::new("127.0.0.1:0".parse().unwrap());
+    service.initialize().unwrap();
+    let address = service.local_address().unwrap();
+    assert_eq!(service.fetch_displays().unwrap().len(), 0);
+
+    let mut display_data = MockVRDisplay::new().read().unwrap().data();
+    display_data.display_name = "Network device".into();
+    let info = NetworkDeviceInfo {
+        display: display_data,
+        refresh_rate: 60.0,
+        gamepads: Vec::new()
+    };
+    let client = Arc::new(NetworkVRClient::connect(address, info).unwrap());
+
+    let displays = service.fetch_displays().unwrap();
+    assert_eq!(displays.len(), 1);
+    let display = displays[0].clone();
+    assert_eq!(display.read().unwrap().id(), client.display_id());
+    let data = display.read().unwrap().data();
+    assert_eq!(data.display_name, "Network device");
+    assert!(data.connected);
+
+    // sync_poses waits for the next pose of the device
+    let mut pose = VRPose::default();
+    pose.position = Some([0.1, 1.5, -0.3]);
+    pose.orientation = Some([0.0, 0.0, 0.0, 1.0]);
+    let sender = client.clone();
+    let streamer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        sender.send_pose(&pose).unwrap();
+    });
+    display.read().unwrap().sync_poses();
+    streamer.join().unwrap();
+    let frame = display.read().unwrap().synced_frame_data(0.1, 100.0);
+    assert_eq!(frame.pose.position, Some([0.1, 1.5, -0.3]));
+
+    // Submitted frames are acknowledged to the device
+    display.write().unwrap().submit_layers(&[VRLayer::default(), VRLayer::default()]);
+    let ack = client.wait_frame_done(Duration::from_secs(1)).unwrap();
+    assert_eq!(ack.frame_index, 1);
+    assert_eq!(ack.layer_count, 2);
+
+    drop(service);
+}
@@ -0,0 +1,128 @@
+#![cfg(all(feature = "replay", feature = "mock"))]
+
+extern crate rust_webvr;
+
+use rust_webvr::*;
+use rust_webvr::api::*;
+use rust_webvr::recording::*;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+// Recording output that can be read after the writer is done with it
+#[derive(Clone)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+const FRAMES: usize = 20;
+
+#[test]
+fn replay_reproduces_recording() {
+    let mut mock = MockVRService::new();
+    let mock_display = mock.display();
+    mock_display.write().unwrap().set_refresh_rate(500.0).unwrap();
+    mock_display.write().unwrap().set_motion(MockMotion::HeadBob {
+        position: [0.0, 1.6, 0.0],
+        amplitude: 0.1,
+        period: 0.02
+    });
+    let mock_gamepad = mock.add_gamepad(VRGamepadHand::Right, 2, 1);
+    let queue = mock.event_queue();
+
+    let buffer = SharedBuffer(Arc::new(Mutex::new(Vec::new())));
+    let writer = RecordingWriter::from_writer(buffer.clone()).unwrap();
+    let display = RecordingDisplay::new(mock.fetch_displays().unwrap()[0].clone(), writer.clone());
+    let gamepad = RecordingGamepad::new(mock.fetch_gamepads().unwrap()[0].clone(), writer.clone());
+    let display_id = display.read().unwrap().id();
+
+    // Frame data, gamepad state and events returned in each recorded frame
+    let mut frames = Vec::new();
+    let mut states = Vec::new();
+    let mut events = Vec::new();
+    for i in 0..FRAMES {
+        display.write().unwrap().sync_poses();
+        frames.push(display.read().unwrap().synced_frame_data(0.1, 100.0));
+        mock_gamepad.write().unwrap().set_button(0, i >= 5 && i < 10, i >= 3);
+        mock_gamepad.write().unwrap().set_axis(0, i as f64 / FRAMES as f64);
+        states.push(gamepad.read().unwrap().state());
+        display.write().unwrap().submit_frame(&VRLayer::default());
+
+        if i == 2 {
+            let data = gamepad.read().unwrap().data();
+            let state = gamepad.read().unwrap().state();
+            queue.push(VRGamepadEvent::Connect(data, state));
+        }
+        if i == 12 {
+            queue.push(VRDisplayEvent::Pause(display_id));
+        }
+        let polled = mock.poll_events();
+        for event in &polled {
+            writer.record(0, RecordedEvent::Event(event.clone()));
+        }
+        events.push(polled);
+    }
+    writer.flush().unwrap();
+
+    let recording = Recording::from_bytes(&buffer.0.lock().unwrap()).unwrap();
+    let mut service = ReplayVRService::from_recording(recording);
+    service.controls().set_as_fast_as_possible(true);
+    let displays = service.fetch_displays().unwrap();
+    let gamepads = service.fetch_gamepads().unwrap();
+    assert_eq!(displays.len(), 1);
+    assert_eq!(gamepads.len(), 1);
+    let replay_display = displays[0].clone();
+    let replay_gamepad = gamepads[0].clone();
+    let replay_display_id = replay_display.read().unwrap().id();
+    let replay_gamepad_id = replay_gamepad.read().unwrap().id();
+    assert_eq!(replay_gamepad.read().unwrap().data().display_id, replay_display_id);
+
+    for i in 0..FRAMES {
+        replay_display.write().unwrap().sync_poses();
+
+        let frame = replay_display.read().unwrap().synced_frame_data(0.1, 100.0);
+        assert_eq!(frame.timestamp, frames[i].timestamp);
+        assert_eq!(frame.pose.position, frames[i].pose.position);
+        assert_eq!(frame.pose.orientation, frames[i].pose.orientation);
+        assert_eq!(frame.left_view_matrix, frames[i].left_view_matrix);
+        assert_eq!(frame.right_view_matrix, frames[i].right_view_matrix);
+        assert_eq!(frame.left_projection_matrix, frames[i].left_projection_matrix);
+
+        let state = replay_gamepad.read().unwrap().state();
+        assert_eq!(state.gamepad_id, replay_gamepad_id);
+        assert_eq!(state.axes, states[i].axes);
+        assert_eq!(state.buttons.len(), states[i].buttons.len());
+        for (button, recorded) in state.buttons.iter().zip(&states[i].buttons) {
+            assert_eq!(button.pressed, recorded.pressed);
+            assert_eq!(button.touched, recorded.touched);
+        }
+
+        // Events are reproduced in the same frame, with the ids of the replayed devices
+        let polled = service.poll_events();
+        assert_eq!(polled.len(), events[i].len());
+        for (event, recorded) in polled.iter().zip(&events[i]) {
+            match (event, recorded) {
+                (&VREvent::Display(VRDisplayEvent::Pause(id)),
+                 &VREvent::Display(VRDisplayEvent::Pause(_))) => {
+                    assert_eq!(id, replay_display_id);
+                }
+                (&VREvent::Gamepad(VRGamepadEvent::Connect(ref data, ref state)),
+                 &VREvent::Gamepad(VRGamepadEvent::Connect(ref recorded_data, _))) => {
+                    assert_eq!(data.display_id, replay_display_id);
+                    assert_eq!(data.name, recorded_data.name);
+                    assert_eq!(state.gamepad_id, replay_gamepad_id);
+                }
+                _ => panic!("Unexpected event {:?}, recorded {:?}", event, recorded)
+            }
+        }
+    }
+    assert!(service.controls().is_finished());
+}